@@ -9,9 +9,10 @@ fn main() {
         .nth(1)
         .expect("Must provide a path to a las file");
     let mut reader = Reader::from_path(path).expect("Unable to open reader");
-    let npoints = reader
-        .points()
-        .map(|p| p.expect("Unable to read point"))
-        .count();
+    let mut npoints = 0;
+    for point in reader.points() {
+        let _ = point.expect("Unable to read point");
+        npoints += 1;
+    }
     println!("Number of points: {}", npoints);
 }
@@ -0,0 +1,272 @@
+use crate::OutputFormat;
+use las::{classification::ClassificationScheme, stats::Stats, Header, Reader, Vlr};
+use serde::Serialize;
+use std::{collections::BTreeMap, error::Error, path::PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The las or laz file.
+    path: PathBuf,
+
+    /// Read every point and print per-dimension statistics.
+    #[arg(long)]
+    stats: bool,
+
+    /// The output format.
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct Info {
+    path: PathBuf,
+    header: HeaderInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<Stats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    classifications_by_name: Option<BTreeMap<String, u64>>,
+    #[serde(skip)]
+    classification_scheme: ClassificationScheme,
+}
+
+#[derive(Debug, Serialize)]
+struct HeaderInfo {
+    version: String,
+    system_identifier: String,
+    generating_software: String,
+    file_source_id: u16,
+    guid: String,
+    date: Option<String>,
+    global_encoding: GlobalEncoding,
+    point_format: PointFormat,
+    transforms: [Transform; 3],
+    bounds: Bounds,
+    number_of_points: u64,
+    number_of_points_by_return: Vec<u64>,
+    vlrs: Vec<VlrInfo>,
+    evlrs: Vec<VlrInfo>,
+}
+
+#[derive(Debug, Serialize)]
+struct GlobalEncoding {
+    value: u16,
+    gps_time_type: String,
+    has_synthetic_return_numbers: bool,
+    has_wkt_crs: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct PointFormat {
+    id: u8,
+    length: u16,
+    extra_bytes: u16,
+    is_compressed: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Transform {
+    scale: f64,
+    offset: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Bounds {
+    min: [f64; 3],
+    max: [f64; 3],
+}
+
+#[derive(Debug, Serialize)]
+struct VlrInfo {
+    user_id: String,
+    record_id: u16,
+    description: String,
+    length: usize,
+}
+
+pub(crate) fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_path(&args.path)?;
+    let header = HeaderInfo::new(reader.header())?;
+    let classification_scheme = reader.header().classification_scheme()?;
+    let stats = if args.stats {
+        let mut stats = Stats::default();
+        for point in reader.points() {
+            stats.add(&point?);
+        }
+        Some(stats)
+    } else {
+        None
+    };
+    let info = Info {
+        path: args.path,
+        header,
+        classifications_by_name: stats
+            .as_ref()
+            .map(|stats| stats.classifications_by_name(&classification_scheme)),
+        stats,
+        classification_scheme,
+    };
+    match args.format {
+        OutputFormat::Text => print!("{}", info.to_text()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&info)?),
+    }
+    Ok(())
+}
+
+impl HeaderInfo {
+    fn new(header: &Header) -> Result<HeaderInfo, Box<dyn Error>> {
+        let format = header.point_format();
+        let transforms = header.transforms();
+        let bounds = header.bounds();
+        let max_return = if format.is_extended { 15 } else { 5 };
+        Ok(HeaderInfo {
+            version: header.version().to_string(),
+            system_identifier: header.system_identifier().to_string(),
+            generating_software: header.generating_software().to_string(),
+            file_source_id: header.file_source_id(),
+            guid: header.guid().to_string(),
+            date: header.date().map(|date| date.to_string()),
+            global_encoding: GlobalEncoding {
+                value: header.clone().into_raw()?.global_encoding,
+                gps_time_type: format!("{:?}", header.gps_time_type()),
+                has_synthetic_return_numbers: header.has_synthetic_return_numbers(),
+                has_wkt_crs: header.has_wkt_crs(),
+            },
+            point_format: PointFormat {
+                id: format.to_u8()?,
+                length: format.len(),
+                extra_bytes: format.extra_bytes,
+                is_compressed: format.is_compressed,
+            },
+            transforms: [transforms.x, transforms.y, transforms.z].map(|t| Transform {
+                scale: t.scale,
+                offset: t.offset,
+            }),
+            bounds: Bounds {
+                min: [bounds.min.x, bounds.min.y, bounds.min.z],
+                max: [bounds.max.x, bounds.max.y, bounds.max.z],
+            },
+            number_of_points: header.number_of_points(),
+            number_of_points_by_return: (1..=max_return)
+                .map(|n| header.number_of_points_by_return(n).unwrap_or(0))
+                .collect(),
+            vlrs: header.vlrs().iter().map(VlrInfo::new).collect(),
+            evlrs: header.evlrs().iter().map(VlrInfo::new).collect(),
+        })
+    }
+}
+
+impl VlrInfo {
+    fn new(vlr: &Vlr) -> VlrInfo {
+        VlrInfo {
+            user_id: vlr.user_id.clone(),
+            record_id: vlr.record_id,
+            description: vlr.description.clone(),
+            length: vlr.data.len(),
+        }
+    }
+}
+
+impl Info {
+    fn to_text(&self) -> String {
+        use std::fmt::Write;
+
+        let mut s = String::new();
+        let h = &self.header;
+        let _ = writeln!(s, "{}", self.path.display());
+        let _ = writeln!(s, "  version:                {}", h.version);
+        let _ = writeln!(s, "  system identifier:      {}", h.system_identifier);
+        let _ = writeln!(s, "  generating software:    {}", h.generating_software);
+        let _ = writeln!(s, "  file source id:         {}", h.file_source_id);
+        let _ = writeln!(s, "  guid:                   {}", h.guid);
+        let _ = writeln!(
+            s,
+            "  date:                   {}",
+            h.date.as_deref().unwrap_or("none")
+        );
+        let e = &h.global_encoding;
+        let _ = writeln!(
+            s,
+            "  global encoding:        {} (gps time: {}, synthetic return numbers: {}, wkt crs: {})",
+            e.value, e.gps_time_type, e.has_synthetic_return_numbers, e.has_wkt_crs
+        );
+        let f = &h.point_format;
+        let _ = writeln!(
+            s,
+            "  point format:           {} (length: {}, extra bytes: {}, compressed: {})",
+            f.id, f.length, f.extra_bytes, f.is_compressed
+        );
+        for (name, t) in ["x", "y", "z"].iter().zip(&h.transforms) {
+            let _ = writeln!(
+                s,
+                "  {} scale, offset:        {}, {}",
+                name, t.scale, t.offset
+            );
+        }
+        let _ = writeln!(
+            s,
+            "  min x, y, z:            {}, {}, {}",
+            h.bounds.min[0], h.bounds.min[1], h.bounds.min[2]
+        );
+        let _ = writeln!(
+            s,
+            "  max x, y, z:            {}, {}, {}",
+            h.bounds.max[0], h.bounds.max[1], h.bounds.max[2]
+        );
+        let _ = writeln!(s, "  number of points:       {}", h.number_of_points);
+        let by_return: Vec<String> = h
+            .number_of_points_by_return
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let _ = writeln!(s, "  points by return:       {}", by_return.join(" "));
+        for (name, vlrs) in [("vlrs", &h.vlrs), ("evlrs", &h.evlrs)] {
+            let _ = writeln!(s, "  {}: {}", name, vlrs.len());
+            for vlr in vlrs {
+                let _ = writeln!(
+                    s,
+                    "    {} {} ({} bytes) {}",
+                    vlr.user_id, vlr.record_id, vlr.length, vlr.description
+                );
+            }
+        }
+        if let Some(stats) = &self.stats {
+            let _ = writeln!(s, "statistics ({} points)", stats.number_of_points);
+            let _ = writeln!(
+                s,
+                "  {:<18} {:>16} {:>16} {:>16} {:>16}",
+                "dimension", "min", "max", "mean", "std dev"
+            );
+            for (dimension, d) in &stats.dimensions {
+                let _ = writeln!(
+                    s,
+                    "  {:<18} {:>16.3} {:>16.3} {:>16.3} {:>16.3}",
+                    dimension.name(),
+                    d.min,
+                    d.max,
+                    d.mean,
+                    d.std_dev()
+                );
+            }
+            let _ = writeln!(s, "  classifications:");
+            for (code, count) in &stats.classifications {
+                let name = self.classification_scheme.label(*code);
+                let _ = writeln!(s, "    {:>3} {:<24} {}", code, name, count);
+            }
+            let _ = writeln!(s, "  returns (number of returns: counts by return number):");
+            for (number_of_returns, row) in stats.returns.iter().enumerate() {
+                if row.iter().any(|&n| n > 0) {
+                    let last = row.iter().rposition(|&n| n > 0).unwrap_or(0);
+                    let counts: Vec<String> = row[..=last].iter().map(|n| n.to_string()).collect();
+                    let _ = writeln!(s, "    {:>2}: {}", number_of_returns, counts.join(" "));
+                }
+            }
+            if stats.scanner_channels[1..].iter().any(|&n| n > 0) {
+                let _ = writeln!(s, "  scanner channels: {:?}", stats.scanner_channels);
+            }
+            if let Some((min, max)) = stats.gps_time_range() {
+                let _ = writeln!(s, "  gps time range: {} to {}", min, max);
+            }
+        }
+        s
+    }
+}
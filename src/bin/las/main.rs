@@ -0,0 +1,50 @@
+//! Command-line utilities for las data.
+
+mod info;
+mod translate;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print information about a las file.
+    Info(info::Args),
+
+    /// Convert a las file to another version, point format, or compression, optionally filtering
+    /// its points and editing their attributes.
+    Translate(Box<translate::Args>),
+}
+
+/// How to print results.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+
+    /// JSON.
+    Json,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Info(args) => info::run(args),
+        Command::Translate(args) => translate::run(*args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use las::{
+    edit::Rule, filters::expression::Expression, point::Format, Builder, Point, Reader, Version,
+    Writer,
+};
+use std::{error::Error, path::PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct Args {
+    /// The las or laz file to read.
+    input: PathBuf,
+
+    /// The las or laz file to write.
+    ///
+    /// The output is laz compressed if its extension is `.laz`.
+    output: PathBuf,
+
+    /// The output version, e.g. `1.4`.
+    ///
+    /// If not provided, the input version is kept, or raised if the output point format needs it.
+    #[arg(long, value_parser = parse_version)]
+    version: Option<Version>,
+
+    /// The output point format.
+    #[arg(long)]
+    point_format: Option<u8>,
+
+    /// The output scales, either one value for all dimensions or three comma-separated values.
+    #[arg(long, value_delimiter = ',')]
+    scale: Vec<f64>,
+
+    /// The output offsets, as three comma-separated values.
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    offset: Vec<f64>,
+
+    /// Set the output coordinate reference system to this OGC well-known text, replacing any
+    /// GeoTIFF keys.
+    ///
+    /// Point formats 6 through 10 can't use GeoTIFF keys, so this is required when converting a
+    /// file with GeoTIFF keys to one of those formats.
+    #[arg(long)]
+    wkt: Option<String>,
+
+    /// Only keep points with these classifications.
+    #[arg(long, value_delimiter = ',')]
+    keep_classes: Vec<u8>,
+
+    /// Drop points with these classifications.
+    #[arg(long, value_delimiter = ',')]
+    drop_classes: Vec<u8>,
+
+    /// Only keep points inside `MINX,MINY,MAXX,MAXY` or `MINX,MINY,MINZ,MAXX,MAXY,MAXZ`.
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    crop: Vec<f64>,
+
+    /// Drop withheld points.
+    #[arg(long)]
+    drop_withheld: bool,
+
+    /// Only keep points that match this filter expression, e.g. `Z > 10 && Intensity < 200`.
+    #[arg(long = "where")]
+    expression: Option<String>,
+
+    /// Edit the kept points with this rule, e.g. `Classification = Ground where Z < 100`.
+    ///
+    /// Can be given more than once, and the rules are applied in order.
+    #[arg(long = "set")]
+    rules: Vec<String>,
+}
+
+/// Decides which points to write.
+#[derive(Debug)]
+struct Filter {
+    keep_classes: Vec<u8>,
+    drop_classes: Vec<u8>,
+    crop: Option<([f64; 3], [f64; 3])>,
+    drop_withheld: bool,
+    expression: Option<Expression>,
+}
+
+pub(crate) fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_path(&args.input)?;
+    let filter = Filter::new(&args, &reader)?;
+    let rules = args
+        .rules
+        .iter()
+        .map(|rule| Rule::with_header(rule, reader.header()))
+        .collect::<las::Result<Vec<_>>>()?;
+    let mut builder = Builder::from(reader.header().clone());
+    builder.vlrs.retain(|vlr| !las::laz::is_laszip_vlr(vlr));
+    if let Some(n) = args.point_format {
+        let mut format = Format::new(n)?;
+        format.extra_bytes = builder.point_format.extra_bytes;
+        builder.point_format = format;
+    }
+    match args.scale.as_slice() {
+        [] => {}
+        [scale] => {
+            builder.transforms.x.scale = *scale;
+            builder.transforms.y.scale = *scale;
+            builder.transforms.z.scale = *scale;
+        }
+        [x, y, z] => {
+            builder.transforms.x.scale = *x;
+            builder.transforms.y.scale = *y;
+            builder.transforms.z.scale = *z;
+        }
+        _ => return Err("--scale takes one or three values".into()),
+    }
+    match args.offset.as_slice() {
+        [] => {}
+        [x, y, z] => {
+            builder.transforms.x.offset = *x;
+            builder.transforms.y.offset = *y;
+            builder.transforms.z.offset = *z;
+        }
+        _ => return Err("--offset takes three values".into()),
+    }
+    if let Some(wkt) = &args.wkt {
+        builder.vlrs.retain(|vlr| vlr.user_id != las::crs::USER_ID);
+        builder.evlrs.retain(|vlr| vlr.user_id != las::crs::USER_ID);
+        builder.set_wkt(wkt);
+    }
+    if let Some(version) = args.version {
+        builder.version = version;
+    } else if let Some(version) = builder.minimum_supported_version() {
+        builder.version = builder.version.max(version);
+    }
+    let format = builder.point_format;
+    let mut writer = Writer::from_path(&args.output, builder.into_header()?)?;
+    for point in reader.points() {
+        let mut point = point?;
+        if filter.keep(&point) {
+            point.conform_to(&format);
+            for rule in &rules {
+                let _ = rule.apply(&mut point, &format)?;
+            }
+            writer.write_point(point)?;
+        }
+    }
+    writer.close()?;
+    Ok(())
+}
+
+impl Filter {
+    fn new(args: &Args, reader: &Reader) -> Result<Filter, Box<dyn Error>> {
+        let crop = match args.crop.as_slice() {
+            [] => None,
+            [min_x, min_y, max_x, max_y] => Some((
+                [*min_x, *min_y, f64::NEG_INFINITY],
+                [*max_x, *max_y, f64::INFINITY],
+            )),
+            [min_x, min_y, min_z, max_x, max_y, max_z] => {
+                Some(([*min_x, *min_y, *min_z], [*max_x, *max_y, *max_z]))
+            }
+            _ => return Err("--crop takes four or six values".into()),
+        };
+        let expression = args
+            .expression
+            .as_deref()
+            .map(|s| Expression::with_header(s, reader.header()))
+            .transpose()?;
+        Ok(Filter {
+            keep_classes: args.keep_classes.clone(),
+            drop_classes: args.drop_classes.clone(),
+            crop,
+            drop_withheld: args.drop_withheld,
+            expression,
+        })
+    }
+
+    fn keep(&self, point: &Point) -> bool {
+        let class = u8::from(point.classification);
+        if !self.keep_classes.is_empty() && !self.keep_classes.contains(&class) {
+            return false;
+        }
+        if self.drop_classes.contains(&class) {
+            return false;
+        }
+        if self.drop_withheld && point.is_withheld {
+            return false;
+        }
+        if let Some((min, max)) = self.crop {
+            let xyz = [point.x, point.y, point.z];
+            if (0..3).any(|i| xyz[i] < min[i] || xyz[i] > max[i]) {
+                return false;
+            }
+        }
+        self.expression
+            .as_ref()
+            .is_none_or(|expression| expression.matches(point))
+    }
+}
+
+fn parse_version(s: &str) -> Result<Version, String> {
+    let parse = |s: Option<&str>| s.and_then(|s| s.parse::<u8>().ok());
+    let mut iter = s.split('.');
+    match (parse(iter.next()), parse(iter.next()), iter.next()) {
+        (Some(major), Some(minor), None) => Ok(Version::new(major, minor)),
+        _ => Err(format!("invalid version, expected e.g. 1.4: {}", s)),
+    }
+}
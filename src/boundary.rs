@@ -0,0 +1,678 @@
+//! Two-dimensional boundaries of las data, e.g. for footprints and tile indices.
+//!
+//! A [Boundary] is either the convex hull of the points, which is quick to compute, or the outline
+//! of the cells of a grid that contain points, which follows concave edges and holes at the
+//! grid's resolution:
+//!
+//! ```
+//! use las::{boundary::{Boundary, Method}, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let hull = Boundary::from_reader(&mut reader, Method::Convex).unwrap();
+//! assert!(hull.to_wkt().starts_with("POLYGON (("));
+//!
+//! reader.seek(0).unwrap();
+//! let outline = Boundary::from_reader(&mut reader, Method::Grid(100.)).unwrap();
+//! assert!(outline.area() <= hull.area() + 100. * 100. * 106.);
+//! ```
+//!
+//! Boundaries can be written as WKT or GeoJSON geometries, and [tile_index] collects the
+//! boundaries of several files into a GeoJSON feature collection. Exterior rings are
+//! counter-clockwise and holes are clockwise, as GeoJSON recommends.
+
+use crate::{raster::Grid, Error, Reader, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+const HULL_BUFFER_LEN: usize = 1 << 16;
+
+/// A ring of grid vertices.
+type Ring = Vec<(i64, i64)>;
+
+/// How to compute a boundary.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Method {
+    /// The convex hull of the points.
+    #[default]
+    Convex,
+
+    /// The outline of the cells that contain points, on a grid with this resolution.
+    Grid(f64),
+}
+
+/// The polygons that bound some points.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Boundary {
+    /// The polygons, which are empty if there were too few points to bound an area.
+    pub polygons: Vec<Polygon>,
+}
+
+/// A polygon with optional holes.
+///
+/// Rings are not closed, i.e. the first coordinate is not repeated at the end.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    /// The counter-clockwise outer ring.
+    pub exterior: Vec<(f64, f64)>,
+
+    /// The clockwise inner rings.
+    pub holes: Vec<Vec<(f64, f64)>>,
+}
+
+/// The boundaries of several files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileIndex {
+    /// One footprint per file.
+    pub footprints: Vec<Footprint>,
+}
+
+/// The boundary of one file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Footprint {
+    /// The path to the file.
+    pub path: PathBuf,
+
+    /// The number of points in the file.
+    pub number_of_points: u64,
+
+    /// The file's boundary.
+    pub boundary: Boundary,
+}
+
+impl Boundary {
+    /// Computes the boundary of the reader's remaining points.
+    ///
+    /// Grid boundaries use a grid over the header's bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{boundary::{Boundary, Method}, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let boundary = Boundary::from_reader(&mut reader, Method::Grid(50.)).unwrap();
+    /// assert!(!boundary.polygons.is_empty());
+    /// ```
+    pub fn from_reader(reader: &mut Reader, method: Method) -> Result<Boundary> {
+        match method {
+            Method::Convex => {
+                let mut buffer = Vec::new();
+                for point in reader.points() {
+                    let point = point?;
+                    buffer.push((point.x, point.y));
+                    if buffer.len() >= HULL_BUFFER_LEN {
+                        buffer = hull(buffer);
+                    }
+                }
+                Ok(Boundary::convex_hull(buffer))
+            }
+            Method::Grid(resolution) => {
+                let grid = Grid::new(&reader.header().bounds(), resolution)?;
+                let mut occupied = vec![false; grid.len()];
+                for point in reader.points() {
+                    let point = point?;
+                    if let Some((column, row)) = grid.cell(point.x, point.y) {
+                        occupied[grid.index(column, row)] = true;
+                    }
+                }
+                Boundary::from_grid(&grid, &occupied)
+            }
+        }
+    }
+
+    /// Returns the convex hull of some coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::boundary::Boundary;
+    ///
+    /// let boundary = Boundary::convex_hull(vec![(0., 0.), (1., 0.), (0.5, 0.5), (0., 1.)]);
+    /// assert_eq!(vec![(0., 0.), (1., 0.), (0., 1.)], boundary.polygons[0].exterior);
+    /// assert!(Boundary::convex_hull(vec![(0., 0.), (1., 1.)]).polygons.is_empty());
+    /// ```
+    pub fn convex_hull<I: IntoIterator<Item = (f64, f64)>>(coordinates: I) -> Boundary {
+        let exterior = hull(coordinates.into_iter().collect());
+        if exterior.len() < 3 {
+            Boundary::default()
+        } else {
+            Boundary {
+                polygons: vec![Polygon {
+                    exterior,
+                    holes: Vec::new(),
+                }],
+            }
+        }
+    }
+
+    /// Returns the outline of the occupied cells of a grid.
+    ///
+    /// `occupied` has one entry per cell, in row-major order from the top row. Cells that only
+    /// touch at a corner are in separate polygons.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{boundary::Boundary, raster::Grid};
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 2., resolution: 1., columns: 2, rows: 2 };
+    /// let boundary = Boundary::from_grid(&grid, &[true, true, true, false]).unwrap();
+    /// assert_eq!(3., boundary.area());
+    /// assert_eq!(6, boundary.polygons[0].exterior.len());
+    /// ```
+    pub fn from_grid(grid: &Grid, occupied: &[bool]) -> Result<Boundary> {
+        if occupied.len() != grid.len() {
+            return Err(Error::InvalidRaster(format!(
+                "{} cells for a grid of {} cells",
+                occupied.len(),
+                grid.len()
+            )));
+        }
+        let is_occupied = |column: i64, row: i64| {
+            column >= 0
+                && row >= 0
+                && (column as usize) < grid.columns
+                && (row as usize) < grid.rows
+                && occupied[grid.index(column as usize, row as usize)]
+        };
+
+        // Vertices are (column, -row) so that y increases upwards, and every edge has an occupied
+        // cell on its left.
+        let mut edges: BTreeMap<(i64, i64), Vec<(i64, i64)>> = BTreeMap::new();
+        let mut add = |from: (i64, i64), to: (i64, i64)| edges.entry(from).or_default().push(to);
+        for row in 0..grid.rows as i64 {
+            for column in 0..grid.columns as i64 {
+                if !is_occupied(column, row) {
+                    continue;
+                }
+                let (left, right, top, bottom) = (column, column + 1, -row, -row - 1);
+                if !is_occupied(column, row + 1) {
+                    add((left, bottom), (right, bottom));
+                }
+                if !is_occupied(column + 1, row) {
+                    add((right, bottom), (right, top));
+                }
+                if !is_occupied(column, row - 1) {
+                    add((right, top), (left, top));
+                }
+                if !is_occupied(column - 1, row) {
+                    add((left, top), (left, bottom));
+                }
+            }
+        }
+
+        let mut exteriors = Vec::new();
+        let mut holes = Vec::new();
+        for ring in trace(edges) {
+            let ring = simplify(ring);
+            let area = signed_area(&ring);
+            if area > 0. {
+                exteriors.push((ring, area));
+            } else if area < 0. {
+                holes.push(ring);
+            }
+        }
+
+        let mut polygons: Vec<(Ring, f64, Vec<Ring>)> = exteriors
+            .into_iter()
+            .map(|(ring, area)| (ring, area, Vec::new()))
+            .collect();
+        for hole in holes {
+            // The center of the empty cell on the right of the hole's first edge.
+            let (a, b) = (hole[0], hole[1]);
+            let (dx, dy) = ((b.0 - a.0).signum() as f64, (b.1 - a.1).signum() as f64);
+            let inside = (
+                a.0 as f64 + 0.5 * dx + 0.5 * dy,
+                a.1 as f64 + 0.5 * dy - 0.5 * dx,
+            );
+            if let Some(polygon) = polygons
+                .iter_mut()
+                .filter(|(ring, _, _)| contains(ring, inside))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+            {
+                polygon.2.push(hole);
+            }
+        }
+
+        let to_world = |ring: Ring| {
+            ring.into_iter()
+                .map(|(i, j)| {
+                    (
+                        grid.min_x + i as f64 * grid.resolution,
+                        grid.max_y + j as f64 * grid.resolution,
+                    )
+                })
+                .collect()
+        };
+        Ok(Boundary {
+            polygons: polygons
+                .into_iter()
+                .map(|(exterior, _, holes)| Polygon {
+                    exterior: to_world(exterior),
+                    holes: holes.into_iter().map(to_world).collect(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the area inside this boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::boundary::Boundary;
+    ///
+    /// let boundary = Boundary::convex_hull(vec![(0., 0.), (2., 0.), (2., 2.), (0., 2.)]);
+    /// assert_eq!(4., boundary.area());
+    /// ```
+    pub fn area(&self) -> f64 {
+        self.polygons
+            .iter()
+            .map(|polygon| {
+                ring_area(&polygon.exterior).abs()
+                    - polygon
+                        .holes
+                        .iter()
+                        .map(|hole| ring_area(hole).abs())
+                        .sum::<f64>()
+            })
+            .sum()
+    }
+
+    /// Returns this boundary as a WKT polygon, multipolygon if there's more than one polygon.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::boundary::Boundary;
+    ///
+    /// let boundary = Boundary::convex_hull(vec![(0., 0.), (1., 0.), (0., 1.)]);
+    /// assert_eq!("POLYGON ((0 0, 1 0, 0 1, 0 0))", boundary.to_wkt());
+    /// assert_eq!("POLYGON EMPTY", Boundary::default().to_wkt());
+    /// ```
+    pub fn to_wkt(&self) -> String {
+        let ring = |ring: &[(f64, f64)]| {
+            let coordinates: Vec<String> = ring
+                .iter()
+                .chain(ring.first())
+                .map(|(x, y)| format!("{} {}", x, y))
+                .collect();
+            format!("({})", coordinates.join(", "))
+        };
+        let polygon = |polygon: &Polygon| {
+            let rings: Vec<String> = std::iter::once(&polygon.exterior)
+                .chain(&polygon.holes)
+                .map(|r| ring(r))
+                .collect();
+            format!("({})", rings.join(", "))
+        };
+        match self.polygons.as_slice() {
+            [] => "POLYGON EMPTY".to_string(),
+            [one] => format!("POLYGON {}", polygon(one)),
+            many => {
+                let polygons: Vec<String> = many.iter().map(polygon).collect();
+                format!("MULTIPOLYGON ({})", polygons.join(", "))
+            }
+        }
+    }
+
+    /// Returns this boundary as a GeoJSON geometry, a multipolygon if there's more than one
+    /// polygon.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::boundary::Boundary;
+    ///
+    /// let boundary = Boundary::convex_hull(vec![(0., 0.), (1., 0.), (0., 1.)]);
+    /// assert_eq!(
+    ///     r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[0,1],[0,0]]]}"#,
+    ///     boundary.to_geojson()
+    /// );
+    /// ```
+    pub fn to_geojson(&self) -> String {
+        let ring = |ring: &[(f64, f64)]| {
+            let coordinates: Vec<String> = ring
+                .iter()
+                .chain(ring.first())
+                .map(|(x, y)| format!("[{},{}]", x, y))
+                .collect();
+            format!("[{}]", coordinates.join(","))
+        };
+        let polygon = |polygon: &Polygon| {
+            let rings: Vec<String> = std::iter::once(&polygon.exterior)
+                .chain(&polygon.holes)
+                .map(|r| ring(r))
+                .collect();
+            format!("[{}]", rings.join(","))
+        };
+        match self.polygons.as_slice() {
+            [one] => format!(r#"{{"type":"Polygon","coordinates":{}}}"#, polygon(one)),
+            many => {
+                let polygons: Vec<String> = many.iter().map(polygon).collect();
+                format!(
+                    r#"{{"type":"MultiPolygon","coordinates":[{}]}}"#,
+                    polygons.join(",")
+                )
+            }
+        }
+    }
+}
+
+impl TileIndex {
+    /// Returns this tile index as a GeoJSON feature collection.
+    ///
+    /// Each feature has the file's `path` and `number_of_points` as properties.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::boundary::{self, Method};
+    ///
+    /// let index = boundary::tile_index(["tests/data/autzen.las"], Method::Convex).unwrap();
+    /// let geojson = index.to_geojson();
+    /// assert!(geojson.starts_with(r#"{"type":"FeatureCollection","features":[{"#));
+    /// assert!(geojson.contains(r#""path":"tests/data/autzen.las""#));
+    /// ```
+    pub fn to_geojson(&self) -> String {
+        let features: Vec<String> = self
+            .footprints
+            .iter()
+            .map(|footprint| {
+                format!(
+                    r#"{{"type":"Feature","properties":{{"path":{},"number_of_points":{}}},"geometry":{}}}"#,
+                    json_string(&footprint.path.to_string_lossy()),
+                    footprint.number_of_points,
+                    footprint.boundary.to_geojson()
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+            features.join(",")
+        )
+    }
+}
+
+/// Computes the boundary of each file.
+///
+/// # Examples
+///
+/// ```
+/// use las::boundary::{self, Method};
+///
+/// let index = boundary::tile_index(["tests/data/autzen.las"], Method::Grid(100.)).unwrap();
+/// assert_eq!(106, index.footprints[0].number_of_points);
+/// ```
+pub fn tile_index<I, P>(paths: I, method: Method) -> Result<TileIndex>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut footprints = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let mut reader = Reader::from_path(path)?;
+        let number_of_points = reader.header().number_of_points();
+        let boundary = Boundary::from_reader(&mut reader, method)?;
+        footprints.push(Footprint {
+            path: path.to_path_buf(),
+            number_of_points,
+            boundary,
+        });
+    }
+    Ok(TileIndex { footprints })
+}
+
+/// Andrew's monotone chain, counter-clockwise without collinear points.
+fn hull(mut coordinates: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    coordinates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    coordinates.dedup();
+    if coordinates.len() < 3 {
+        return coordinates;
+    }
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut lower: Vec<(f64, f64)> = Vec::new();
+    for &c in &coordinates {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], c) <= 0. {
+            let _ = lower.pop();
+        }
+        lower.push(c);
+    }
+    let mut upper: Vec<(f64, f64)> = Vec::new();
+    for &c in coordinates.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], c) <= 0. {
+            let _ = upper.pop();
+        }
+        upper.push(c);
+    }
+    let _ = lower.pop();
+    let _ = upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+/// Follows edges into closed rings, turning left where two rings touch at a corner.
+fn trace(mut edges: BTreeMap<(i64, i64), Vec<(i64, i64)>>) -> Vec<Ring> {
+    fn take(
+        edges: &mut BTreeMap<(i64, i64), Vec<(i64, i64)>>,
+        from: (i64, i64),
+        preferred: &[(i64, i64)],
+    ) -> Option<(i64, i64)> {
+        let ends = edges.get_mut(&from)?;
+        let index = preferred
+            .iter()
+            .find_map(|end| ends.iter().position(|e| e == end))
+            .unwrap_or(0);
+        let end = ends.swap_remove(index);
+        if ends.is_empty() {
+            let _ = edges.remove(&from);
+        }
+        Some(end)
+    }
+
+    let mut rings = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut walk = vec![start];
+        let (mut previous, mut current) =
+            (start, take(&mut edges, start, &[]).expect("edge exists"));
+        while current != start {
+            walk.push(current);
+            let (dx, dy) = (current.0 - previous.0, current.1 - previous.1);
+            let preferred = [
+                (current.0 - dy, current.1 + dx),
+                (current.0 + dx, current.1 + dy),
+                (current.0 + dy, current.1 - dx),
+            ];
+            let next = take(&mut edges, current, &preferred).expect("rings are closed");
+            previous = current;
+            current = next;
+        }
+
+        // Split the walk wherever it revisits a vertex.
+        let mut stack: Vec<(i64, i64)> = Vec::new();
+        let mut positions: HashMap<(i64, i64), usize> = HashMap::new();
+        for vertex in walk {
+            if let Some(&position) = positions.get(&vertex) {
+                let ring: Vec<_> = stack.drain(position..).collect();
+                for v in &ring {
+                    let _ = positions.remove(v);
+                }
+                rings.push(ring);
+            }
+            let _ = positions.insert(vertex, stack.len());
+            stack.push(vertex);
+        }
+        rings.push(stack);
+    }
+    rings
+}
+
+fn simplify(ring: Ring) -> Ring {
+    let n = ring.len();
+    (0..n)
+        .filter(|&i| {
+            let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            (b.0 - a.0) * (c.1 - b.1) != (b.1 - a.1) * (c.0 - b.0)
+        })
+        .map(|i| ring[i])
+        .collect()
+}
+
+fn signed_area(ring: &[(i64, i64)]) -> f64 {
+    let n = ring.len();
+    let twice: i64 = (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    twice as f64 / 2.
+}
+
+fn ring_area(ring: &[(f64, f64)]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+fn contains(ring: &[(i64, i64)], (x, y): (f64, f64)) -> bool {
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (ring[i], ring[(i + 1) % n]);
+        let (ax, ay, bx, by) = (a.0 as f64, a.1 as f64, b.0 as f64, b.1 as f64);
+        if (ay > y) != (by > y) && x < ax + (y - ay) / (by - ay) * (bx - ax) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", u32::from(c));
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(columns: usize, rows: usize) -> Grid {
+        Grid {
+            min_x: 0.,
+            max_y: rows as f64,
+            resolution: 1.,
+            columns,
+            rows,
+        }
+    }
+
+    #[test]
+    fn hull_is_counter_clockwise() {
+        let boundary = Boundary::convex_hull(vec![
+            (0., 0.),
+            (2., 0.),
+            (1., 0.),
+            (2., 2.),
+            (1., 1.),
+            (0., 2.),
+        ]);
+        assert_eq!(
+            vec![(0., 0.), (2., 0.), (2., 2.), (0., 2.)],
+            boundary.polygons[0].exterior
+        );
+        assert!(ring_area(&boundary.polygons[0].exterior) > 0.);
+    }
+
+    #[test]
+    fn grid_hole() {
+        #[rustfmt::skip]
+        let occupied = [
+            true, true, true,
+            true, false, true,
+            true, true, true,
+        ];
+        let boundary = Boundary::from_grid(&grid(3, 3), &occupied).unwrap();
+        assert_eq!(1, boundary.polygons.len());
+        let polygon = &boundary.polygons[0];
+        assert_eq!(4, polygon.exterior.len());
+        assert_eq!(1, polygon.holes.len());
+        assert!(ring_area(&polygon.holes[0]) < 0.);
+        assert_eq!(8., boundary.area());
+        assert_eq!(
+            "POLYGON ((0 0, 3 0, 3 3, 0 3, 0 0), (1 1, 1 2, 2 2, 2 1, 1 1))",
+            boundary.to_wkt()
+        );
+    }
+
+    #[test]
+    fn grid_diagonal() {
+        #[rustfmt::skip]
+        let occupied = [
+            true, false,
+            false, true,
+        ];
+        let boundary = Boundary::from_grid(&grid(2, 2), &occupied).unwrap();
+        assert_eq!(2, boundary.polygons.len());
+        assert_eq!(2., boundary.area());
+        assert!(boundary.to_wkt().starts_with("MULTIPOLYGON ((("));
+        assert!(boundary
+            .to_geojson()
+            .starts_with(r#"{"type":"MultiPolygon","coordinates":[[[["#));
+    }
+
+    #[test]
+    fn grid_island_in_hole() {
+        let mut occupied = vec![true; 25];
+        for (column, row) in [
+            (1, 1),
+            (2, 1),
+            (3, 1),
+            (1, 2),
+            (3, 2),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+        ] {
+            occupied[row * 5 + column] = false;
+        }
+        let boundary = Boundary::from_grid(&grid(5, 5), &occupied).unwrap();
+        assert_eq!(2, boundary.polygons.len());
+        assert_eq!(17., boundary.area());
+        let holes: usize = boundary.polygons.iter().map(|p| p.holes.len()).sum();
+        assert_eq!(1, holes);
+    }
+
+    #[test]
+    fn grid_len_mismatch() {
+        assert!(Boundary::from_grid(&grid(2, 2), &[true]).is_err());
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(r#""a\"b\\c\u000a""#, json_string("a\"b\\c\n"));
+    }
+}
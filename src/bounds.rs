@@ -1,8 +1,8 @@
 use crate::{transform::RoundingMode, Point, Result, Transform, Vector};
-use std::f64;
 
 /// Minimum and maximum bounds in three dimensions.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
     /// The minimum values.
     pub min: Vector<f64>,
@@ -44,6 +44,61 @@ impl Bounds {
         }
     }
 
+    /// Returns the smallest bounds that contain both these bounds and the other bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Vector};
+    ///
+    /// let a = Bounds { min: Vector { x: 0., y: 0., z: 0. }, max: Vector { x: 1., y: 1., z: 1. } };
+    /// let b = Bounds { min: Vector { x: -1., y: 0., z: 0. }, max: Vector { x: 0., y: 2., z: 0. } };
+    /// let union = a.union(&b);
+    /// assert_eq!(-1., union.min.x);
+    /// assert_eq!(2., union.max.y);
+    /// assert_eq!(a, a.union(&Bounds::default()));
+    /// ```
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Vector {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+                z: self.min.z.min(other.min.z),
+            },
+            max: Vector {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+                z: self.max.z.max(other.max.z),
+            },
+        }
+    }
+
+    /// Returns transforms that can represent every point within these bounds.
+    ///
+    /// See [Transform::fit] for how the scale and offset are chosen for each dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Vector};
+    ///
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 500_000., y: 5_000_000., z: 0. },
+    ///     max: Vector { x: 501_000., y: 5_001_000., z: 100. },
+    /// };
+    /// let precision = Vector { x: 0.01, y: 0.01, z: 0.01 };
+    /// let transforms = bounds.fit_transforms(&precision).unwrap();
+    /// assert_eq!(500_500., transforms.x.offset);
+    /// assert_eq!(0.01, transforms.x.scale);
+    /// ```
+    pub fn fit_transforms(&self, precision: &Vector<f64>) -> Result<Vector<Transform>> {
+        Ok(Vector {
+            x: Transform::fit(self.min.x, self.max.x, precision.x)?,
+            y: Transform::fit(self.min.y, self.max.y, precision.y)?,
+            z: Transform::fit(self.min.z, self.max.z, precision.z)?,
+        })
+    }
+
     /// Transform the bounds to be compatible with the chosen transform. Otherwise, points may lay outside of the bounding box due to floating-point issues.
     ///
     /// # Example
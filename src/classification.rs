@@ -0,0 +1,315 @@
+//! Name classification codes.
+//!
+//! The ASPRS standard names classification codes 0 through 18. Codes 64 through 255 are free for
+//! users to define, and their names can be stored in the Classification Lookup vlr (`LASF_Spec`,
+//! record id 0). A [ClassificationScheme] combines both:
+//!
+//! ```
+//! use las::{classification::ClassificationScheme, Builder};
+//!
+//! let mut scheme = ClassificationScheme::default();
+//! scheme.set_name(64, "Power Line").unwrap();
+//! let mut builder = Builder::default();
+//! builder.vlrs.push(scheme.to_vlr().unwrap());
+//! let header = builder.into_header().unwrap();
+//!
+//! let scheme = header.classification_scheme().unwrap();
+//! assert_eq!(Some("Ground"), scheme.name(2));
+//! assert_eq!(Some("Power Line"), scheme.name(64));
+//! assert_eq!(Some(64), scheme.code("PowerLine"));
+//! ```
+//!
+//! Filter [expressions](crate::filters::expression) created [with a
+//! header](crate::filters::expression::Expression::with_header) resolve classification names
+//! through the header's scheme, and [Stats::classifications_by_name](crate::stats::Stats::classifications_by_name)
+//! labels classification counts with it.
+
+use crate::{
+    utils::{AsLasStr, FromLasStr},
+    Error, Header, Result, Vlr,
+};
+use std::collections::BTreeMap;
+
+/// The user id of the Classification Lookup vlr.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of the Classification Lookup vlr.
+pub const RECORD_ID: u16 = 0;
+
+/// The length of one Classification Lookup record: a code and a 15-byte description.
+const RECORD_LEN: usize = 16;
+
+/// The ASPRS names of the standard classification codes, indexed by code.
+const STANDARD_NAMES: [&str; 19] = [
+    "Created, Never Classified",
+    "Unclassified",
+    "Ground",
+    "Low Vegetation",
+    "Medium Vegetation",
+    "High Vegetation",
+    "Building",
+    "Low Point",
+    "Model Key-Point",
+    "Water",
+    "Rail",
+    "Road Surface",
+    "Overlap Points",
+    "Wire - Guard",
+    "Wire - Conductor",
+    "Transmission Tower",
+    "Wire-Structure Connector",
+    "Bridge Deck",
+    "High Noise",
+];
+
+/// Maps classification codes to names, using the ASPRS names and any user-defined names.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassificationScheme {
+    names: BTreeMap<u8, String>,
+}
+
+impl ClassificationScheme {
+    /// Reads the user-defined names from a Classification Lookup vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{classification::ClassificationScheme, Vlr};
+    ///
+    /// let mut data = vec![64];
+    /// data.extend(b"Power Line\0\0\0\0\0");
+    /// let vlr = Vlr { user_id: "LASF_Spec".to_string(), record_id: 0, data, ..Default::default() };
+    /// let scheme = ClassificationScheme::from_vlr(&vlr).unwrap();
+    /// assert_eq!(Some("Power Line"), scheme.name(64));
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Result<ClassificationScheme> {
+        if !vlr.data.len().is_multiple_of(RECORD_LEN) {
+            return Err(Error::InvalidClassificationLookup(format!(
+                "{} bytes is not a whole number of {} byte records",
+                vlr.data.len(),
+                RECORD_LEN
+            )));
+        }
+        let mut scheme = ClassificationScheme::default();
+        for record in vlr.data.chunks_exact(RECORD_LEN) {
+            let name = &record[1..];
+            let name = name.as_las_str()?;
+            if !name.is_empty() {
+                let _ = scheme.names.insert(record[0], name.to_string());
+            }
+        }
+        Ok(scheme)
+    }
+
+    /// Creates a Classification Lookup vlr with this scheme's user-defined names.
+    ///
+    /// The vlr has a record for every code, with an empty description for codes without a
+    /// user-defined name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    ///
+    /// let vlr = ClassificationScheme::default().to_vlr().unwrap();
+    /// assert_eq!(4096, vlr.data.len());
+    /// ```
+    pub fn to_vlr(&self) -> Result<Vlr> {
+        let mut data = vec![0; 256 * RECORD_LEN];
+        for (code, record) in (0..=u8::MAX).zip(data.chunks_exact_mut(RECORD_LEN)) {
+            record[0] = code;
+            if let Some(name) = self.names.get(&code) {
+                (&mut record[1..]).from_las_str(name)?;
+            }
+        }
+        Ok(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            description: "Classification Lookup".to_string(),
+            data,
+        })
+    }
+
+    /// Sets the name of a classification code, overriding any standard name.
+    ///
+    /// Names must be ascii and at most 15 bytes long, so they fit in the Classification Lookup vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    ///
+    /// let mut scheme = ClassificationScheme::default();
+    /// scheme.set_name(64, "Power Line").unwrap();
+    /// assert!(scheme.set_name(65, "A much too long name").is_err());
+    /// ```
+    pub fn set_name(&mut self, code: u8, name: &str) -> Result<()> {
+        if !name.is_ascii() {
+            return Err(Error::NotAscii(name.to_string()));
+        }
+        (&mut [0; RECORD_LEN - 1][..]).from_las_str(name)?;
+        let _ = self.names.insert(code, name.to_string());
+        Ok(())
+    }
+
+    /// Returns the name of a classification code.
+    ///
+    /// User-defined names take precedence over the standard names. Returns `None` for reserved
+    /// and user-definable codes that haven't been named.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    ///
+    /// let scheme = ClassificationScheme::default();
+    /// assert_eq!(Some("Low Vegetation"), scheme.name(3));
+    /// assert_eq!(None, scheme.name(64));
+    /// ```
+    pub fn name(&self, code: u8) -> Option<&str> {
+        self.names
+            .get(&code)
+            .map(String::as_str)
+            .or_else(|| STANDARD_NAMES.get(usize::from(code)).copied())
+    }
+
+    /// Returns the name of a classification code, or a generic label if it doesn't have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    ///
+    /// let scheme = ClassificationScheme::default();
+    /// assert_eq!("Ground", scheme.label(2));
+    /// assert_eq!("Reserved 20", scheme.label(20));
+    /// assert_eq!("User Definable 64", scheme.label(64));
+    /// ```
+    pub fn label(&self, code: u8) -> String {
+        match self.name(code) {
+            Some(name) => name.to_string(),
+            None if code < 64 => format!("Reserved {}", code),
+            None => format!("User Definable {}", code),
+        }
+    }
+
+    /// Returns the code for a classification name.
+    ///
+    /// Names are compared ignoring case and anything that isn't a letter or a digit, so
+    /// `LowVegetation`, `low_vegetation`, and `Low Vegetation` are all code 3. User-defined
+    /// names take precedence over the standard names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    ///
+    /// let scheme = ClassificationScheme::default();
+    /// assert_eq!(Some(3), scheme.code("low_vegetation"));
+    /// assert_eq!(None, scheme.code("Power Line"));
+    /// ```
+    pub fn code(&self, name: &str) -> Option<u8> {
+        let name = normalize(name);
+        self.names
+            .iter()
+            .find(|(_, n)| normalize(n) == name)
+            .map(|(&code, _)| code)
+            .or_else(|| {
+                (0..=u8::MAX)
+                    .zip(STANDARD_NAMES)
+                    .find(|(_, n)| normalize(n) == name)
+                    .map(|(code, _)| code)
+            })
+    }
+
+    /// Returns the user-defined names, by code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::classification::ClassificationScheme;
+    /// assert!(ClassificationScheme::default().user_names().is_empty());
+    /// ```
+    pub fn user_names(&self) -> &BTreeMap<u8, String> {
+        &self.names
+    }
+}
+
+impl Header {
+    /// Returns this header's classification scheme.
+    ///
+    /// If there isn't a Classification Lookup vlr, the scheme only has the standard names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    ///
+    /// let scheme = Header::default().classification_scheme().unwrap();
+    /// assert_eq!(Some("Building"), scheme.name(6));
+    /// ```
+    pub fn classification_scheme(&self) -> Result<ClassificationScheme> {
+        self.find_vlr(USER_ID, RECORD_ID)
+            .map(ClassificationScheme::from_vlr)
+            .unwrap_or_else(|| Ok(ClassificationScheme::default()))
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Classification;
+
+    #[test]
+    fn standard_names_match_classification() {
+        for code in (0..=18).filter(|&code| code != 12) {
+            let classification = Classification::new(code).unwrap();
+            assert_eq!(
+                Some(code),
+                ClassificationScheme::default().code(&format!("{:?}", classification)),
+                "{:?}",
+                classification
+            );
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut scheme = ClassificationScheme::default();
+        scheme.set_name(64, "Power Line").unwrap();
+        scheme.set_name(2, "Bare Earth").unwrap();
+        let vlr = scheme.to_vlr().unwrap();
+        let roundtrip = ClassificationScheme::from_vlr(&vlr).unwrap();
+        assert_eq!(scheme, roundtrip);
+        assert_eq!(Some("Bare Earth"), roundtrip.name(2));
+        assert_eq!(Some(2), roundtrip.code("ground"));
+        assert_eq!(Some(2), roundtrip.code("bare earth"));
+    }
+
+    #[test]
+    fn invalid_lookup() {
+        let vlr = Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: RECORD_ID,
+            data: vec![0; 17],
+            ..Default::default()
+        };
+        assert!(ClassificationScheme::from_vlr(&vlr).is_err());
+    }
+
+    #[test]
+    fn invalid_name() {
+        let mut scheme = ClassificationScheme::default();
+        assert!(scheme.set_name(64, "16 characters!!!").is_err());
+        assert!(scheme.set_name(64, "héllo").is_err());
+        assert!(scheme.user_names().is_empty());
+    }
+}
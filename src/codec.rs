@@ -0,0 +1,519 @@
+//! Decode and encode application-specific vlrs.
+//!
+//! Vendors and applications store their own records in vlrs, identified by a user id and a
+//! record id. Implement [VlrCodec] for a type to describe how its vlr's data are laid out, then
+//! use the typed accessors on [Header] instead of reading and writing [Vlr::data] by hand:
+//!
+//! ```
+//! use las::{codec::{Registry, VlrCodec}, Header, Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct ScannerSerial(u32);
+//!
+//! impl VlrCodec for ScannerSerial {
+//!     const USER_ID: &'static str = "ExampleVendor";
+//!     const RECORD_ID: u16 = 1;
+//!
+//!     fn decode(data: &[u8]) -> las::Result<ScannerSerial> {
+//!         let bytes = data.try_into().map_err(|_| las::codec::invalid::<Self>("expected 4 bytes"))?;
+//!         Ok(ScannerSerial(u32::from_le_bytes(bytes)))
+//!     }
+//!
+//!     fn encode(&self) -> las::Result<Vec<u8>> {
+//!         Ok(self.0.to_le_bytes().to_vec())
+//!     }
+//! }
+//!
+//! let mut header = Header::default();
+//! header.set_vlr_value(ScannerSerial(42)).unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! writer.close().unwrap();
+//! let cursor = writer.into_inner().unwrap();
+//!
+//! let mut registry = Registry::default();
+//! registry.register::<ScannerSerial>();
+//! let mut header = Reader::new(cursor).unwrap().header().clone();
+//! header.decode_vlrs(&registry).unwrap();
+//! assert_eq!(Some(&ScannerSerial(42)), header.vlr_value::<ScannerSerial>());
+//! ```
+//!
+//! A [Registry] decodes every registered vlr of a header at once, so malformed records are caught
+//! early and each record is decoded only once. Values set with [Header::set_vlr_value] are encoded
+//! right away, so a [Writer](crate::Writer) always writes their current bytes.
+
+use crate::{feature::Evlrs, Error, Header, Result, Vlr};
+use std::{any::Any, collections::HashMap, fmt::Debug};
+
+/// Decodes and encodes the data of one kind of vlr.
+pub trait VlrCodec: Any + Clone + Debug + Send + Sync {
+    /// The user id of this type's vlr.
+    const USER_ID: &'static str;
+
+    /// The record id of this type's vlr.
+    const RECORD_ID: u16;
+
+    /// The description written into new vlrs.
+    const DESCRIPTION: &'static str = "";
+
+    /// Decodes a value from a vlr's data.
+    fn decode(data: &[u8]) -> Result<Self>;
+
+    /// Encodes this value as vlr data.
+    fn encode(&self) -> Result<Vec<u8>>;
+}
+
+/// A set of [VlrCodec]s, keyed by user id and record id.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    decoders: HashMap<(String, u16), Decoder>,
+}
+
+type Decoder = fn(&[u8]) -> Result<Box<dyn Decoded>>;
+
+/// The decoded values of a header's registered vlrs.
+///
+/// The values always match the header's vlrs, so they're ignored when comparing headers.
+#[derive(Default)]
+pub(crate) struct DecodedVlrs(HashMap<(String, u16), Box<dyn Decoded>>);
+
+trait Decoded: Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn clone_box(&self) -> Box<dyn Decoded>;
+}
+
+impl Registry {
+    /// Registers a codec, replacing any codec with the same user id and record id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::codec::{Registry, VlrCodec};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// struct Note(String);
+    ///
+    /// impl VlrCodec for Note {
+    ///     const USER_ID: &'static str = "ExampleVendor";
+    ///     const RECORD_ID: u16 = 2;
+    ///
+    ///     fn decode(data: &[u8]) -> las::Result<Note> {
+    ///         Ok(Note(String::from_utf8_lossy(data).into_owned()))
+    ///     }
+    ///
+    ///     fn encode(&self) -> las::Result<Vec<u8>> {
+    ///         Ok(self.0.as_bytes().to_vec())
+    ///     }
+    /// }
+    ///
+    /// let mut registry = Registry::default();
+    /// registry.register::<Note>();
+    /// assert!(registry.is_registered("ExampleVendor", 2));
+    /// ```
+    pub fn register<T: VlrCodec>(&mut self) -> &mut Registry {
+        let _ = self.decoders.insert(key::<T>(), decode::<T>);
+        self
+    }
+
+    /// Returns true if a codec is registered for this user id and record id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::codec::Registry;
+    /// assert!(!Registry::default().is_registered("ExampleVendor", 1));
+    /// ```
+    pub fn is_registered(&self, user_id: &str, record_id: u16) -> bool {
+        self.decoders
+            .contains_key(&(user_id.to_string(), record_id))
+    }
+}
+
+impl Header {
+    /// Decodes every vlr and evlr that has a codec in the registry.
+    ///
+    /// The decoded values are available from [Header::vlr_value]. If several vlrs share a user id
+    /// and record id, the first one is decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{codec::Registry, Reader};
+    ///
+    /// let mut header = Reader::from_path("tests/data/autzen.las").unwrap().header().clone();
+    /// header.decode_vlrs(&Registry::default()).unwrap();
+    /// ```
+    pub fn decode_vlrs(&mut self, registry: &Registry) -> Result<()> {
+        let mut decoded = HashMap::new();
+        for vlr in self.all_vlrs() {
+            let key = (vlr.user_id.clone(), vlr.record_id);
+            if decoded.contains_key(&key) {
+                continue;
+            }
+            if let Some(decode) = registry.decoders.get(&key) {
+                let value = decode(&vlr.data)?;
+                let _ = decoded.insert(key, value);
+            }
+        }
+        self.decoded_vlrs.0.extend(decoded);
+        Ok(())
+    }
+
+    /// Returns the decoded value of this type's vlr.
+    ///
+    /// Returns `None` if the vlr hasn't been decoded with [Header::decode_vlrs] or set with
+    /// [Header::set_vlr_value]. Use [Header::decode_vlr] to decode a vlr without a registry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{codec::VlrCodec, Header};
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Flag(u8);
+    ///
+    /// impl VlrCodec for Flag {
+    ///     const USER_ID: &'static str = "ExampleVendor";
+    ///     const RECORD_ID: u16 = 3;
+    ///
+    ///     fn decode(data: &[u8]) -> las::Result<Flag> {
+    ///         Ok(Flag(data.first().copied().unwrap_or_default()))
+    ///     }
+    ///
+    ///     fn encode(&self) -> las::Result<Vec<u8>> {
+    ///         Ok(vec![self.0])
+    ///     }
+    /// }
+    ///
+    /// let mut header = Header::default();
+    /// assert_eq!(None, header.vlr_value::<Flag>());
+    /// header.set_vlr_value(Flag(1)).unwrap();
+    /// assert_eq!(Some(&Flag(1)), header.vlr_value::<Flag>());
+    /// ```
+    pub fn vlr_value<T: VlrCodec>(&self) -> Option<&T> {
+        self.decoded_vlrs
+            .0
+            .get(&key::<T>())
+            .and_then(|value| value.as_any().downcast_ref())
+    }
+
+    /// Decodes this type's vlr, or returns `None` if the header doesn't have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{codec::VlrCodec, Header};
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Flag(u8);
+    ///
+    /// impl VlrCodec for Flag {
+    ///     const USER_ID: &'static str = "ExampleVendor";
+    ///     const RECORD_ID: u16 = 3;
+    ///
+    ///     fn decode(data: &[u8]) -> las::Result<Flag> {
+    ///         Ok(Flag(data.first().copied().unwrap_or_default()))
+    ///     }
+    ///
+    ///     fn encode(&self) -> las::Result<Vec<u8>> {
+    ///         Ok(vec![self.0])
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(None, Header::default().decode_vlr::<Flag>().unwrap());
+    /// ```
+    pub fn decode_vlr<T: VlrCodec>(&self) -> Result<Option<T>> {
+        self.find_vlr(T::USER_ID, T::RECORD_ID)
+            .map(|vlr| T::decode(&vlr.data))
+            .transpose()
+    }
+
+    /// Encodes a value into this type's vlr, and keeps the value for [Header::vlr_value].
+    ///
+    /// An existing vlr or evlr with the same user id and record id is overwritten, including its
+    /// description. Otherwise a new vlr is added. Data that are too long for a regular vlr are
+    /// stored in an evlr, which returns an error if the header's version doesn't support evlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{codec::VlrCodec, Header};
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Flag(u8);
+    ///
+    /// impl VlrCodec for Flag {
+    ///     const USER_ID: &'static str = "ExampleVendor";
+    ///     const RECORD_ID: u16 = 3;
+    ///
+    ///     fn decode(data: &[u8]) -> las::Result<Flag> {
+    ///         Ok(Flag(data.first().copied().unwrap_or_default()))
+    ///     }
+    ///
+    ///     fn encode(&self) -> las::Result<Vec<u8>> {
+    ///         Ok(vec![self.0])
+    ///     }
+    /// }
+    ///
+    /// let mut header = Header::default();
+    /// header.set_vlr_value(Flag(1)).unwrap();
+    /// header.set_vlr_value(Flag(2)).unwrap();
+    /// assert_eq!(1, header.vlrs().len());
+    /// assert_eq!(Some(Flag(2)), header.decode_vlr::<Flag>().unwrap());
+    /// ```
+    pub fn set_vlr_value<T: VlrCodec>(&mut self, value: T) -> Result<()> {
+        let new = vlr(&value)?;
+        let is_key = |vlr: &Vlr| vlr.user_id == T::USER_ID && vlr.record_id == T::RECORD_ID;
+        if let Some(existing) = self.evlrs.iter_mut().find(|vlr| is_key(vlr)) {
+            *existing = new;
+        } else if !new.has_large_data() {
+            match self.vlrs.iter_mut().find(|vlr| is_key(vlr)) {
+                Some(existing) => *existing = new,
+                None => self.vlrs.push(new),
+            }
+        } else {
+            self.version().verify_support_for::<Evlrs>()?;
+            self.vlrs.retain(|vlr| !is_key(vlr));
+            self.evlrs.push(new);
+        }
+        let _ = self.decoded_vlrs.0.insert(key::<T>(), Box::new(value));
+        Ok(())
+    }
+}
+
+/// Creates a vlr for a value.
+///
+/// # Examples
+///
+/// ```
+/// use las::codec::{self, VlrCodec};
+///
+/// #[derive(Clone, Debug)]
+/// struct Flag(u8);
+///
+/// impl VlrCodec for Flag {
+///     const USER_ID: &'static str = "ExampleVendor";
+///     const RECORD_ID: u16 = 3;
+///     const DESCRIPTION: &'static str = "A flag";
+///
+///     fn decode(data: &[u8]) -> las::Result<Flag> {
+///         Ok(Flag(data.first().copied().unwrap_or_default()))
+///     }
+///
+///     fn encode(&self) -> las::Result<Vec<u8>> {
+///         Ok(vec![self.0])
+///     }
+/// }
+///
+/// let vlr = codec::vlr(&Flag(1)).unwrap();
+/// assert_eq!("A flag", vlr.description);
+/// assert_eq!(vec![1], vlr.data);
+/// ```
+pub fn vlr<T: VlrCodec>(value: &T) -> Result<Vlr> {
+    Ok(Vlr {
+        user_id: T::USER_ID.to_string(),
+        record_id: T::RECORD_ID,
+        description: T::DESCRIPTION.to_string(),
+        data: value.encode()?,
+    })
+}
+
+/// Creates an [Error::InvalidVlr] for this type's vlr, for use in [VlrCodec::decode].
+///
+/// # Examples
+///
+/// ```
+/// use las::codec::{self, VlrCodec};
+///
+/// #[derive(Clone, Debug)]
+/// struct Flag(u8);
+///
+/// impl VlrCodec for Flag {
+///     const USER_ID: &'static str = "ExampleVendor";
+///     const RECORD_ID: u16 = 3;
+///
+///     fn decode(data: &[u8]) -> las::Result<Flag> {
+///         match data {
+///             [flag] => Ok(Flag(*flag)),
+///             _ => Err(codec::invalid::<Flag>("expected one byte")),
+///         }
+///     }
+///
+///     fn encode(&self) -> las::Result<Vec<u8>> {
+///         Ok(vec![self.0])
+///     }
+/// }
+///
+/// assert!(Flag::decode(&[]).is_err());
+/// ```
+pub fn invalid<T: VlrCodec>(message: &str) -> Error {
+    Error::InvalidVlr {
+        user_id: T::USER_ID.to_string(),
+        record_id: T::RECORD_ID,
+        message: message.to_string(),
+    }
+}
+
+impl<T: VlrCodec> Decoded for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Decoded> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for DecodedVlrs {
+    fn clone(&self) -> DecodedVlrs {
+        DecodedVlrs(
+            self.0
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone_box()))
+                .collect(),
+        )
+    }
+}
+
+impl Debug for DecodedVlrs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl PartialEq for DecodedVlrs {
+    fn eq(&self, _: &DecodedVlrs) -> bool {
+        true
+    }
+}
+
+fn decode<T: VlrCodec>(data: &[u8]) -> Result<Box<dyn Decoded>> {
+    Ok(Box::new(T::decode(data)?))
+}
+
+fn key<T: VlrCodec>() -> (String, u16) {
+    (T::USER_ID.to_string(), T::RECORD_ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Builder, Reader, Writer};
+    use std::io::Cursor;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Calibration {
+        range_offset: f64,
+        label: String,
+    }
+
+    impl VlrCodec for Calibration {
+        const USER_ID: &'static str = "ExampleVendor";
+        const RECORD_ID: u16 = 10;
+        const DESCRIPTION: &'static str = "Calibration";
+
+        fn decode(data: &[u8]) -> Result<Calibration> {
+            if data.len() < 8 {
+                return Err(invalid::<Calibration>("too short"));
+            }
+            let (offset, label) = data.split_at(8);
+            Ok(Calibration {
+                range_offset: f64::from_le_bytes(offset.try_into().unwrap()),
+                label: String::from_utf8_lossy(label).into_owned(),
+            })
+        }
+
+        fn encode(&self) -> Result<Vec<u8>> {
+            let mut data = self.range_offset.to_le_bytes().to_vec();
+            data.extend(self.label.as_bytes());
+            Ok(data)
+        }
+    }
+
+    fn calibration() -> Calibration {
+        Calibration {
+            range_offset: 0.25,
+            label: "left".to_string(),
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut builder = Builder::default();
+        builder.vlrs.push(vlr(&calibration()).unwrap());
+        let header = builder.into_header().unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.close().unwrap();
+        let cursor = writer.into_inner().unwrap();
+
+        let mut header = Reader::new(cursor).unwrap().header().clone();
+        assert_eq!(None, header.vlr_value::<Calibration>());
+        let mut registry = Registry::default();
+        let _ = registry.register::<Calibration>();
+        header.decode_vlrs(&registry).unwrap();
+        assert_eq!(Some(&calibration()), header.vlr_value::<Calibration>());
+        assert_eq!("Calibration", header.vlrs()[0].description);
+    }
+
+    #[test]
+    fn set_replaces() {
+        let mut header = Header::default();
+        header.set_vlr_value(calibration()).unwrap();
+        let mut updated = calibration();
+        updated.label = "right".to_string();
+        header.set_vlr_value(updated.clone()).unwrap();
+        assert_eq!(1, header.vlrs().len());
+        assert_eq!(Some(&updated), header.vlr_value::<Calibration>());
+        assert_eq!(Some(updated), header.decode_vlr::<Calibration>().unwrap());
+        assert_eq!(header.clone(), header);
+    }
+
+    #[test]
+    fn set_updates_description() {
+        let mut header = Header::default();
+        header.vlrs.push(Vlr {
+            description: "stale".to_string(),
+            ..vlr(&calibration()).unwrap()
+        });
+        header.set_vlr_value(calibration()).unwrap();
+        assert_eq!("Calibration", header.vlrs()[0].description);
+    }
+
+    #[test]
+    fn set_moves_large_data_to_evlr() {
+        let mut large = calibration();
+        large.label = "x".repeat(usize::from(u16::MAX));
+        let mut header = Header::from((1, 4));
+        header.set_vlr_value(calibration()).unwrap();
+        header.set_vlr_value(large.clone()).unwrap();
+        assert!(header.vlrs().is_empty());
+        assert_eq!(1, header.evlrs().len());
+        assert_eq!(
+            Some(large.clone()),
+            header.decode_vlr::<Calibration>().unwrap()
+        );
+
+        let mut header = Header::from((1, 2));
+        header.set_vlr_value(calibration()).unwrap();
+        assert!(header.set_vlr_value(large).is_err());
+        assert_eq!(
+            Some(calibration()),
+            header.decode_vlr::<Calibration>().unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut header = Header::default();
+        header.vlrs.push(Vlr {
+            user_id: Calibration::USER_ID.to_string(),
+            record_id: Calibration::RECORD_ID,
+            data: vec![1, 2],
+            ..Default::default()
+        });
+        let mut registry = Registry::default();
+        let _ = registry.register::<Calibration>();
+        assert!(header.decode_vlrs(&registry).is_err());
+        assert!(header.decode_vlr::<Calibration>().is_err());
+    }
+}
@@ -0,0 +1,893 @@
+//! Read and write GeoTIFF and WKT coordinate reference systems.
+//!
+//! Point formats 0 through 5 store their coordinate reference system as GeoTIFF keys, split
+//! across three `LASF_Projection` vlrs: the GeoKeyDirectoryTag (record id 34735), which is
+//! required, and the GeoDoubleParamsTag (34736) and GeoAsciiParamsTag (34737), which hold key
+//! values that don't fit in the directory.
+//!
+//! ```
+//! use las::Reader;
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let geo_keys = reader.header().geo_keys().unwrap().unwrap();
+//! assert_eq!(Some(2994), geo_keys.epsg());
+//! ```
+//!
+//! Use [GeoKeys::from_epsg] and [GeoKeys::to_vlrs] to describe a coordinate reference system by
+//! its EPSG code:
+//!
+//! ```
+//! use las::{crs::GeoKeys, Builder};
+//!
+//! let mut builder = Builder::default();
+//! builder.vlrs.extend(GeoKeys::from_epsg(32610).unwrap().to_vlrs().unwrap());
+//! let header = builder.into_header().unwrap();
+//! assert_eq!(Some(32610), header.geo_keys().unwrap().unwrap().epsg());
+//! ```
+//!
+//! Point formats 6 through 10 must use OGC well-known text instead, stored in a single
+//! `LASF_Projection` vlr (or evlr) with record id 2112 and flagged by the wkt bit of the global
+//! encoding:
+//!
+//! ```
+//! use las::{point::Format, Builder};
+//!
+//! let mut builder = Builder::from((1, 4));
+//! builder.point_format = Format::new(6).unwrap();
+//! builder.set_wkt("GEOGCS[\"WGS 84\"]");
+//! let header = builder.into_header().unwrap();
+//! assert!(header.has_wkt_crs());
+//! assert_eq!("GEOGCS[\"WGS 84\"]", header.wkt().unwrap().unwrap());
+//! ```
+
+use crate::{Builder, Error, Header, Result, Vlr};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+/// The user id of the GeoTIFF vlrs.
+pub const USER_ID: &str = "LASF_Projection";
+
+/// The record id of the GeoKeyDirectoryTag vlr.
+pub const GEO_KEY_DIRECTORY_RECORD_ID: u16 = 34735;
+
+/// The record id of the GeoDoubleParamsTag vlr.
+pub const GEO_DOUBLE_PARAMS_RECORD_ID: u16 = 34736;
+
+/// The record id of the GeoAsciiParamsTag vlr.
+pub const GEO_ASCII_PARAMS_RECORD_ID: u16 = 34737;
+
+/// The record id of the OGC coordinate system WKT vlr.
+pub const WKT_RECORD_ID: u16 = 2112;
+
+/// GTModelTypeGeoKey: 1 for projected, 2 for geographic, 3 for geocentric.
+pub const GT_MODEL_TYPE: u16 = 1024;
+
+/// GTRasterTypeGeoKey: 1 for pixel is area, 2 for pixel is point.
+pub const GT_RASTER_TYPE: u16 = 1025;
+
+/// GTCitationGeoKey.
+pub const GT_CITATION: u16 = 1026;
+
+/// GeographicTypeGeoKey, the EPSG code of a geographic coordinate reference system.
+pub const GEOGRAPHIC_TYPE: u16 = 2048;
+
+/// GeogCitationGeoKey.
+pub const GEOG_CITATION: u16 = 2049;
+
+/// GeogAngularUnitsGeoKey.
+pub const GEOG_ANGULAR_UNITS: u16 = 2054;
+
+/// ProjectedCSTypeGeoKey, the EPSG code of a projected coordinate reference system.
+pub const PROJECTED_CS_TYPE: u16 = 3072;
+
+/// PCSCitationGeoKey.
+pub const PCS_CITATION: u16 = 3073;
+
+/// ProjLinearUnitsGeoKey.
+pub const PROJ_LINEAR_UNITS: u16 = 3076;
+
+/// VerticalCSTypeGeoKey, the EPSG code of a vertical coordinate reference system.
+pub const VERTICAL_CS_TYPE: u16 = 4096;
+
+/// VerticalUnitsGeoKey.
+pub const VERTICAL_UNITS: u16 = 4099;
+
+/// The key value that means a user-defined coordinate reference system.
+pub const USER_DEFINED: u16 = 32767;
+
+/// The EPSG code for degrees, used for [GEOG_ANGULAR_UNITS].
+const ANGULAR_DEGREE: u16 = 9102;
+
+/// The number of u16s in the directory header and in each key entry.
+const ENTRY_LEN: usize = 4;
+
+/// The EPSG codes of well-known geographic 2D coordinate reference systems.
+const GEOGRAPHIC_EPSG_CODES: [u16; 23] = [
+    4148, // Hartebeesthoek94
+    4152, // NAD83(HARN)
+    4167, // NZGD2000
+    4171, // RGF93
+    4230, // ED50
+    4258, // ETRS89
+    4267, // NAD27
+    4269, // NAD83
+    4277, // OSGB36
+    4283, // GDA94
+    4284, // Pulkovo 1942
+    4314, // DHDN
+    4322, // WGS 72
+    4326, // WGS 84
+    4490, // CGCS2000
+    4612, // JGD2000
+    4617, // NAD83(CSRS)
+    4619, // SWEREF99
+    4674, // SIRGAS 2000
+    4759, // NAD83(NSRS2007)
+    6318, // NAD83(2011)
+    6668, // JGD2011
+    7844, // GDA2020
+];
+
+/// The GeoTIFF keys of a coordinate reference system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoKeys {
+    /// The keys, sorted by id.
+    pub keys: Vec<GeoKey>,
+}
+
+/// One GeoTIFF key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoKey {
+    /// The key id, e.g. [PROJECTED_CS_TYPE].
+    pub id: u16,
+
+    /// The key's value.
+    pub value: GeoKeyValue,
+}
+
+/// The contents of a GeoKeyDirectoryTag vlr, with key values left unresolved.
+///
+/// Use [GeoKeys::from_vlrs] to resolve the values that live in the other GeoTIFF vlrs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeoKeyDirectory {
+    /// The key entries.
+    pub entries: Vec<GeoKeyEntry>,
+
+    /// The short values stored after the key entries.
+    ///
+    /// Entries with a location of [GEO_KEY_DIRECTORY_RECORD_ID] point into these values with an
+    /// offset counted from the start of the directory, as in the vlr itself.
+    pub shorts: Vec<u16>,
+}
+
+/// One entry of a GeoKeyDirectoryTag vlr.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GeoKeyEntry {
+    /// The key id.
+    pub id: u16,
+
+    /// Zero if the value is stored in this entry, or the record id of the vlr holding it.
+    pub location: u16,
+
+    /// The number of values.
+    pub count: u16,
+
+    /// The value if the location is zero, otherwise the index of the first value.
+    pub value_offset: u16,
+}
+
+/// The value of a GeoTIFF key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeoKeyValue {
+    /// A single short, stored in the key entry itself.
+    Short(u16),
+
+    /// Shorts stored after the key entries in the directory.
+    Shorts(Vec<u16>),
+
+    /// Doubles stored in the GeoDoubleParamsTag vlr.
+    Doubles(Vec<f64>),
+
+    /// A string stored in the GeoAsciiParamsTag vlr.
+    Ascii(String),
+}
+
+impl GeoKeys {
+    /// Parses GeoTIFF keys from the GeoKeyDirectoryTag vlr and, if provided, the
+    /// GeoDoubleParamsTag and GeoAsciiParamsTag vlrs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::GeoKeys;
+    ///
+    /// let vlrs = GeoKeys::from_epsg(4326).unwrap().to_vlrs().unwrap();
+    /// let geo_keys = GeoKeys::from_vlrs(&vlrs[0], None, None).unwrap();
+    /// assert_eq!(Some(4326), geo_keys.epsg());
+    /// ```
+    pub fn from_vlrs(
+        directory: &Vlr,
+        double_params: Option<&Vlr>,
+        ascii_params: Option<&Vlr>,
+    ) -> Result<GeoKeys> {
+        let directory = GeoKeyDirectory::from_vlr(directory)?;
+        let values_start = ENTRY_LEN * (directory.entries.len() + 1);
+        let doubles = match double_params {
+            Some(vlr) => double_params_from_data(&vlr.data)?,
+            None => Vec::new(),
+        };
+        let ascii = ascii_params
+            .map(|vlr| String::from_utf8_lossy(&vlr.data).into_owned())
+            .unwrap_or_default();
+        let mut keys = Vec::with_capacity(directory.entries.len());
+        for entry in &directory.entries {
+            let (id, location, count, value) = (
+                entry.id,
+                entry.location,
+                usize::from(entry.count),
+                usize::from(entry.value_offset),
+            );
+            let value = match location {
+                0 => GeoKeyValue::Short(entry.value_offset),
+                GEO_KEY_DIRECTORY_RECORD_ID => GeoKeyValue::Shorts(
+                    value
+                        .checked_sub(values_start)
+                        .and_then(|start| directory.shorts.get(start..start + count))
+                        .ok_or_else(|| invalid("short values are out of bounds"))?
+                        .to_vec(),
+                ),
+                GEO_DOUBLE_PARAMS_RECORD_ID => GeoKeyValue::Doubles(
+                    doubles
+                        .get(value..value + count)
+                        .ok_or_else(|| invalid("double values are out of bounds"))?
+                        .to_vec(),
+                ),
+                GEO_ASCII_PARAMS_RECORD_ID => {
+                    let s = ascii
+                        .get(value..value + count)
+                        .ok_or_else(|| invalid("ascii values are out of bounds"))?;
+                    GeoKeyValue::Ascii(s.trim_end_matches(['|', '\0']).to_string())
+                }
+                _ => return Err(invalid(&format!("unknown tag location: {}", location))),
+            };
+            keys.push(GeoKey { id, value });
+        }
+        Ok(GeoKeys { keys })
+    }
+
+    /// Creates keys for the coordinate reference system with this EPSG code.
+    ///
+    /// Well-known geographic codes, like 4326 and 4269, are written as geographic.
+    /// Other codes from 4000 through 4999 are mostly geographic, geocentric, or 3D systems that
+    /// can't be described by a code alone, so they return [Error::UnknownEpsg]. All other codes
+    /// are treated as projected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKeys, GeoKeyValue, GEOGRAPHIC_TYPE, PROJECTED_CS_TYPE};
+    ///
+    /// let geo_keys = GeoKeys::from_epsg(4326).unwrap();
+    /// assert_eq!(Some(&GeoKeyValue::Short(4326)), geo_keys.get(GEOGRAPHIC_TYPE));
+    /// let geo_keys = GeoKeys::from_epsg(32610).unwrap();
+    /// assert_eq!(Some(&GeoKeyValue::Short(32610)), geo_keys.get(PROJECTED_CS_TYPE));
+    /// assert!(GeoKeys::from_epsg(4978).is_err());
+    /// ```
+    pub fn from_epsg(code: u16) -> Result<GeoKeys> {
+        let short = |id, value| GeoKey {
+            id,
+            value: GeoKeyValue::Short(value),
+        };
+        let keys = if GEOGRAPHIC_EPSG_CODES.contains(&code) {
+            vec![
+                short(GT_MODEL_TYPE, 2),
+                short(GT_RASTER_TYPE, 1),
+                short(GEOGRAPHIC_TYPE, code),
+                short(GEOG_ANGULAR_UNITS, ANGULAR_DEGREE),
+            ]
+        } else if (4000..5000).contains(&code) {
+            return Err(Error::UnknownEpsg(code));
+        } else {
+            vec![
+                short(GT_MODEL_TYPE, 1),
+                short(GT_RASTER_TYPE, 1),
+                short(PROJECTED_CS_TYPE, code),
+            ]
+        };
+        Ok(GeoKeys { keys })
+    }
+
+    /// Returns the value of the key with this id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKeys, GeoKeyValue, GT_MODEL_TYPE, VERTICAL_CS_TYPE};
+    ///
+    /// let geo_keys = GeoKeys::from_epsg(32610).unwrap();
+    /// assert_eq!(Some(&GeoKeyValue::Short(1)), geo_keys.get(GT_MODEL_TYPE));
+    /// assert_eq!(None, geo_keys.get(VERTICAL_CS_TYPE));
+    /// ```
+    pub fn get(&self, id: u16) -> Option<&GeoKeyValue> {
+        self.keys
+            .iter()
+            .find(|key| key.id == id)
+            .map(|key| &key.value)
+    }
+
+    /// Returns the EPSG code of the projected or geographic coordinate reference system.
+    ///
+    /// The projected code is preferred. Returns `None` if neither is set, or if they are
+    /// user-defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::GeoKeys;
+    ///
+    /// assert_eq!(Some(4269), GeoKeys::from_epsg(4269).unwrap().epsg());
+    /// assert_eq!(None, GeoKeys::default().epsg());
+    /// ```
+    pub fn epsg(&self) -> Option<u16> {
+        self.code(PROJECTED_CS_TYPE)
+            .or_else(|| self.code(GEOGRAPHIC_TYPE))
+    }
+
+    /// Returns the EPSG code of the vertical coordinate reference system, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKey, GeoKeys, GeoKeyValue, VERTICAL_CS_TYPE};
+    ///
+    /// let mut geo_keys = GeoKeys::from_epsg(32610).unwrap();
+    /// geo_keys.keys.push(GeoKey { id: VERTICAL_CS_TYPE, value: GeoKeyValue::Short(5703) });
+    /// assert_eq!(Some(5703), geo_keys.vertical_epsg());
+    /// ```
+    pub fn vertical_epsg(&self) -> Option<u16> {
+        self.code(VERTICAL_CS_TYPE)
+    }
+
+    /// Creates the GeoKeyDirectoryTag vlr and, if needed, the GeoDoubleParamsTag and
+    /// GeoAsciiParamsTag vlrs.
+    ///
+    /// Keys are written in id order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKeys, GEO_KEY_DIRECTORY_RECORD_ID};
+    ///
+    /// let vlrs = GeoKeys::from_epsg(32610).unwrap().to_vlrs().unwrap();
+    /// assert_eq!(1, vlrs.len());
+    /// assert_eq!(GEO_KEY_DIRECTORY_RECORD_ID, vlrs[0].record_id);
+    /// ```
+    pub fn to_vlrs(&self) -> Result<Vec<Vlr>> {
+        let mut keys: Vec<&GeoKey> = self.keys.iter().collect();
+        keys.sort_by_key(|key| key.id);
+        let mut entries = Vec::with_capacity(keys.len());
+        let mut shorts: Vec<u16> = Vec::new();
+        let mut doubles = Vec::new();
+        let mut ascii = String::new();
+        let values_start = ENTRY_LEN * (keys.len() + 1);
+        for key in keys {
+            let (location, count, value) = match &key.value {
+                GeoKeyValue::Short(n) => (0, 1, *n),
+                GeoKeyValue::Shorts(values) => {
+                    let offset = values_start + shorts.len();
+                    shorts.extend(values);
+                    (
+                        GEO_KEY_DIRECTORY_RECORD_ID,
+                        values.len(),
+                        u16::try_from(offset)?,
+                    )
+                }
+                GeoKeyValue::Doubles(values) => {
+                    let offset = doubles.len();
+                    doubles.extend(values);
+                    (
+                        GEO_DOUBLE_PARAMS_RECORD_ID,
+                        values.len(),
+                        u16::try_from(offset)?,
+                    )
+                }
+                GeoKeyValue::Ascii(s) => {
+                    let offset = ascii.len();
+                    ascii.push_str(s);
+                    ascii.push('|');
+                    (
+                        GEO_ASCII_PARAMS_RECORD_ID,
+                        s.len() + 1,
+                        u16::try_from(offset)?,
+                    )
+                }
+            };
+            entries.push(GeoKeyEntry {
+                id: key.id,
+                location,
+                count: u16::try_from(count)?,
+                value_offset: value,
+            });
+        }
+        let mut vlrs = vec![GeoKeyDirectory { entries, shorts }.to_vlr()?];
+        if !doubles.is_empty() {
+            let mut data = Vec::with_capacity(doubles.len() * 8);
+            for n in doubles {
+                data.write_f64::<LittleEndian>(n)?;
+            }
+            vlrs.push(vlr(
+                GEO_DOUBLE_PARAMS_RECORD_ID,
+                "GeoTiff GeoDoubleParamsTag",
+                data,
+            ));
+        }
+        if !ascii.is_empty() {
+            let mut data = ascii.into_bytes();
+            data.push(0);
+            vlrs.push(vlr(
+                GEO_ASCII_PARAMS_RECORD_ID,
+                "GeoTiff GeoAsciiParamsTag",
+                data,
+            ));
+        }
+        Ok(vlrs)
+    }
+
+    fn code(&self, id: u16) -> Option<u16> {
+        match self.get(id)? {
+            GeoKeyValue::Short(0) | GeoKeyValue::Short(USER_DEFINED) => None,
+            GeoKeyValue::Short(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl GeoKeyDirectory {
+    /// Reads the entries and short values of a GeoKeyDirectoryTag vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKeyDirectory, GeoKeys, PROJECTED_CS_TYPE};
+    ///
+    /// let vlrs = GeoKeys::from_epsg(32610).unwrap().to_vlrs().unwrap();
+    /// let directory = GeoKeyDirectory::from_vlr(&vlrs[0]).unwrap();
+    /// let entry = directory.entries.iter().find(|entry| entry.id == PROJECTED_CS_TYPE).unwrap();
+    /// assert_eq!(32610, entry.value_offset);
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Result<GeoKeyDirectory> {
+        let shorts = vlr
+            .data
+            .chunks_exact(2)
+            .map(|mut chunk| chunk.read_u16::<LittleEndian>())
+            .collect::<std::io::Result<Vec<u16>>>()?;
+        if shorts.len() < ENTRY_LEN {
+            return Err(invalid("the directory is too short"));
+        }
+        let number_of_keys = usize::from(shorts[3]);
+        let values_start = ENTRY_LEN * (number_of_keys + 1);
+        let entries = shorts
+            .get(ENTRY_LEN..values_start)
+            .ok_or_else(|| invalid("the directory has too few keys"))?
+            .chunks_exact(ENTRY_LEN)
+            .map(|entry| GeoKeyEntry {
+                id: entry[0],
+                location: entry[1],
+                count: entry[2],
+                value_offset: entry[3],
+            })
+            .collect();
+        Ok(GeoKeyDirectory {
+            entries,
+            shorts: shorts[values_start..].to_vec(),
+        })
+    }
+
+    /// Creates a GeoKeyDirectoryTag vlr from these entries and short values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::crs::{GeoKeyDirectory, GEO_KEY_DIRECTORY_RECORD_ID};
+    ///
+    /// let vlr = GeoKeyDirectory::default().to_vlr().unwrap();
+    /// assert_eq!(GEO_KEY_DIRECTORY_RECORD_ID, vlr.record_id);
+    /// assert_eq!(8, vlr.data.len());
+    /// ```
+    pub fn to_vlr(&self) -> Result<Vlr> {
+        let number_of_keys = u16::try_from(self.entries.len())?;
+        let mut data =
+            Vec::with_capacity(2 * (ENTRY_LEN * (self.entries.len() + 1) + self.shorts.len()));
+        for n in [1, 1, 0, number_of_keys] {
+            data.write_u16::<LittleEndian>(n)?;
+        }
+        for entry in &self.entries {
+            for n in [entry.id, entry.location, entry.count, entry.value_offset] {
+                data.write_u16::<LittleEndian>(n)?;
+            }
+        }
+        for &n in &self.shorts {
+            data.write_u16::<LittleEndian>(n)?;
+        }
+        Ok(vlr(
+            GEO_KEY_DIRECTORY_RECORD_ID,
+            "GeoTiff GeoKeyDirectoryTag",
+            data,
+        ))
+    }
+}
+
+impl Header {
+    /// Returns the GeoTIFF keys from this header's vlrs, or `None` if there isn't a
+    /// GeoKeyDirectoryTag vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().geo_keys().unwrap().is_none());
+    /// ```
+    pub fn geo_keys(&self) -> Result<Option<GeoKeys>> {
+        let find = |record_id| self.find_vlr(USER_ID, record_id);
+        match find(GEO_KEY_DIRECTORY_RECORD_ID) {
+            Some(directory) => GeoKeys::from_vlrs(
+                directory,
+                find(GEO_DOUBLE_PARAMS_RECORD_ID),
+                find(GEO_ASCII_PARAMS_RECORD_ID),
+            )
+            .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the OGC well-known text of this header's coordinate reference system, or `None` if
+    /// there isn't a WKT vlr or evlr.
+    ///
+    /// The WKT is read whether or not [Header::has_wkt_crs] is set, so check the bit if you need
+    /// to know which coordinate reference system takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Header};
+    ///
+    /// assert!(Header::default().wkt().unwrap().is_none());
+    /// let mut builder = Builder::default();
+    /// builder.set_wkt("GEOGCS[\"WGS 84\"]");
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!("GEOGCS[\"WGS 84\"]", header.wkt().unwrap().unwrap());
+    /// ```
+    pub fn wkt(&self) -> Result<Option<String>> {
+        self.all_vlrs()
+            .find(|vlr| is_wkt_vlr(vlr))
+            .map(|vlr| wkt_from_data(&vlr.data))
+            .transpose()
+    }
+
+    /// Returns an error if this header has GeoTIFF keys but no WKT and its point format requires
+    /// WKT.
+    ///
+    /// Only writers check this, so existing files with GeoTIFF keys can still be read.
+    pub(crate) fn verify_wkt(&self) -> Result<()> {
+        if self.point_format().is_extended
+            && self.all_vlrs().any(is_geo_key_directory_vlr)
+            && !self.all_vlrs().any(is_wkt_vlr)
+        {
+            Err(Error::WktRequired(*self.point_format()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Builder {
+    /// Returns the OGC well-known text of this builder's coordinate reference system, or `None`
+    /// if there isn't a WKT vlr or evlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    ///
+    /// let mut builder = Builder::default();
+    /// assert!(builder.wkt().unwrap().is_none());
+    /// builder.set_wkt("GEOGCS[\"WGS 84\"]");
+    /// assert_eq!("GEOGCS[\"WGS 84\"]", builder.wkt().unwrap().unwrap());
+    /// ```
+    pub fn wkt(&self) -> Result<Option<String>> {
+        self.vlrs
+            .iter()
+            .chain(&self.evlrs)
+            .find(|vlr| is_wkt_vlr(vlr))
+            .map(|vlr| wkt_from_data(&vlr.data))
+            .transpose()
+    }
+
+    /// Sets the OGC well-known text of this builder's coordinate reference system.
+    ///
+    /// Any existing WKT vlrs and evlrs are removed, and the wkt bit of the global encoding is
+    /// set. The WKT is stored in a vlr if it fits, otherwise in an evlr, which requires las 1.4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Builder;
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.set_wkt("GEOGCS[\"WGS 84\"]");
+    /// assert!(builder.has_wkt_crs);
+    /// assert_eq!(1, builder.vlrs.len());
+    ///
+    /// builder.set_wkt(&"x".repeat(usize::from(u16::MAX)));
+    /// assert!(builder.vlrs.is_empty());
+    /// assert_eq!(1, builder.evlrs.len());
+    /// ```
+    pub fn set_wkt(&mut self, wkt: &str) {
+        self.vlrs.retain(|vlr| !is_wkt_vlr(vlr));
+        self.evlrs.retain(|vlr| !is_wkt_vlr(vlr));
+        let mut data = wkt.as_bytes().to_vec();
+        data.push(0);
+        let vlr = vlr(WKT_RECORD_ID, "OGC Coordinate System WKT", data);
+        if vlr.has_large_data() {
+            self.evlrs.push(vlr);
+        } else {
+            self.vlrs.push(vlr);
+        }
+        self.has_wkt_crs = true;
+    }
+}
+
+pub(crate) fn is_wkt_vlr(vlr: &Vlr) -> bool {
+    vlr.user_id == USER_ID && vlr.record_id == WKT_RECORD_ID
+}
+
+pub(crate) fn is_geo_key_directory_vlr(vlr: &Vlr) -> bool {
+    vlr.user_id == USER_ID && vlr.record_id == GEO_KEY_DIRECTORY_RECORD_ID
+}
+
+fn wkt_from_data(data: &[u8]) -> Result<String> {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    Ok(std::str::from_utf8(&data[..end])?.to_string())
+}
+
+fn vlr(record_id: u16, description: &str, data: Vec<u8>) -> Vlr {
+    Vlr {
+        user_id: USER_ID.to_string(),
+        record_id,
+        description: description.to_string(),
+        data,
+    }
+}
+
+/// Reads the values of a GeoDoubleParamsTag vlr.
+pub(crate) fn double_params_from_data(data: &[u8]) -> Result<Vec<f64>> {
+    if !data.len().is_multiple_of(8) {
+        return Err(invalid(&format!(
+            "the double params length ({}) is not a multiple of eight",
+            data.len()
+        )));
+    }
+    data.chunks_exact(8)
+        .map(|mut chunk| chunk.read_f64::<LittleEndian>().map_err(Error::from))
+        .collect()
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidGeoKeys(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Writer};
+    use std::io::Cursor;
+
+    fn autzen() -> Header {
+        Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .header()
+            .clone()
+    }
+
+    #[test]
+    fn autzen_keys() {
+        let geo_keys = autzen().geo_keys().unwrap().unwrap();
+        assert_eq!(7, geo_keys.keys.len());
+        assert_eq!(Some(2994), geo_keys.epsg());
+        assert_eq!(
+            Some(&GeoKeyValue::Ascii(
+                "NAD83(HARN) / Oregon Lambert (ft)".to_string()
+            )),
+            geo_keys.get(GT_CITATION)
+        );
+        assert_eq!(
+            Some(&GeoKeyValue::Ascii("NAD83(HARN)".to_string())),
+            geo_keys.get(GEOG_CITATION)
+        );
+        assert_eq!(
+            Some(&GeoKeyValue::Short(9002)),
+            geo_keys.get(PROJ_LINEAR_UNITS)
+        );
+    }
+
+    #[test]
+    fn roundtrip_autzen() {
+        let header = autzen();
+        let geo_keys = header.geo_keys().unwrap().unwrap();
+        let vlrs = geo_keys.to_vlrs().unwrap();
+        assert_eq!(2, vlrs.len());
+        let original = header
+            .vlrs()
+            .iter()
+            .find(|vlr| vlr.record_id == GEO_KEY_DIRECTORY_RECORD_ID)
+            .unwrap();
+        assert_eq!(original.data, vlrs[0].data);
+        assert_eq!(
+            geo_keys,
+            GeoKeys::from_vlrs(&vlrs[0], None, Some(&vlrs[1])).unwrap()
+        );
+    }
+
+    #[test]
+    fn roundtrip_all_value_types() {
+        let geo_keys = GeoKeys {
+            keys: vec![
+                GeoKey {
+                    id: GT_MODEL_TYPE,
+                    value: GeoKeyValue::Short(1),
+                },
+                GeoKey {
+                    id: PCS_CITATION,
+                    value: GeoKeyValue::Ascii("a projection".to_string()),
+                },
+                GeoKey {
+                    id: 3074,
+                    value: GeoKeyValue::Shorts(vec![1, 2, 3]),
+                },
+                GeoKey {
+                    id: 3078,
+                    value: GeoKeyValue::Doubles(vec![1.5, -2.5]),
+                },
+            ],
+        };
+        let vlrs = geo_keys.to_vlrs().unwrap();
+        assert_eq!(3, vlrs.len());
+        let roundtrip = GeoKeys::from_vlrs(&vlrs[0], Some(&vlrs[1]), Some(&vlrs[2])).unwrap();
+        assert_eq!(geo_keys, roundtrip);
+    }
+
+    #[test]
+    fn roundtrip_epsg() {
+        for code in [4326, 4269, 6318, 2994, 32610, 26910] {
+            let vlrs = GeoKeys::from_epsg(code).unwrap().to_vlrs().unwrap();
+            let geo_keys = GeoKeys::from_vlrs(&vlrs[0], None, None).unwrap();
+            assert_eq!(Some(code), geo_keys.epsg());
+        }
+    }
+
+    #[test]
+    fn from_epsg_model_type() {
+        let model_type = |code| {
+            GeoKeys::from_epsg(code)
+                .unwrap()
+                .get(GT_MODEL_TYPE)
+                .cloned()
+        };
+        assert_eq!(Some(GeoKeyValue::Short(2)), model_type(4326));
+        assert_eq!(Some(GeoKeyValue::Short(2)), model_type(7844));
+        assert_eq!(Some(GeoKeyValue::Short(1)), model_type(32610));
+        assert!(matches!(
+            GeoKeys::from_epsg(4978),
+            Err(Error::UnknownEpsg(4978))
+        ));
+        assert!(matches!(
+            GeoKeys::from_epsg(4087),
+            Err(Error::UnknownEpsg(4087))
+        ));
+    }
+
+    #[test]
+    fn user_defined() {
+        let geo_keys = GeoKeys {
+            keys: vec![GeoKey {
+                id: PROJECTED_CS_TYPE,
+                value: GeoKeyValue::Short(USER_DEFINED),
+            }],
+        };
+        assert_eq!(None, geo_keys.epsg());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut vlr = GeoKeys::from_epsg(4326)
+            .unwrap()
+            .to_vlrs()
+            .unwrap()
+            .remove(0);
+        assert!(GeoKeys::from_vlrs(&vlr, None, None).is_ok());
+        vlr.data.truncate(10);
+        assert!(GeoKeys::from_vlrs(&vlr, None, None).is_err());
+        let vlrs = GeoKeys {
+            keys: vec![GeoKey {
+                id: GT_CITATION,
+                value: GeoKeyValue::Ascii("citation".to_string()),
+            }],
+        }
+        .to_vlrs()
+        .unwrap();
+        assert!(GeoKeys::from_vlrs(&vlrs[0], None, None).is_err());
+    }
+
+    #[test]
+    fn wkt_roundtrip() {
+        let mut builder = Builder::from((1, 4));
+        let format = crate::point::Format::new(6).unwrap();
+        builder.point_format = format;
+        builder.set_wkt("GEOGCS[\"WGS 84\"]");
+        builder.set_wkt("PROJCS[\"WGS 84 / UTM zone 10N\"]");
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let mut point = crate::Point::default();
+        point.conform_to(&format);
+        writer.write_point(point).unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let header = reader.header();
+        assert!(header.has_wkt_crs());
+        assert_eq!(1, header.vlrs().len());
+        assert_eq!(
+            "PROJCS[\"WGS 84 / UTM zone 10N\"]",
+            header.wkt().unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn wkt_evlr() {
+        let wkt = "x".repeat(usize::from(u16::MAX));
+        let mut builder = Builder::from((1, 2));
+        builder.set_wkt(&wkt);
+        assert!(builder.clone().into_header().is_err());
+        builder.version = (1, 4).into();
+        let header = builder.into_header().unwrap();
+        assert!(header.vlrs().is_empty());
+        assert_eq!(wkt, header.wkt().unwrap().unwrap());
+    }
+
+    #[test]
+    fn wkt_required() {
+        let mut builder = Builder::from(autzen());
+        builder.version = (1, 4).into();
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        let header = builder.clone().into_header().unwrap();
+        assert!(matches!(
+            Writer::new(Cursor::new(Vec::new()), header),
+            Err(Error::WktRequired(_))
+        ));
+        builder.set_wkt("PROJCS[\"NAD83(HARN) / Oregon Lambert (ft)\"]");
+        let header = builder.into_header().unwrap();
+        let _ = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    }
+
+    #[test]
+    fn wkt_not_required_without_crs() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        let header = builder.into_header().unwrap();
+        let _ = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    }
+
+    #[test]
+    fn read_geo_keys_without_wkt() {
+        let mut builder = Builder::from(autzen());
+        builder.version = (1, 4).into();
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        let mut cursor = Cursor::new(Vec::new());
+        builder
+            .into_header()
+            .unwrap()
+            .write_to(&mut cursor)
+            .unwrap();
+        cursor.set_position(0);
+        let reader = Reader::new(cursor).unwrap();
+        assert!(reader.header().geo_keys().unwrap().is_some());
+        assert!(reader.header().wkt().unwrap().is_none());
+    }
+}
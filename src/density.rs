@@ -0,0 +1,509 @@
+//! Point density and coverage per grid cell.
+//!
+//! [Density] counts all points, first returns, and points of each classification in every cell of
+//! a [Grid], and [Density::report] summarizes those counts for delivery checks:
+//!
+//! ```
+//! use las::{density::{Density, Options}, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let density = Density::from_reader(&mut reader, 100.).unwrap();
+//! let report = density.report(&Options { min_density: 0.001, ..Default::default() });
+//! assert_eq!(106, report.number_of_points);
+//! assert!(report.void_cells <= report.cells);
+//! for cell in &report.flagged {
+//!     assert!(cell.density < 0.001);
+//! }
+//! ```
+//!
+//! Densities are in points per square unit of the horizontal coordinates, e.g. points per square
+//! metre for data in metres. Density maps can be written with the [raster](crate::raster) writers.
+
+use crate::{raster::Grid, Bounds, Point, Reader, Result};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Point counts for every cell of a grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Density {
+    grid: Grid,
+    cells: Vec<Counts>,
+}
+
+/// The points in one cell.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Counts {
+    /// The number of points.
+    pub points: u64,
+
+    /// The number of first returns.
+    pub first_returns: u64,
+
+    /// The number of points with each classification code.
+    pub classifications: BTreeMap<u8, u64>,
+}
+
+/// Which points to measure density with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Returns {
+    /// All points.
+    #[default]
+    All,
+
+    /// Only first returns, i.e. pulse density.
+    First,
+}
+
+/// Report options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// Cells with a density below this are flagged.
+    pub min_density: f64,
+
+    /// The points that count towards the flagging density.
+    pub returns: Returns,
+
+    /// The percentiles, from zero to one hundred, to include in each summary.
+    pub percentiles: Vec<f64>,
+}
+
+/// A summary of the density and coverage of a grid.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    /// The grid.
+    pub grid: Grid,
+
+    /// The number of points.
+    pub number_of_points: u64,
+
+    /// The number of first returns.
+    pub first_returns: u64,
+
+    /// The number of cells.
+    pub cells: u64,
+
+    /// The number of cells without any points.
+    pub void_cells: u64,
+
+    /// The density of all points in the cells with points.
+    pub density: Summary,
+
+    /// The density of first returns in the cells with points.
+    pub first_return_density: Summary,
+
+    /// The density of each classification in the cells with points.
+    pub classification_density: BTreeMap<u8, Summary>,
+
+    /// The cells with a density below the threshold, including void cells.
+    pub flagged: Vec<Flagged>,
+}
+
+/// Summary statistics of per-cell densities.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Summary {
+    /// The number of cells.
+    pub cells: u64,
+
+    /// The smallest density.
+    pub min: f64,
+
+    /// The largest density.
+    pub max: f64,
+
+    /// The mean density.
+    pub mean: f64,
+
+    /// The requested percentiles.
+    pub percentiles: Vec<Percentile>,
+}
+
+/// One percentile of per-cell densities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Percentile {
+    /// The percentile, from zero to one hundred.
+    pub percentile: f64,
+
+    /// The density at that percentile.
+    pub density: f64,
+}
+
+/// A cell with a density below the threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Flagged {
+    /// The cell's column.
+    pub column: usize,
+
+    /// The cell's row, from the top.
+    pub row: usize,
+
+    /// The x coordinate of the cell's center.
+    pub x: f64,
+
+    /// The y coordinate of the cell's center.
+    pub y: f64,
+
+    /// The cell's density.
+    pub density: f64,
+}
+
+impl Density {
+    /// Creates empty counts for a grid over the bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::Density, Header};
+    ///
+    /// let density = Density::new(&Header::default().bounds(), 1.).unwrap();
+    /// assert_eq!(1, density.grid().len());
+    /// ```
+    pub fn new(bounds: &Bounds, resolution: f64) -> Result<Density> {
+        let grid = Grid::new(bounds, resolution)?;
+        Ok(Density {
+            grid,
+            cells: vec![Counts::default(); grid.len()],
+        })
+    }
+
+    /// Counts the reader's remaining points on a grid over the header's bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::Density, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let density = Density::from_reader(&mut reader, 10.).unwrap();
+    /// assert!(density.grid().len() > 1);
+    /// ```
+    pub fn from_reader(reader: &mut Reader, resolution: f64) -> Result<Density> {
+        let mut density = Density::new(&reader.header().bounds(), resolution)?;
+        for point in reader.points() {
+            density.add(&point?);
+        }
+        Ok(density)
+    }
+
+    /// Adds a point, if it's inside the grid.
+    ///
+    /// Points with a return number of one are first returns, as are points with a return number
+    /// of zero, which some writers use for single returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::Density, Header, Point};
+    ///
+    /// let mut density = Density::new(&Header::default().bounds(), 1.).unwrap();
+    /// density.add(&Point { return_number: 1, ..Default::default() });
+    /// density.add(&Point { return_number: 2, ..Default::default() });
+    /// assert_eq!(2, density.counts(0, 0).points);
+    /// assert_eq!(1, density.counts(0, 0).first_returns);
+    /// ```
+    pub fn add(&mut self, point: &Point) {
+        if let Some((column, row)) = self.grid.cell(point.x, point.y) {
+            let counts = &mut self.cells[self.grid.index(column, row)];
+            counts.points += 1;
+            if point.return_number <= 1 {
+                counts.first_returns += 1;
+            }
+            *counts
+                .classifications
+                .entry(point.classification.into())
+                .or_default() += 1;
+        }
+    }
+
+    /// Returns the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::Density, Header};
+    ///
+    /// let density = Density::new(&Header::default().bounds(), 2.).unwrap();
+    /// assert_eq!(2., density.grid().resolution);
+    /// ```
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Returns the counts of a cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::Density, Header};
+    ///
+    /// let density = Density::new(&Header::default().bounds(), 1.).unwrap();
+    /// assert_eq!(0, density.counts(0, 0).points);
+    /// ```
+    pub fn counts(&self, column: usize, row: usize) -> &Counts {
+        &self.cells[self.grid.index(column, row)]
+    }
+
+    /// Returns the density of every cell, in row-major order from the top row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::{Density, Returns}, raster, Header, Point};
+    ///
+    /// let mut density = Density::new(&Header::default().bounds(), 0.5).unwrap();
+    /// density.add(&Point::default());
+    /// let values = density.densities(Returns::All);
+    /// assert_eq!(vec![4.], values);
+    ///
+    /// let values: Vec<_> = values.into_iter().map(Some).collect();
+    /// let mut asc = Vec::new();
+    /// raster::write_asc(&mut asc, density.grid(), &values).unwrap();
+    /// ```
+    pub fn densities(&self, returns: Returns) -> Vec<f64> {
+        let area = self.grid.cell_area();
+        self.cells
+            .iter()
+            .map(|counts| counts.count(returns) as f64 / area)
+            .collect()
+    }
+
+    /// Summarizes the densities and flags cells below a threshold.
+    ///
+    /// Summaries only include cells with at least one point, so that the area around
+    /// irregularly-shaped data doesn't drag them down. Void cells are counted separately and are
+    /// always flagged if the threshold is above zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{density::{Density, Options}, Bounds, Point, Vector};
+    ///
+    /// let bounds = Bounds { min: Vector::default(), max: Vector { x: 1.5, y: 0., z: 0. } };
+    /// let mut density = Density::new(&bounds, 1.).unwrap();
+    /// density.add(&Point::default());
+    /// let report = density.report(&Options::default());
+    /// assert_eq!(2, report.cells);
+    /// assert_eq!(1, report.void_cells);
+    /// assert_eq!(1, report.flagged.len());
+    /// assert_eq!(1., report.density.mean);
+    /// ```
+    pub fn report(&self, options: &Options) -> Report {
+        let area = self.grid.cell_area();
+        let occupied: Vec<&Counts> = self.cells.iter().filter(|c| c.points > 0).collect();
+        let density = |f: &dyn Fn(&Counts) -> u64| {
+            summarize(
+                occupied.iter().map(|c| f(c) as f64 / area).collect(),
+                &options.percentiles,
+            )
+        };
+        let mut classifications: Vec<u8> = occupied
+            .iter()
+            .flat_map(|c| c.classifications.keys().copied())
+            .collect();
+        classifications.sort_unstable();
+        classifications.dedup();
+
+        let mut flagged = Vec::new();
+        for row in 0..self.grid.rows {
+            for column in 0..self.grid.columns {
+                let count = self.counts(column, row).count(options.returns);
+                let density = count as f64 / area;
+                if density < options.min_density {
+                    let (x, y) = self.grid.center(column, row);
+                    flagged.push(Flagged {
+                        column,
+                        row,
+                        x,
+                        y,
+                        density,
+                    });
+                }
+            }
+        }
+
+        Report {
+            grid: self.grid,
+            number_of_points: self.cells.iter().map(|c| c.points).sum(),
+            first_returns: self.cells.iter().map(|c| c.first_returns).sum(),
+            cells: self.cells.len() as u64,
+            void_cells: (self.cells.len() - occupied.len()) as u64,
+            density: density(&|c| c.points),
+            first_return_density: density(&|c| c.first_returns),
+            classification_density: classifications
+                .into_iter()
+                .map(|class| {
+                    let summary = density(&|c| c.classifications.get(&class).copied().unwrap_or(0));
+                    (class, summary)
+                })
+                .collect(),
+            flagged,
+        }
+    }
+}
+
+impl Counts {
+    fn count(&self, returns: Returns) -> u64 {
+        match returns {
+            Returns::All => self.points,
+            Returns::First => self.first_returns,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            min_density: 1.,
+            returns: Returns::All,
+            percentiles: vec![5., 25., 50., 75., 95.],
+        }
+    }
+}
+
+fn summarize(mut values: Vec<f64>, percentiles: &[f64]) -> Summary {
+    if values.is_empty() {
+        return Summary::default();
+    }
+    values.sort_by(f64::total_cmp);
+    let last = values.len() - 1;
+    Summary {
+        cells: values.len() as u64,
+        min: values[0],
+        max: values[last],
+        mean: values.iter().sum::<f64>() / values.len() as f64,
+        percentiles: percentiles
+            .iter()
+            .map(|&percentile| {
+                let rank = percentile.clamp(0., 100.) / 100. * last as f64;
+                let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+                let density =
+                    values[lower] + (values[upper] - values[lower]) * (rank - lower as f64);
+                Percentile {
+                    percentile,
+                    density,
+                }
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, Vector};
+
+    fn density() -> Density {
+        let bounds = Bounds {
+            min: Vector::default(),
+            max: Vector {
+                x: 2.5,
+                y: 0.5,
+                z: 0.,
+            },
+        };
+        let mut density = Density::new(&bounds, 1.).unwrap();
+        for (x, count) in [(0.5, 1), (1.5, 4)] {
+            for i in 0..count {
+                density.add(&Point {
+                    x,
+                    y: 0.5,
+                    return_number: if i % 2 == 0 { 1 } else { 2 },
+                    classification: if i == 0 {
+                        Classification::Ground
+                    } else {
+                        Classification::HighVegetation
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+        density
+    }
+
+    #[test]
+    fn counts() {
+        let density = density();
+        assert_eq!(3, density.grid().columns);
+        let counts = density.counts(1, 0);
+        assert_eq!(4, counts.points);
+        assert_eq!(2, counts.first_returns);
+        assert_eq!(Some(&1), counts.classifications.get(&2));
+        assert_eq!(Some(&3), counts.classifications.get(&5));
+        assert_eq!(vec![1., 2., 0.], density.densities(Returns::First));
+    }
+
+    #[test]
+    fn report() {
+        let report = density().report(&Options {
+            min_density: 2.,
+            returns: Returns::All,
+            percentiles: vec![0., 50., 100.],
+        });
+        assert_eq!(5, report.number_of_points);
+        assert_eq!(3, report.first_returns);
+        assert_eq!(3, report.cells);
+        assert_eq!(1, report.void_cells);
+        assert_eq!(2, report.density.cells);
+        assert_eq!(2.5, report.density.mean);
+        let percentiles: Vec<f64> = report
+            .density
+            .percentiles
+            .iter()
+            .map(|p| p.density)
+            .collect();
+        assert_eq!(vec![1., 2.5, 4.], percentiles);
+        assert_eq!(1., report.classification_density[&2].max);
+        assert_eq!(0., report.classification_density[&5].min);
+        let flagged: Vec<usize> = report.flagged.iter().map(|f| f.column).collect();
+        assert_eq!(vec![0, 2], flagged);
+        assert_eq!(0.5, report.flagged[0].x);
+    }
+
+    #[test]
+    fn first_return_threshold() {
+        let options = Options {
+            min_density: 2.5,
+            returns: Returns::All,
+            percentiles: Vec::new(),
+        };
+        assert_eq!(2, density().report(&options).flagged.len());
+        let options = Options {
+            returns: Returns::First,
+            ..options
+        };
+        assert_eq!(3, density().report(&options).flagged.len());
+    }
+
+    #[test]
+    fn aligned_bounds_have_no_edge_voids() {
+        let bounds = Bounds {
+            min: Vector::default(),
+            max: Vector {
+                x: 4.,
+                y: 4.,
+                z: 0.,
+            },
+        };
+        let mut density = Density::new(&bounds, 2.).unwrap();
+        for (x, y) in [(0., 0.), (4., 0.), (0., 4.), (4., 4.)] {
+            density.add(&Point {
+                x,
+                y,
+                ..Default::default()
+            });
+        }
+        let report = density.report(&Options {
+            min_density: 0.25,
+            ..Default::default()
+        });
+        assert_eq!(4, report.cells);
+        assert_eq!(0, report.void_cells);
+        assert!(report.flagged.is_empty());
+    }
+}
@@ -0,0 +1,406 @@
+//! Edit point attributes with rules.
+//!
+//! A [Rule] sets a dimension or extra bytes field to the value of an
+//! [expression](crate::filters::expression), optionally only for the points that match a
+//! condition. Rules are written as `TARGET = VALUE` or `TARGET = VALUE where CONDITION`:
+//!
+//! ```
+//! use las::{edit::Rule, point::{Classification, Format}, Point};
+//!
+//! let rule: Rule = "Classification = Ground where Classification == Unclassified && Z < 10"
+//!     .parse()
+//!     .unwrap();
+//! let mut point = Point { classification: Classification::Unclassified, z: 5., ..Default::default() };
+//! assert!(rule.apply(&mut point, &Format::default()).unwrap());
+//! assert_eq!(Classification::Ground, point.classification);
+//! ```
+//!
+//! Use [edit] to apply rules to every point of a [Reader] as they're written to a [Writer]:
+//!
+//! ```
+//! use las::{edit::{self, Rule}, Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let rules = [Rule::with_header("Withheld = 1 where Classification == LowPoint || Classification == HighNoise", reader.header()).unwrap()];
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+//! edit::edit(&mut reader, &mut writer, &rules).unwrap();
+//! ```
+//!
+//! # Overlap points
+//!
+//! Point formats 0 through 5 don't have an overlap flag, so overlap points are stored with
+//! classification 12 and read as [Unclassified](crate::point::Classification::Unclassified)
+//! points with [Point::is_overlap] set. Rules follow the same convention, whatever the point
+//! format:
+//!
+//! - Setting the classification to 12 (`OverlapPoints`) sets the overlap flag and the
+//!   `Unclassified` classification, instead of failing with
+//!   [Error::OverlapClassification].
+//! - `Classification == 12` never matches; use `Overlap` in conditions instead.
+//!
+//! Rules are applied for the point format that the points will be written with. In formats 0
+//! through 5 the overlap flag and the classification share one field, so setting any other
+//! classification clears the overlap flag, just as rewriting classification 12 would. In formats 6
+//! through 10 they're independent, so the overlap flag is kept.
+
+use crate::{
+    extra_bytes::{self, Descriptor},
+    filters::expression::Expression,
+    point::{Classification, Dimension, Format},
+    Error, Header, Point, Reader, Result, Writer,
+};
+use std::{
+    io::{Seek, Write},
+    str::FromStr,
+};
+
+/// The classification code for overlap points.
+const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
+
+/// Sets a point attribute to the value of an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    target: Target,
+    value: Expression,
+    condition: Option<Expression>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Target {
+    Dimension(Dimension),
+    ExtraBytes(usize, Descriptor),
+}
+
+impl Rule {
+    /// Parses a rule that may also use the header's extra bytes fields and classification names.
+    ///
+    /// Extra bytes fields can be targets as well as part of the expressions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::Rule, Header};
+    /// let rule = Rule::with_header("Intensity = Intensity * 2", &Header::default()).unwrap();
+    /// ```
+    pub fn with_header(s: &str, header: &Header) -> Result<Rule> {
+        parse(s, &header.extra_bytes()?, |s| {
+            Expression::with_header(s, header)
+        })
+    }
+
+    /// Applies this rule to a point that will be written with the given point format.
+    ///
+    /// Returns true if the point matched the condition and the value was set. Points without a
+    /// value for the expression, e.g. because they don't have a dimension it uses, are left
+    /// alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{edit::Rule, point::{Classification, Format}, Point};
+    ///
+    /// let rule: Rule = "Classification = 12 where Z > 100".parse().unwrap();
+    /// let mut point = Point { classification: Classification::Ground, z: 150., ..Default::default() };
+    /// assert!(rule.apply(&mut point, &Format::new(1).unwrap()).unwrap());
+    /// assert!(point.is_overlap);
+    /// assert_eq!(Classification::Unclassified, point.classification);
+    /// ```
+    pub fn apply(&self, point: &mut Point, format: &Format) -> Result<bool> {
+        if let Some(condition) = &self.condition {
+            if !condition.matches(point) {
+                return Ok(false);
+            }
+        }
+        let value = match self.value.evaluate(point) {
+            Some(value) => value,
+            None => return Ok(false),
+        };
+        match &self.target {
+            Target::Dimension(Dimension::Classification) => {
+                let code = value.round().clamp(0., 255.) as u8;
+                if code == OVERLAP_CLASSIFICATION_CODE {
+                    point.is_overlap = true;
+                    point.classification = Classification::Unclassified;
+                } else {
+                    point.classification = Classification::new(code)?;
+                    if !format.is_extended {
+                        point.is_overlap = false;
+                    }
+                }
+            }
+            Target::Dimension(dimension) => dimension.set_value(point, value)?,
+            Target::ExtraBytes(offset, descriptor) => {
+                let end = offset + descriptor.len();
+                if point.extra_bytes.len() < end {
+                    point.extra_bytes.resize(end, 0);
+                }
+                descriptor.encode(value, &mut point.extra_bytes[*offset..end])?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Rule> {
+        parse(s, &[], str::parse)
+    }
+}
+
+/// Applies the rules, in order, to each of the reader's remaining points and writes them.
+///
+/// Points are [conformed](Point::conform_to) to the writer's point format, and the rules are
+/// applied for that format. Returns the number of points changed by at least one rule.
+///
+/// # Examples
+///
+/// ```
+/// use las::{edit::{self, Rule}, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let rules = ["Classification = Ground where Z < 410".parse::<Rule>().unwrap()];
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+/// let n = edit::edit(&mut reader, &mut writer, &rules).unwrap();
+/// assert!(n > 0);
+/// ```
+pub fn edit<W: 'static + Write + Seek + Send>(
+    reader: &mut Reader,
+    writer: &mut Writer<W>,
+    rules: &[Rule],
+) -> Result<u64> {
+    let format = *writer.header().point_format();
+    let mut count = 0;
+    for point in reader.points() {
+        let mut point = point?;
+        point.conform_to(&format);
+        let mut edited = false;
+        for rule in rules {
+            edited |= rule.apply(&mut point, &format)?;
+        }
+        if edited {
+            count += 1;
+        }
+        writer.write_point(point)?;
+    }
+    Ok(count)
+}
+
+fn parse(
+    s: &str,
+    descriptors: &[Descriptor],
+    expression: impl Fn(&str) -> Result<Expression>,
+) -> Result<Rule> {
+    let invalid = |message: &str| Error::InvalidRule(format!("{}: `{}`", message, s));
+    let (assignment, condition) = match split_where(s) {
+        Some((assignment, condition)) => (assignment, Some(condition)),
+        None => (s, None),
+    };
+    let (target, value) = assignment
+        .split_once('=')
+        .filter(|(_, value)| !value.starts_with('='))
+        .ok_or_else(|| invalid("expected `TARGET = VALUE`"))?;
+    let target = target.trim().trim_matches('"');
+    let target = if let Ok(dimension) = target.parse::<Dimension>() {
+        Target::Dimension(dimension)
+    } else if let Some((offset, descriptor)) = extra_bytes::find(descriptors, target) {
+        Target::ExtraBytes(offset, descriptor.clone())
+    } else {
+        return Err(invalid(&format!("unknown target `{}`", target)));
+    };
+    let value = expression(value.trim())?;
+    let condition = condition
+        .map(|condition| expression(condition.trim()))
+        .transpose()?;
+    Ok(Rule {
+        target,
+        value,
+        condition,
+    })
+}
+
+/// Splits a rule at the `where` keyword, if it has one outside of double quotes.
+fn split_where(s: &str) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if !quoted
+            && s[i..]
+                .get(..5)
+                .is_some_and(|word| word.eq_ignore_ascii_case("where"))
+            && !s[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && !s[i + 5..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        {
+            return Some((&s[..i], &s[i + 5..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extra_bytes::DataType, Builder};
+    use std::io::Cursor;
+
+    fn legacy() -> Format {
+        Format::new(1).unwrap()
+    }
+
+    fn extended() -> Format {
+        Format::new(6).unwrap()
+    }
+
+    #[test]
+    fn remap_class_below_z() {
+        let rule: Rule = "Classification = 2 where Classification == 1 && Z < 10"
+            .parse()
+            .unwrap();
+        let mut low = Point {
+            classification: Classification::Unclassified,
+            z: 5.,
+            ..Default::default()
+        };
+        let mut high = Point {
+            z: 15.,
+            ..low.clone()
+        };
+        assert!(rule.apply(&mut low, &legacy()).unwrap());
+        assert!(!rule.apply(&mut high, &legacy()).unwrap());
+        assert_eq!(Classification::Ground, low.classification);
+        assert_eq!(Classification::Unclassified, high.classification);
+    }
+
+    #[test]
+    fn withheld_by_class() {
+        let rule: Rule = "Withheld = 1 WHERE Classification == 7 || Classification == 18"
+            .parse()
+            .unwrap();
+        for (classification, withheld) in [
+            (Classification::LowPoint, true),
+            (Classification::HighNoise, true),
+            (Classification::Ground, false),
+        ] {
+            let mut point = Point {
+                classification,
+                ..Default::default()
+            };
+            let _ = rule.apply(&mut point, &extended()).unwrap();
+            assert_eq!(withheld, point.is_withheld);
+        }
+    }
+
+    #[test]
+    fn overlap_classification() {
+        let rule: Rule = "Classification = OverlapPoints".parse().unwrap();
+        let mut point = Point {
+            classification: Classification::Ground,
+            ..Default::default()
+        };
+        assert!(rule.apply(&mut point, &extended()).unwrap());
+        assert!(point.is_overlap);
+        assert_eq!(Classification::Unclassified, point.classification);
+        assert!(!"Classification == 12"
+            .parse::<Expression>()
+            .unwrap()
+            .matches(&point));
+    }
+
+    #[test]
+    fn classify_overlap_point_per_format() {
+        let rule: Rule = "Classification = Ground where Overlap".parse().unwrap();
+        let overlap = Point {
+            is_overlap: true,
+            classification: Classification::Unclassified,
+            ..Default::default()
+        };
+
+        let mut point = overlap.clone();
+        assert!(rule.apply(&mut point, &legacy()).unwrap());
+        assert!(!point.is_overlap);
+        assert_eq!(Classification::Ground, point.classification);
+
+        let mut point = overlap;
+        assert!(rule.apply(&mut point, &extended()).unwrap());
+        assert!(point.is_overlap);
+        assert_eq!(Classification::Ground, point.classification);
+    }
+
+    #[test]
+    fn extra_bytes_target() {
+        let mut builder = Builder::default();
+        builder
+            .vlrs
+            .push(extra_bytes::vlr(&[Descriptor::new("height", DataType::F32)]).unwrap());
+        builder.point_format.extra_bytes = 4;
+        let header = builder.into_header().unwrap();
+        let rule = Rule::with_header("height = Z * 2", &header).unwrap();
+        let mut point = Point {
+            z: 1.5,
+            ..Default::default()
+        };
+        assert!(rule.apply(&mut point, header.point_format()).unwrap());
+        let descriptors = header.extra_bytes().unwrap();
+        assert_eq!(Some(3.), extra_bytes::value(&descriptors, "height", &point));
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!("Classification".parse::<Rule>().is_err());
+        assert!("Classification == 2".parse::<Rule>().is_err());
+        assert!("Foo = 2".parse::<Rule>().is_err());
+        assert!("Classification = 2 where".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn where_inside_names() {
+        assert_eq!(None, split_where("\"somewhere\" = 1"));
+        assert_eq!(None, split_where("Nowhere = Whereas"));
+        assert_eq!(
+            Some(("\"where\" = 1 ", " Z > 0")),
+            split_where("\"where\" = 1 where Z > 0")
+        );
+    }
+
+    #[test]
+    fn edit_to_legacy_writer() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = extended();
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        for z in [1., 20.] {
+            let mut point = Point {
+                z,
+                is_overlap: true,
+                classification: Classification::Unclassified,
+                gps_time: Some(0.),
+                ..Default::default()
+            };
+            point.conform_to(&extended());
+            writer.write_point(point).unwrap();
+        }
+        writer.close().unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = legacy();
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let rules = ["Classification = Ground where Z < 10".parse().unwrap()];
+        assert_eq!(1, edit(&mut reader, &mut writer, &rules).unwrap());
+        writer.close().unwrap();
+        let points = Reader::new(writer.into_inner().unwrap())
+            .unwrap()
+            .points()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(Classification::Ground, points[0].classification);
+        assert!(!points[0].is_overlap);
+        assert_eq!(Classification::Unclassified, points[1].classification);
+        assert!(points[1].is_overlap);
+    }
+}
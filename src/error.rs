@@ -1,4 +1,4 @@
-use crate::{point::Format, Transform, Version};
+use crate::{point::Format, GpsTimeType, Transform, Version};
 use thiserror::Error;
 
 /// Crate-specific error enum.
@@ -9,18 +9,46 @@ pub enum Error {
     #[error("the writer is closed")]
     ClosedWriter,
 
+    /// A coordinate transform could not transform a point.
+    #[error("coordinate transform failed: {0}")]
+    CoordinateTransform(String),
+
+    /// The coordinate reference systems of las data to be combined do not match.
+    #[error("the coordinate reference systems do not match")]
+    CrsConflict,
+
+    /// The gps time types of las data to be combined do not match.
+    #[error("the gps time types do not match: {0:?} and {1:?}")]
+    GpsTimeTypeMismatch(GpsTimeType, GpsTimeType),
+
     /// The header size, as computed, is too large.
     #[error("the header is too large ({0} bytes) to convert to a raw header")]
     HeaderTooLarge(usize),
 
+    /// The cell size of a grid must be positive and finite.
+    #[error("invalid cell size: {0}")]
+    InvalidCellSize(f64),
+
     /// An invalid classification number.
     #[error("invalid classification: {0}")]
     InvalidClassification(u8),
 
+    /// The Classification Lookup vlr could not be parsed.
+    #[error("invalid classification lookup: {0}")]
+    InvalidClassificationLookup(String),
+
+    /// The text column spec could not be parsed.
+    #[error("invalid text columns: {0}")]
+    InvalidColumns(String),
+
     /// The file signature is not LASF.
     #[error("the file signature is not 'LASF': {0:?}")]
     InvalidFileSignature([u8; 4]),
 
+    /// The GeoTIFF keys could not be parsed.
+    #[error("invalid geo keys: {0}")]
+    InvalidGeoKeys(String),
+
     /// The value can't have the inverse transform applied.
     #[error("the transform {transform} cannot be inversely applied to {n}")]
     InvalidInverseTransform {
@@ -31,6 +59,20 @@ pub enum Error {
         transform: Transform,
     },
 
+    /// The decimation parameters are out of range.
+    #[error("invalid decimation: {0:?}")]
+    InvalidDecimation(crate::filters::Decimation),
+
+    /// The filter expression could not be parsed.
+    #[error("invalid expression at position {position}: {message}")]
+    InvalidExpression {
+        /// The byte offset in the expression where the problem was found.
+        position: usize,
+
+        /// What went wrong.
+        message: String,
+    },
+
     /// This is an invalid point format.
     ///
     /// It has a combination of options that can't exist.
@@ -41,10 +83,65 @@ pub enum Error {
     #[error("invalid format number: {0}")]
     InvalidPointFormatNumber(u8),
 
+    /// The precision is not a finite, positive number.
+    #[error("invalid precision: {0}")]
+    InvalidPrecision(f64),
+
+    /// The ply data could not be parsed.
+    #[error("invalid ply: {0}")]
+    InvalidPly(String),
+
+    /// The tile buffer is negative or not finite.
+    #[error("invalid tile buffer: {0}")]
+    InvalidTileBuffer(f64),
+
+    /// The tile size is not a finite, positive number.
+    #[error("invalid tile size: {0}")]
+    InvalidTileSize(f64),
+
+    /// Raster values don't match their grid.
+    #[error("invalid raster: {0}")]
+    InvalidRaster(String),
+
+    /// An edit rule could not be parsed.
+    #[error("invalid edit rule: {0}")]
+    InvalidRule(String),
+
     /// This is not a valid scanner channel
     #[error("invalid scanner channel: {0}")]
     InvalidScannerChannel(u8),
 
+    /// A line of delimited text could not be parsed into a point.
+    #[error("invalid text on line {line}: {message}")]
+    InvalidText {
+        /// The line number, starting at one.
+        line: u64,
+
+        /// What went wrong.
+        message: String,
+    },
+
+    /// The UTM zone is not between 1 and 60.
+    #[error("invalid utm zone: {0}")]
+    InvalidUtmZone(u8),
+
+    /// A vlr's data could not be decoded by its [VlrCodec](crate::codec::VlrCodec).
+    #[error("invalid vlr with user id {user_id} and record id {record_id}: {message}")]
+    InvalidVlr {
+        /// The user id of the vlr.
+        user_id: String,
+
+        /// The record id of the vlr.
+        record_id: u16,
+
+        /// What went wrong.
+        message: String,
+    },
+
+    /// Waveform packet descriptors or data packets could not be read or written.
+    #[error("invalid waveform: {0}")]
+    InvalidWaveform(String),
+
     /// [std::io::Error]
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -63,6 +160,10 @@ pub enum Error {
     #[error("laszip vlr not found")]
     LasZipVlrNotFound,
 
+    /// There are no ground points to build a ground surface from.
+    #[error("there are no ground points")]
+    NoGroundPoints,
+
     /// This string is not ASCII.
     #[error("this string is not ascii: {0}")]
     NotAscii(String),
@@ -125,6 +226,16 @@ pub enum Error {
         len: usize,
     },
 
+    /// Too many cells in a gridded surface.
+    #[error("too many cells: {columns} columns by {rows} rows")]
+    TooManyCells {
+        /// The number of columns.
+        columns: u64,
+
+        /// The number of rows.
+        rows: u64,
+    },
+
     /// Too many extended variable length records.
     #[error("too many extended variable length records: {0}")]
     TooManyEvlrs(usize),
@@ -147,6 +258,18 @@ pub enum Error {
     #[error(transparent)]
     TryFromIntError(#[from] std::num::TryFromIntError),
 
+    /// This is not the name of a point dimension.
+    #[error("unknown dimension: {0}")]
+    UnknownDimension(String),
+
+    /// This EPSG code can't be turned into GeoTIFF keys.
+    #[error("unknown or unsupported EPSG code: {0}")]
+    UnknownEpsg(u16),
+
+    /// There's no extra bytes field with this name.
+    #[error("unknown extra bytes field: {0}")]
+    UnknownExtraBytes(String),
+
     /// Feature is not supported by version.
     #[error("feature {feature} is not supported by version {version}")]
     UnsupportedFeature {
@@ -171,10 +294,27 @@ pub enum Error {
     #[error(transparent)]
     Utf8(#[from] std::str::Utf8Error),
 
-    /// Wkt is required for this point format.
-    #[error("wkt is required for this point format: {0}")]
+    /// The header has a GeoTIFF coordinate reference system, but its point format (6 through 10)
+    /// requires WKT.
+    ///
+    /// Writers return this for headers with GeoTIFF keys and no WKT. Headers without any
+    /// coordinate reference system are still written, and readers don't check.
+    #[error(
+        "point format {0} requires a wkt crs, but the header has a GeoTIFF crs and no wkt; \
+         set a wkt crs or remove the GeoTIFF keys"
+    )]
     WktRequired(Format),
 
+    /// Two vlrs with the same user id and record id have different data.
+    #[error("vlrs with user id {user_id} and record id {record_id} have different data")]
+    VlrConflict {
+        /// The user id of the conflicting vlrs.
+        user_id: String,
+
+        /// The record id of the conflicting vlrs.
+        record_id: u16,
+    },
+
     /// The vlr data is too long.
     #[error("the vlr is too long: {0}")]
     VlrTooLong(usize),
@@ -0,0 +1,535 @@
+//! Describe the extra bytes at the end of each point record.
+//!
+//! The extra bytes vlr (user id `LASF_Spec`, record id 4) holds one [Descriptor] for each
+//! field stored in [Point::extra_bytes](crate::Point::extra_bytes), in order:
+//!
+//! ```
+//! use las::{extra_bytes::{self, DataType, Descriptor}, point::Format, Builder, Point};
+//!
+//! let descriptor = Descriptor::new("height", DataType::U16);
+//! let mut builder = Builder::from((1, 4));
+//! builder.point_format = Format { extra_bytes: 2, ..Default::default() };
+//! builder.vlrs.push(extra_bytes::vlr(&[descriptor]).unwrap());
+//! let header = builder.into_header().unwrap();
+//!
+//! let descriptors = header.extra_bytes().unwrap();
+//! let point = Point { extra_bytes: vec![42, 0], ..Default::default() };
+//! assert_eq!(Some(42.), extra_bytes::value(&descriptors, "height", &point));
+//! ```
+
+use crate::{
+    utils::{AsLasStr, FromLasStr},
+    Header, Point, Result, Vlr,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// The user id of the extra bytes vlr.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of the extra bytes vlr.
+pub const RECORD_ID: u16 = 4;
+
+const DESCRIPTOR_LEN: usize = 192;
+
+/// The type of an extra bytes field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DataType {
+    /// Bytes without a documented meaning, with the given length.
+    Undocumented(u8),
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+    /// One of the array types (11 through 30) deprecated in las 1.4 R13, kept as raw bytes.
+    Deprecated(u8),
+}
+
+/// A description of one extra bytes field.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Descriptor {
+    /// The field's name.
+    pub name: String,
+
+    /// The field's type.
+    pub data_type: DataType,
+
+    /// The raw value that means there's no data.
+    pub no_data: Option<f64>,
+
+    /// The smallest value in the data, after scaling.
+    pub min: Option<f64>,
+
+    /// The largest value in the data, after scaling.
+    pub max: Option<f64>,
+
+    /// The factor the raw value is multiplied by.
+    pub scale: Option<f64>,
+
+    /// The amount added to the raw value after scaling.
+    pub offset: Option<f64>,
+
+    /// A description of the field.
+    pub description: String,
+}
+
+impl DataType {
+    fn new(n: u8, options: u8) -> DataType {
+        match n {
+            0 => DataType::Undocumented(options),
+            1 => DataType::U8,
+            2 => DataType::I8,
+            3 => DataType::U16,
+            4 => DataType::I16,
+            5 => DataType::U32,
+            6 => DataType::I32,
+            7 => DataType::U64,
+            8 => DataType::I64,
+            9 => DataType::F32,
+            10 => DataType::F64,
+            _ => DataType::Deprecated(n),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            DataType::Undocumented(_) => 0,
+            DataType::U8 => 1,
+            DataType::I8 => 2,
+            DataType::U16 => 3,
+            DataType::I16 => 4,
+            DataType::U32 => 5,
+            DataType::I32 => 6,
+            DataType::U64 => 7,
+            DataType::I64 => 8,
+            DataType::F32 => 9,
+            DataType::F64 => 10,
+            DataType::Deprecated(n) => n,
+        }
+    }
+
+    /// Returns the number of bytes used by this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    ///
+    /// assert_eq!(2, DataType::I16.len());
+    /// assert_eq!(7, DataType::Undocumented(7).len());
+    /// assert_eq!(6, DataType::Deprecated(23).len()); // three u16s
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            DataType::Undocumented(n) => usize::from(*n),
+            DataType::U8 | DataType::I8 => 1,
+            DataType::U16 | DataType::I16 => 2,
+            DataType::U32 | DataType::I32 | DataType::F32 => 4,
+            DataType::U64 | DataType::I64 | DataType::F64 => 8,
+            DataType::Deprecated(n) => match n {
+                11..=20 => 2 * DataType::new(n - 10, 0).len(),
+                21..=30 => 3 * DataType::new(n - 20, 0).len(),
+                _ => 0,
+            },
+        }
+    }
+
+    /// No data, min, and max are stored as the widest type of the same kind.
+    fn wide(self) -> DataType {
+        match self {
+            DataType::U8 | DataType::U16 | DataType::U32 | DataType::U64 => DataType::U64,
+            DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64 => DataType::I64,
+            _ => DataType::F64,
+        }
+    }
+
+    fn read<R: Read>(&self, mut read: R) -> Result<Option<f64>> {
+        Ok(Some(match self {
+            DataType::U8 => read.read_u8()?.into(),
+            DataType::I8 => read.read_i8()?.into(),
+            DataType::U16 => read.read_u16::<LittleEndian>()?.into(),
+            DataType::I16 => read.read_i16::<LittleEndian>()?.into(),
+            DataType::U32 => read.read_u32::<LittleEndian>()?.into(),
+            DataType::I32 => read.read_i32::<LittleEndian>()?.into(),
+            DataType::U64 => read.read_u64::<LittleEndian>()? as f64,
+            DataType::I64 => read.read_i64::<LittleEndian>()? as f64,
+            DataType::F32 => read.read_f32::<LittleEndian>()?.into(),
+            DataType::F64 => read.read_f64::<LittleEndian>()?,
+            DataType::Undocumented(_) | DataType::Deprecated(_) => return Ok(None),
+        }))
+    }
+
+    fn write<W: Write>(&self, mut write: W, n: f64) -> Result<()> {
+        match self {
+            DataType::U8 => write.write_u8(n as u8)?,
+            DataType::I8 => write.write_i8(n as i8)?,
+            DataType::U16 => write.write_u16::<LittleEndian>(n as u16)?,
+            DataType::I16 => write.write_i16::<LittleEndian>(n as i16)?,
+            DataType::U32 => write.write_u32::<LittleEndian>(n as u32)?,
+            DataType::I32 => write.write_i32::<LittleEndian>(n as i32)?,
+            DataType::U64 => write.write_u64::<LittleEndian>(n as u64)?,
+            DataType::I64 => write.write_i64::<LittleEndian>(n as i64)?,
+            DataType::F32 => write.write_f32::<LittleEndian>(n as f32)?,
+            DataType::F64 => write.write_f64::<LittleEndian>(n)?,
+            DataType::Undocumented(_) | DataType::Deprecated(_) => {
+                write.write_all(&vec![0; self.len()])?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for DataType {
+    fn default() -> DataType {
+        DataType::Undocumented(0)
+    }
+}
+
+impl Descriptor {
+    /// Creates a new descriptor with no options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("height", DataType::F32);
+    /// assert_eq!(4, descriptor.len());
+    /// ```
+    pub fn new(name: &str, data_type: DataType) -> Descriptor {
+        Descriptor {
+            name: name.to_string(),
+            data_type,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the number of bytes this field uses in each point record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// assert_eq!(8, Descriptor::new("time", DataType::F64).len());
+    /// ```
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.data_type.len()
+    }
+
+    /// Reads a descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    ///
+    /// let mut bytes = Vec::new();
+    /// Descriptor::new("height", DataType::U8).write_to(&mut bytes).unwrap();
+    /// let descriptor = Descriptor::read_from(bytes.as_slice()).unwrap();
+    /// assert_eq!("height", descriptor.name);
+    /// ```
+    pub fn read_from<R: Read>(mut read: R) -> Result<Descriptor> {
+        let mut reserved = [0; 2];
+        read.read_exact(&mut reserved)?;
+        let data_type = read.read_u8()?;
+        let options = read.read_u8()?;
+        let data_type = DataType::new(data_type, options);
+        let mut name = [0; 32];
+        read.read_exact(&mut name)?;
+        let mut unused = [0; 4];
+        read.read_exact(&mut unused)?;
+        let mut values = [[0; 8]; 5];
+        for value in &mut values {
+            read.read_exact(value)?;
+            let mut rest = [0; 16];
+            read.read_exact(&mut rest)?;
+        }
+        let mut description = [0; 32];
+        read.read_exact(&mut description)?;
+
+        // The options of undocumented extra bytes are their length.
+        let option = |bit: u8, bytes: [u8; 8], wide: DataType| -> Result<Option<f64>> {
+            if matches!(data_type, DataType::Undocumented(_)) || options & (1 << bit) == 0 {
+                Ok(None)
+            } else {
+                wide.read(bytes.as_slice())
+            }
+        };
+        let [no_data, min, max, scale, offset] = values;
+        let wide = data_type.wide();
+        Ok(Descriptor {
+            name: name.as_ref().as_las_string_lossy(),
+            data_type,
+            no_data: option(0, no_data, wide)?,
+            min: option(1, min, wide)?,
+            max: option(2, max, wide)?,
+            scale: option(3, scale, DataType::F64)?,
+            offset: option(4, offset, DataType::F64)?,
+            description: description.as_ref().as_las_string_lossy(),
+        })
+    }
+
+    /// Writes a descriptor.
+    ///
+    /// Returns an error if the name or description are longer than 32 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    ///
+    /// let mut bytes = Vec::new();
+    /// Descriptor::new("height", DataType::U8).write_to(&mut bytes).unwrap();
+    /// assert_eq!(192, bytes.len());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut write: W) -> Result<()> {
+        write.write_all(&[0; 2])?;
+        write.write_u8(self.data_type.to_u8())?;
+        let mut options = match self.data_type {
+            DataType::Undocumented(n) => n,
+            _ => 0,
+        };
+        let wide = self.data_type.wide();
+        let values = [
+            (self.no_data, wide),
+            (self.min, wide),
+            (self.max, wide),
+            (self.scale, DataType::F64),
+            (self.offset, DataType::F64),
+        ];
+        for (bit, (value, _)) in values.iter().enumerate() {
+            if value.is_some() {
+                options |= 1 << bit;
+            }
+        }
+        write.write_u8(options)?;
+        let mut name = [0; 32];
+        name.as_mut().from_las_str(&self.name)?;
+        write.write_all(&name)?;
+        write.write_all(&[0; 4])?;
+        for (value, data_type) in values {
+            data_type.write(&mut write, value.unwrap_or(0.))?;
+            write.write_all(&[0; 16])?;
+        }
+        let mut description = [0; 32];
+        description.as_mut().from_las_str(&self.description)?;
+        write.write_all(&description)?;
+        Ok(())
+    }
+
+    /// Decodes this field's value from the start of the bytes, applying the scale and offset.
+    ///
+    /// Returns `None` if the type has no numeric meaning, the value is no data, or there aren't
+    /// enough bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    ///
+    /// let mut descriptor = Descriptor::new("height", DataType::I16);
+    /// descriptor.scale = Some(0.1);
+    /// assert_eq!(Some(-0.1), descriptor.decode(&[255, 255]));
+    /// descriptor.no_data = Some(-1.);
+    /// assert_eq!(None, descriptor.decode(&[255, 255]));
+    /// ```
+    pub fn decode(&self, bytes: &[u8]) -> Option<f64> {
+        let n = self.data_type.read(bytes.get(..self.len())?).ok()??;
+        if self.no_data == Some(n) {
+            None
+        } else {
+            Some(n * self.scale.unwrap_or(1.) + self.offset.unwrap_or(0.))
+        }
+    }
+
+    /// Encodes a value into the start of the bytes, removing the scale and offset.
+    ///
+    /// Integer types are rounded. Types without a numeric meaning are zero-filled. Returns an
+    /// error if there aren't enough bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    ///
+    /// let mut descriptor = Descriptor::new("height", DataType::I16);
+    /// descriptor.scale = Some(0.1);
+    /// let mut bytes = [0; 2];
+    /// descriptor.encode(-0.1, &mut bytes).unwrap();
+    /// assert_eq!([255, 255], bytes);
+    /// assert!(descriptor.encode(1., &mut [0]).is_err());
+    /// ```
+    pub fn encode(&self, value: f64, bytes: &mut [u8]) -> Result<()> {
+        let n = (value - self.offset.unwrap_or(0.)) / self.scale.unwrap_or(1.);
+        let n = match self.data_type {
+            DataType::F32 | DataType::F64 => n,
+            _ => n.round(),
+        };
+        self.data_type.write(bytes, n)
+    }
+}
+
+impl Header {
+    /// Returns the extra bytes descriptors from this header's vlrs or evlrs.
+    ///
+    /// Returns an empty vector if there isn't an extra bytes vlr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().extra_bytes().unwrap().is_empty());
+    /// ```
+    pub fn extra_bytes(&self) -> Result<Vec<Descriptor>> {
+        match self.find_vlr(USER_ID, RECORD_ID) {
+            Some(vlr) => descriptors(vlr),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Reads the descriptors from an extra bytes vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::extra_bytes::{self, DataType, Descriptor};
+///
+/// let vlr = extra_bytes::vlr(&[Descriptor::new("height", DataType::U8)]).unwrap();
+/// assert_eq!(1, extra_bytes::descriptors(&vlr).unwrap().len());
+/// ```
+pub fn descriptors(vlr: &Vlr) -> Result<Vec<Descriptor>> {
+    vlr.data
+        .chunks_exact(DESCRIPTOR_LEN)
+        .map(Descriptor::read_from)
+        .collect()
+}
+
+/// Creates an extra bytes vlr from descriptors.
+///
+/// # Examples
+///
+/// ```
+/// use las::extra_bytes::{self, DataType, Descriptor};
+///
+/// let vlr = extra_bytes::vlr(&[Descriptor::new("height", DataType::U8)]).unwrap();
+/// assert_eq!("LASF_Spec", vlr.user_id);
+/// assert_eq!(4, vlr.record_id);
+/// ```
+pub fn vlr(descriptors: &[Descriptor]) -> Result<Vlr> {
+    let mut data = Vec::with_capacity(descriptors.len() * DESCRIPTOR_LEN);
+    for descriptor in descriptors {
+        descriptor.write_to(&mut data)?;
+    }
+    Ok(Vlr {
+        user_id: USER_ID.to_string(),
+        record_id: RECORD_ID,
+        description: "Extra bytes".to_string(),
+        data,
+    })
+}
+
+/// Returns the value of the named field for a point.
+///
+/// Returns `None` if there's no field with this name, or if it has no value for this point.
+///
+/// # Examples
+///
+/// ```
+/// use las::{extra_bytes::{self, DataType, Descriptor}, Point};
+///
+/// let descriptors = [Descriptor::new("a", DataType::U8), Descriptor::new("b", DataType::U8)];
+/// let point = Point { extra_bytes: vec![1, 2], ..Default::default() };
+/// assert_eq!(Some(2.), extra_bytes::value(&descriptors, "b", &point));
+/// assert_eq!(None, extra_bytes::value(&descriptors, "c", &point));
+/// ```
+pub fn value(descriptors: &[Descriptor], name: &str, point: &Point) -> Option<f64> {
+    let (offset, descriptor) = find(descriptors, name)?;
+    descriptor.decode(point.extra_bytes.get(offset..)?)
+}
+
+/// Returns the byte offset into the extra bytes and the descriptor of the named field.
+///
+/// # Examples
+///
+/// ```
+/// use las::extra_bytes::{self, DataType, Descriptor};
+///
+/// let descriptors = [Descriptor::new("a", DataType::U16), Descriptor::new("b", DataType::U8)];
+/// assert_eq!(2, extra_bytes::find(&descriptors, "b").unwrap().0);
+/// ```
+pub fn find<'a>(descriptors: &'a [Descriptor], name: &str) -> Option<(usize, &'a Descriptor)> {
+    let mut offset = 0;
+    for descriptor in descriptors {
+        if descriptor.name == name {
+            return Some((offset, descriptor));
+        }
+        offset += descriptor.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let descriptor = Descriptor {
+            name: "height".to_string(),
+            data_type: DataType::I32,
+            no_data: Some(-9999.),
+            min: Some(-10.),
+            max: Some(100.),
+            scale: Some(0.01),
+            offset: Some(1.),
+            description: "height above ground".to_string(),
+        };
+        let mut bytes = Vec::new();
+        descriptor.write_to(&mut bytes).unwrap();
+        assert_eq!(DESCRIPTOR_LEN, bytes.len());
+        assert_eq!(descriptor, Descriptor::read_from(bytes.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn undocumented() {
+        let descriptor = Descriptor::new("", DataType::Undocumented(7));
+        let mut bytes = Vec::new();
+        descriptor.write_to(&mut bytes).unwrap();
+        assert_eq!(7, bytes[3]);
+        let descriptor = Descriptor::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(DataType::Undocumented(7), descriptor.data_type);
+        assert_eq!(None, descriptor.decode(&[0; 7]));
+    }
+
+    #[test]
+    fn name_too_long() {
+        let descriptor = Descriptor::new(&"a".repeat(33), DataType::U8);
+        assert!(descriptor.write_to(Vec::new()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "laz")]
+    fn extrabytes_laz() {
+        let mut reader = crate::Reader::from_path("tests/data/extrabytes.laz").unwrap();
+        let descriptors = reader.header().extra_bytes().unwrap();
+        let names: Vec<&str> = descriptors.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            vec!["Colors", "Reserved", "Flags", "Intensity", "Time"],
+            names
+        );
+        let len: usize = descriptors.iter().map(|d| d.len()).sum();
+        assert_eq!(usize::from(reader.header().point_format().extra_bytes), len);
+        let point = reader.read_point().unwrap().unwrap();
+        assert!(value(&descriptors, "Time", &point).is_some());
+        assert!(value(&descriptors, "Reserved", &point).is_none());
+    }
+}
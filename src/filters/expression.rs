@@ -0,0 +1,587 @@
+//! A small language for choosing points.
+//!
+//! Expressions compare point [dimensions](crate::point::Dimension), numbers, and names:
+//!
+//! ```
+//! use las::{filters::expression::Expression, point::Classification, Point};
+//!
+//! let expression: Expression = "Classification == Ground && ReturnNumber == 1 && Z > 100"
+//!     .parse()
+//!     .unwrap();
+//! let mut point = Point { return_number: 1, z: 150., ..Default::default() };
+//! assert!(!expression.matches(&point));
+//! point.classification = Classification::Ground;
+//! assert!(expression.matches(&point));
+//! ```
+//!
+//! The operators, from lowest to highest precedence, are `||`, `&&`, the comparisons (`==`, `!=`,
+//! `<`, `<=`, `>`, `>=`), `+` and `-`, `*` and `/`, and the unary `!` and `-`. Parentheses group.
+//!
+//! Names are resolved in this order:
+//!
+//! 1. Dimension names, ignoring case, e.g. `GpsTime` or `withheld`. Flags are one or zero.
+//! 2. Extra bytes field names, if the expression was created [with a
+//!    header](Expression::with_header). Names that aren't identifiers can be double quoted, e.g.
+//!    `"height above ground" > 2`, and quoted names are looked up in the extra bytes first.
+//! 3. Classification names, ignoring case and anything that isn't a letter or digit, e.g.
+//!    `LowVegetation` or `"Low Vegetation"`. Expressions created with a header also know the
+//!    user-defined names from its [classification scheme](crate::classification).
+//!
+//! A dimension the point doesn't have, like `GpsTime` for point format 0, has no value, and any
+//! comparison with no value is false.
+//!
+//! Use [Expression::predicate] to filter [Reader::points](crate::Reader::points):
+//!
+//! ```
+//! use las::{filters::expression::Expression, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let expression = Expression::with_header("Z > 450", reader.header()).unwrap();
+//! let points = reader.points().filter(expression.predicate()).count();
+//! assert!(points < 106);
+//! ```
+
+use crate::{
+    classification::ClassificationScheme,
+    extra_bytes::{self, Descriptor},
+    point::Dimension,
+    Error, Header, Point, Result,
+};
+use std::str::FromStr;
+
+/// A compiled filter expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    node: Node,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Dimension(Dimension),
+    ExtraBytes(usize, Descriptor),
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Quoted(String),
+    Operator(Operator),
+    Not,
+    Open,
+    Close,
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    descriptors: &'a [Descriptor],
+    scheme: &'a ClassificationScheme,
+}
+
+impl Expression {
+    /// Parses an expression that may also use the names of the header's extra bytes fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::expression::Expression, Header};
+    /// let expression = Expression::with_header("Intensity > 0", &Header::default()).unwrap();
+    /// ```
+    pub fn with_header(s: &str, header: &Header) -> Result<Expression> {
+        parse(s, &header.extra_bytes()?, &header.classification_scheme()?)
+    }
+
+    /// Evaluates this expression for a point.
+    ///
+    /// Comparisons and logical operators are one for true and zero for false. Returns `None` if
+    /// the point is missing a dimension that the result depends on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::expression::Expression, Point};
+    ///
+    /// let expression: Expression = "Intensity * 2".parse().unwrap();
+    /// let point = Point { intensity: 21, ..Default::default() };
+    /// assert_eq!(Some(42.), expression.evaluate(&point));
+    /// let expression: Expression = "GpsTime".parse().unwrap();
+    /// assert_eq!(None, expression.evaluate(&point));
+    /// ```
+    pub fn evaluate(&self, point: &Point) -> Option<f64> {
+        self.node.evaluate(point)
+    }
+
+    /// Returns true if this expression evaluates to a non-zero value for this point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::expression::Expression, Point};
+    ///
+    /// let expression: Expression = "!Withheld".parse().unwrap();
+    /// assert!(expression.matches(&Point::default()));
+    /// ```
+    pub fn matches(&self, point: &Point) -> bool {
+        truthy(self.evaluate(point))
+    }
+
+    /// Returns a predicate for [Iterator::filter] that keeps errors and matching points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::expression::Expression, Point};
+    ///
+    /// let expression: Expression = "Z > 1".parse().unwrap();
+    /// let points = vec![Ok(Point { z: 2., ..Default::default() }), Ok(Point::default())];
+    /// assert_eq!(1, points.into_iter().filter(expression.predicate()).count());
+    /// ```
+    pub fn predicate(&self) -> impl FnMut(&Result<Point>) -> bool + '_ {
+        move |point| point.as_ref().map(|p| self.matches(p)).unwrap_or(true)
+    }
+}
+
+impl FromStr for Expression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Expression> {
+        parse(s, &[], &ClassificationScheme::default())
+    }
+}
+
+impl Node {
+    fn evaluate(&self, point: &Point) -> Option<f64> {
+        match self {
+            Node::Number(n) => Some(*n),
+            Node::Dimension(dimension) => dimension.value(point),
+            Node::ExtraBytes(offset, descriptor) => {
+                descriptor.decode(point.extra_bytes.get(*offset..)?)
+            }
+            Node::Not(node) => node.evaluate(point).map(|n| boolean(n == 0.)),
+            Node::Negate(node) => node.evaluate(point).map(|n| -n),
+            Node::Binary(Operator::Or, a, b) => Some(boolean(
+                truthy(a.evaluate(point)) || truthy(b.evaluate(point)),
+            )),
+            Node::Binary(Operator::And, a, b) => Some(boolean(
+                truthy(a.evaluate(point)) && truthy(b.evaluate(point)),
+            )),
+            Node::Binary(operator, a, b) => {
+                let (a, b) = (a.evaluate(point), b.evaluate(point));
+                let (a, b) = match (a, b) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => return operator.is_comparison().then_some(0.),
+                };
+                Some(match operator {
+                    Operator::Equal => boolean(a == b),
+                    Operator::NotEqual => boolean(a != b),
+                    Operator::Less => boolean(a < b),
+                    Operator::LessEqual => boolean(a <= b),
+                    Operator::Greater => boolean(a > b),
+                    Operator::GreaterEqual => boolean(a >= b),
+                    Operator::Add => a + b,
+                    Operator::Subtract => a - b,
+                    Operator::Multiply => a * b,
+                    Operator::Divide => a / b,
+                    Operator::Or | Operator::And => unreachable!(),
+                })
+            }
+        }
+    }
+}
+
+impl Operator {
+    fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::Equal
+                | Operator::NotEqual
+                | Operator::Less
+                | Operator::LessEqual
+                | Operator::Greater
+                | Operator::GreaterEqual
+        )
+    }
+}
+
+impl Parser<'_> {
+    fn or(&mut self) -> Result<Node> {
+        let mut node = self.and()?;
+        while self.eat(&Token::Operator(Operator::Or)) {
+            node = Node::Binary(Operator::Or, Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        let mut node = self.comparison()?;
+        while self.eat(&Token::Operator(Operator::And)) {
+            node = Node::Binary(Operator::And, Box::new(node), Box::new(self.comparison()?));
+        }
+        Ok(node)
+    }
+
+    fn comparison(&mut self) -> Result<Node> {
+        let node = self.sum()?;
+        match self.peek() {
+            Some(Token::Operator(operator)) if operator.is_comparison() => {
+                let operator = *operator;
+                self.position += 1;
+                Ok(Node::Binary(
+                    operator,
+                    Box::new(node),
+                    Box::new(self.sum()?),
+                ))
+            }
+            _ => Ok(node),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node> {
+        let mut node = self.product()?;
+        while let Some(Token::Operator(operator @ (Operator::Add | Operator::Subtract))) =
+            self.peek()
+        {
+            let operator = *operator;
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+        Ok(node)
+    }
+
+    fn product(&mut self) -> Result<Node> {
+        let mut node = self.unary()?;
+        while let Some(Token::Operator(operator @ (Operator::Multiply | Operator::Divide))) =
+            self.peek()
+        {
+            let operator = *operator;
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.eat(&Token::Not) {
+            Ok(Node::Not(Box::new(self.unary()?)))
+        } else if self.eat(&Token::Operator(Operator::Subtract)) {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node> {
+        let (position, token) = match self.tokens.get(self.position) {
+            Some((position, token)) => (*position, token.clone()),
+            None => return Err(self.error(self.end, "unexpected end of expression")),
+        };
+        self.position += 1;
+        match token {
+            Token::Number(n) => Ok(Node::Number(n)),
+            Token::Name(name) => self.resolve(&name, false, position),
+            Token::Quoted(name) => self.resolve(&name, true, position),
+            Token::Open => {
+                let node = self.or()?;
+                if self.eat(&Token::Close) {
+                    Ok(node)
+                } else {
+                    Err(self.error(self.current_position(), "expected `)`"))
+                }
+            }
+            _ => Err(self.error(position, "expected a number, a name, or `(`")),
+        }
+    }
+
+    fn resolve(&self, name: &str, quoted: bool, position: usize) -> Result<Node> {
+        let extra_bytes = || {
+            extra_bytes::find(self.descriptors, name)
+                .map(|(offset, descriptor)| Node::ExtraBytes(offset, descriptor.clone()))
+        };
+        if quoted {
+            if let Some(node) = extra_bytes() {
+                return Ok(node);
+            }
+        }
+        if let Ok(dimension) = name.parse::<Dimension>() {
+            return Ok(Node::Dimension(dimension));
+        }
+        if let Some(node) = extra_bytes() {
+            return Ok(node);
+        }
+        if let Some(code) = self.scheme.code(name) {
+            return Ok(Node::Number(code.into()));
+        }
+        Err(self.error(position, &format!("unknown name `{}`", name)))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn current_position(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn error(&self, position: usize, message: &str) -> Error {
+        Error::InvalidExpression {
+            position,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn parse(s: &str, descriptors: &[Descriptor], scheme: &ClassificationScheme) -> Result<Expression> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        position: 0,
+        end: s.len(),
+        descriptors,
+        scheme,
+    };
+    let node = parser.or()?;
+    if parser.position < parser.tokens.len() {
+        return Err(parser.error(parser.current_position(), "unexpected token"));
+    }
+    Ok(Expression { node })
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>> {
+    let error = |position: usize, message: &str| Error::InvalidExpression {
+        position,
+        message: message.to_string(),
+    };
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Operator(Operator::Subtract),
+            '*' => Token::Operator(Operator::Multiply),
+            '/' => Token::Operator(Operator::Divide),
+            '|' if next_is('|') => Token::Operator(Operator::Or),
+            '&' if next_is('&') => Token::Operator(Operator::And),
+            '=' if next_is('=') => Token::Operator(Operator::Equal),
+            '!' if next_is('=') => Token::Operator(Operator::NotEqual),
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Operator(Operator::LessEqual),
+            '<' => Token::Operator(Operator::Less),
+            '>' if next_is('=') => Token::Operator(Operator::GreaterEqual),
+            '>' => Token::Operator(Operator::Greater),
+            '"' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(error(position, "unterminated quoted name")),
+                    }
+                }
+                Token::Quoted(name)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = position + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                {
+                    end = i + c.len_utf8();
+                }
+                // Exponents, e.g. 1e-3
+                if s[position..end].ends_with(['e', 'E']) {
+                    if let Some((i, c)) = chars.next_if(|&(_, c)| c == '-' || c == '+') {
+                        end = i + c.len_utf8();
+                        while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_ascii_digit()) {
+                            end = i + c.len_utf8();
+                        }
+                    }
+                }
+                Token::Number(
+                    s[position..end]
+                        .parse()
+                        .map_err(|_| error(position, "invalid number"))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = position + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                    end = i + c.len_utf8();
+                }
+                Token::Name(s[position..end].to_string())
+            }
+            _ => return Err(error(position, &format!("unexpected character `{}`", c))),
+        };
+        tokens.push((position, token));
+    }
+    Ok(tokens)
+}
+
+fn truthy(n: Option<f64>) -> bool {
+    matches!(n, Some(n) if n != 0.)
+}
+
+fn boolean(b: bool) -> f64 {
+    if b {
+        1.
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extra_bytes::DataType,
+        point::{Classification, Format},
+        Builder,
+    };
+
+    fn evaluate(s: &str, point: &Point) -> Option<f64> {
+        s.parse::<Expression>().unwrap().evaluate(point)
+    }
+
+    #[test]
+    fn precedence() {
+        let point = Point::default();
+        assert_eq!(Some(7.), evaluate("1 + 2 * 3", &point));
+        assert_eq!(Some(9.), evaluate("(1 + 2) * 3", &point));
+        assert_eq!(Some(-1.), evaluate("-1", &point));
+        assert_eq!(Some(1.), evaluate("1 < 2 && 2 < 3 || 0", &point));
+        assert_eq!(Some(1.), evaluate("0 && 1 || 1", &point));
+        assert_eq!(Some(0.), evaluate("!(1 + 1 == 2)", &point));
+        assert_eq!(Some(1.), evaluate("1e-3 < 0.01", &point));
+    }
+
+    #[test]
+    fn names() {
+        let point = Point {
+            is_key_point: true,
+            classification: Classification::HighVegetation,
+            gps_time: Some(10.),
+            ..Default::default()
+        };
+        assert_eq!(Some(1.), evaluate("KeyPoint", &point));
+        assert_eq!(
+            Some(1.),
+            evaluate("classification == highvegetation", &point)
+        );
+        assert_eq!(Some(1.), evaluate("GpsTime >= 10", &point));
+        assert_eq!(Some(0.), evaluate("GpsTime >= 10", &Point::default()));
+        assert_eq!(Some(1.), evaluate("!(GpsTime >= 10)", &Point::default()));
+        assert_eq!(None, evaluate("GpsTime + 1", &Point::default()));
+    }
+
+    #[test]
+    fn errors() {
+        for (s, position) in [
+            ("", 0),
+            ("Z >", 3),
+            ("(Z > 1", 6),
+            ("Z > 1)", 5),
+            ("Foo == 1", 0),
+            ("Z = 1", 2),
+            ("Z > 1 $", 6),
+            ("\"Z", 0),
+        ] {
+            match s.parse::<Expression>().unwrap_err() {
+                Error::InvalidExpression { position: p, .. } => assert_eq!(position, p, "{}", s),
+                err => panic!("unexpected error for {}: {}", s, err),
+            }
+        }
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format {
+            extra_bytes: 3,
+            ..Default::default()
+        };
+        let mut height = Descriptor::new("height above ground", DataType::U16);
+        height.scale = Some(0.5);
+        builder
+            .vlrs
+            .push(extra_bytes::vlr(&[Descriptor::new("Intensity", DataType::U8), height]).unwrap());
+        let header = builder.into_header().unwrap();
+        let point = Point {
+            intensity: 1,
+            extra_bytes: vec![2, 6, 0],
+            ..Default::default()
+        };
+        let evaluate = |s: &str| {
+            Expression::with_header(s, &header)
+                .unwrap()
+                .evaluate(&point)
+        };
+        assert_eq!(Some(1.), evaluate("Intensity"));
+        assert_eq!(Some(2.), evaluate("\"Intensity\""));
+        assert_eq!(Some(3.), evaluate("\"height above ground\""));
+        assert!("\"height above ground\" > 1".parse::<Expression>().is_err());
+    }
+
+    #[test]
+    fn classification_scheme() {
+        let mut scheme = ClassificationScheme::default();
+        scheme.set_name(64, "Power Line").unwrap();
+        let mut builder = Builder::default();
+        builder.vlrs.push(scheme.to_vlr().unwrap());
+        let header = builder.into_header().unwrap();
+        let point = Point {
+            classification: Classification::new(64).unwrap(),
+            ..Default::default()
+        };
+        let expression = Expression::with_header("Classification == PowerLine", &header).unwrap();
+        assert!(expression.matches(&point));
+        let expression =
+            Expression::with_header("Classification != \"Power Line\"", &header).unwrap();
+        assert!(!expression.matches(&point));
+        assert!("Classification == PowerLine".parse::<Expression>().is_err());
+        assert_eq!(
+            Some(1.),
+            evaluate(
+                "classification == \"Low Vegetation\"",
+                &Point {
+                    classification: Classification::LowVegetation,
+                    ..Default::default()
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Filter points.
+//!
+//! The [Decimate] trait adds thinning adapters to any iterator of point results, such as
+//! [Reader::points]:
+//!
+//! ```
+//! use las::{filters::Decimate, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let points = reader.points().every_nth(10).collect::<las::Result<Vec<_>>>().unwrap();
+//! assert_eq!(11, points.len());
+//! ```
+//!
+//! Use [thin] to decimate a whole file into a [Writer], and [expression] to choose points with
+//! filter expressions like `Classification == Ground && Z > 100`.
+
+pub mod expression;
+
+use crate::{Error, Point, Reader, Result, Writer};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    io::{Seek, Write},
+};
+
+/// Iterator adapters that thin out points.
+///
+/// Errors are passed through and don't count as points.
+pub trait Decimate: Iterator<Item = Result<Point>> + Sized {
+    /// Keeps the first point and every nth point after that.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::Decimate, Point};
+    ///
+    /// let points = (0..5).map(|_| Ok::<_, las::Error>(Point::default()));
+    /// assert_eq!(3, points.every_nth(2).count());
+    /// ```
+    fn every_nth(self, n: usize) -> EveryNth<Self> {
+        assert!(n > 0, "n must be greater than zero");
+        EveryNth {
+            inner: self,
+            n,
+            count: 0,
+        }
+    }
+
+    /// Keeps each point with probability `fraction`.
+    ///
+    /// The same seed always keeps the same points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not between zero and one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::Decimate, Point};
+    ///
+    /// let points = || (0..1000).map(|_| Ok::<_, las::Error>(Point::default()));
+    /// let n = points().sample(0.1, 42).count();
+    /// assert!(n > 50 && n < 150);
+    /// assert_eq!(n, points().sample(0.1, 42).count());
+    /// ```
+    fn sample(self, fraction: f64, seed: u64) -> Sample<Self> {
+        assert!(
+            (0. ..=1.).contains(&fraction),
+            "fraction must be between zero and one"
+        );
+        Sample {
+            inner: self,
+            fraction,
+            rng: SplitMix64(seed),
+        }
+    }
+
+    /// Keeps one point per cubic cell.
+    ///
+    /// With [Prefer::First], points are streamed and only the occupied voxels are remembered.
+    /// Otherwise, all points are read on the first call to `next`, and one point per voxel is held
+    /// in memory. The kept points are returned in their original order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the voxel size is not finite and positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{filters::{Decimate, Prefer, VoxelOptions}, Point};
+    ///
+    /// let points = vec![
+    ///     Ok(Point { z: 2., ..Default::default() }),
+    ///     Ok(Point { z: 1., ..Default::default() }),
+    ///     Ok(Point { z: 12., ..Default::default() }),
+    /// ];
+    /// let options = VoxelOptions { size: 10., prefer: Prefer::Lowest };
+    /// let points = points.into_iter().voxel(options).collect::<las::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(vec![1., 12.], points.iter().map(|p| p.z).collect::<Vec<_>>());
+    /// ```
+    fn voxel(self, options: VoxelOptions) -> Voxel<Self> {
+        assert!(
+            options.size.is_finite() && options.size > 0.,
+            "voxel size must be finite and positive"
+        );
+        Voxel {
+            inner: Some(self),
+            options,
+            voxels: HashSet::new(),
+            points: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Decimate for I {}
+
+/// An iterator that keeps every nth point.
+///
+/// Created by [Decimate::every_nth].
+#[derive(Debug)]
+pub struct EveryNth<I> {
+    inner: I,
+    n: usize,
+    count: usize,
+}
+
+/// An iterator that keeps a random sample of points.
+///
+/// Created by [Decimate::sample].
+#[derive(Debug)]
+pub struct Sample<I> {
+    inner: I,
+    fraction: f64,
+    rng: SplitMix64,
+}
+
+/// An iterator that keeps one point per voxel.
+///
+/// Created by [Decimate::voxel].
+#[derive(Debug)]
+pub struct Voxel<I> {
+    inner: Option<I>,
+    options: VoxelOptions,
+    voxels: HashSet<[i64; 3]>,
+    points: std::vec::IntoIter<Point>,
+}
+
+/// Voxel thinning options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoxelOptions {
+    /// The edge length of each voxel.
+    pub size: f64,
+
+    /// Which point to keep in each voxel.
+    pub prefer: Prefer,
+}
+
+/// Which point to keep when several share a voxel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Prefer {
+    /// The first point.
+    #[default]
+    First,
+
+    /// The point with the smallest z.
+    Lowest,
+
+    /// The point with the largest z.
+    Highest,
+
+    /// The first point flagged as a key point, or the first point if there are none.
+    KeyPoint,
+}
+
+/// A way to decimate a whole file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decimation {
+    /// See [Decimate::every_nth].
+    EveryNth(usize),
+
+    /// See [Decimate::sample].
+    Sample {
+        /// The fraction of points to keep.
+        fraction: f64,
+
+        /// The random seed.
+        seed: u64,
+    },
+
+    /// See [Decimate::voxel].
+    Voxel(VoxelOptions),
+}
+
+/// A small, fast, seedable pseudo-random number generator.
+#[derive(Clone, Copy, Debug)]
+struct SplitMix64(u64);
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for EveryNth<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            match self.inner.next()? {
+                Ok(point) => {
+                    let keep = self.count.is_multiple_of(self.n);
+                    self.count += 1;
+                    if keep {
+                        return Some(Ok(point));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for Sample<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            match self.inner.next()? {
+                Ok(point) => {
+                    if self.rng.next_f64() < self.fraction {
+                        return Some(Ok(point));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for Voxel<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        if self.options.prefer == Prefer::First {
+            loop {
+                match self.inner.as_mut()?.next()? {
+                    Ok(point) => {
+                        if self.voxels.insert(self.options.voxel(&point)) {
+                            return Some(Ok(point));
+                        }
+                    }
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        }
+        if let Some(inner) = self.inner.take() {
+            match self.options.thin(inner) {
+                Ok(points) => self.points = points.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        self.points.next().map(Ok)
+    }
+}
+
+impl VoxelOptions {
+    fn voxel(&self, point: &Point) -> [i64; 3] {
+        [
+            (point.x / self.size).floor() as i64,
+            (point.y / self.size).floor() as i64,
+            (point.z / self.size).floor() as i64,
+        ]
+    }
+
+    fn thin<I: Iterator<Item = Result<Point>>>(&self, points: I) -> Result<Vec<Point>> {
+        let mut cells: HashMap<[i64; 3], (usize, Point)> = HashMap::new();
+        for (i, point) in points.enumerate() {
+            let point = point?;
+            match cells.entry(self.voxel(&point)) {
+                Entry::Occupied(mut entry) => {
+                    if self.prefer.replaces(&entry.get().1, &point) {
+                        let _ = entry.insert((i, point));
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    let _ = entry.insert((i, point));
+                }
+            }
+        }
+        let mut points: Vec<(usize, Point)> = cells.into_values().collect();
+        points.sort_by_key(|(i, _)| *i);
+        Ok(points.into_iter().map(|(_, point)| point).collect())
+    }
+}
+
+impl Prefer {
+    fn replaces(&self, kept: &Point, point: &Point) -> bool {
+        match self {
+            Prefer::First => false,
+            Prefer::Lowest => point.z < kept.z,
+            Prefer::Highest => point.z > kept.z,
+            Prefer::KeyPoint => point.is_key_point && !kept.is_key_point,
+        }
+    }
+}
+
+impl Decimation {
+    fn verify(&self) -> Result<()> {
+        let valid = match self {
+            Decimation::EveryNth(n) => *n > 0,
+            Decimation::Sample { fraction, .. } => (0. ..=1.).contains(fraction),
+            Decimation::Voxel(options) => options.size.is_finite() && options.size > 0.,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidDecimation(*self))
+        }
+    }
+}
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Writes a decimated copy of the reader's remaining points to the writer.
+///
+/// Returns the number of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{filters::{self, Decimation}, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let header = reader.header().clone();
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+/// let n = filters::thin(&mut reader, &mut writer, &Decimation::EveryNth(2)).unwrap();
+/// assert_eq!(53, n);
+/// ```
+pub fn thin<W: 'static + Write + Seek + Send>(
+    reader: &mut Reader,
+    writer: &mut Writer<W>,
+    decimation: &Decimation,
+) -> Result<u64> {
+    decimation.verify()?;
+    let points = reader.points();
+    let points: Box<dyn Iterator<Item = Result<Point>>> = match *decimation {
+        Decimation::EveryNth(n) => Box::new(points.every_nth(n)),
+        Decimation::Sample { fraction, seed } => Box::new(points.sample(fraction, seed)),
+        Decimation::Voxel(options) => Box::new(points.voxel(options)),
+    };
+    let mut count = 0;
+    for point in points {
+        writer.write_point(point?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(zs: &[f64]) -> Vec<Result<Point>> {
+        zs.iter()
+            .map(|&z| {
+                Ok(Point {
+                    z,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_nth_passes_errors() {
+        let mut points = points(&[1., 2., 3.]);
+        points.insert(1, Err(Error::ClosedWriter));
+        let points: Vec<_> = points.into_iter().every_nth(2).collect();
+        assert_eq!(3, points.len());
+        assert!(points[1].is_err());
+        assert_eq!(3., points[2].as_ref().unwrap().z);
+    }
+
+    #[test]
+    #[should_panic]
+    fn every_nth_zero() {
+        let _ = points(&[1.]).into_iter().every_nth(0);
+    }
+
+    #[test]
+    fn sample_seed() {
+        let zs: Vec<f64> = (0..100).map(f64::from).collect();
+        let a: Vec<_> = points(&zs)
+            .into_iter()
+            .sample(0.5, 1)
+            .map(|p| p.unwrap().z)
+            .collect();
+        let b: Vec<_> = points(&zs)
+            .into_iter()
+            .sample(0.5, 2)
+            .map(|p| p.unwrap().z)
+            .collect();
+        assert_ne!(a, b);
+        assert_eq!(0, points(&zs).into_iter().sample(0., 1).count());
+        assert_eq!(100, points(&zs).into_iter().sample(1., 1).count());
+    }
+
+    #[test]
+    fn voxel_prefer() {
+        let zs = [2., 1., 3.];
+        let thin = |prefer| {
+            let options = VoxelOptions { size: 10., prefer };
+            points(&zs)
+                .into_iter()
+                .voxel(options)
+                .map(|p| p.unwrap().z)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![2.], thin(Prefer::First));
+        assert_eq!(vec![1.], thin(Prefer::Lowest));
+        assert_eq!(vec![3.], thin(Prefer::Highest));
+        assert_eq!(vec![2.], thin(Prefer::KeyPoint));
+    }
+
+    #[test]
+    fn voxel_key_point() {
+        let mut points = points(&[1., 2., 3.]);
+        for point in points.iter_mut().skip(1) {
+            point.as_mut().unwrap().is_key_point = true;
+        }
+        let options = VoxelOptions {
+            size: 10.,
+            prefer: Prefer::KeyPoint,
+        };
+        let points: Vec<_> = points.into_iter().voxel(options).collect();
+        assert_eq!(2., points[0].as_ref().unwrap().z);
+    }
+
+    #[test]
+    fn voxel_error() {
+        let mut points = points(&[1.]);
+        points.push(Err(Error::ClosedWriter));
+        let options = VoxelOptions {
+            size: 1.,
+            prefer: Prefer::Lowest,
+        };
+        let mut points = points.into_iter().voxel(options);
+        assert!(points.next().unwrap().is_err());
+        assert!(points.next().is_none());
+    }
+
+    #[test]
+    fn voxel_first_streams() {
+        let mut points = points(&[1., 1.5, 12.]);
+        points.insert(1, Err(Error::ClosedWriter));
+        let options = VoxelOptions {
+            size: 10.,
+            prefer: Prefer::First,
+        };
+        let mut points = points.into_iter().voxel(options);
+        assert_eq!(1., points.next().unwrap().unwrap().z);
+        assert!(points.next().unwrap().is_err());
+        assert_eq!(12., points.next().unwrap().unwrap().z);
+        assert!(points.next().is_none());
+    }
+
+    #[test]
+    fn thin_invalid() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer =
+            Writer::new(std::io::Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        assert!(thin(&mut reader, &mut writer, &Decimation::EveryNth(0)).is_err());
+        let voxel = Decimation::Voxel(VoxelOptions {
+            size: 100.,
+            prefer: Prefer::Lowest,
+        });
+        let n = thin(&mut reader, &mut writer, &voxel).unwrap();
+        assert!(n > 0 && n < 106);
+    }
+}
@@ -1,13 +1,39 @@
 use crate::{
-    header::Error, point::Format, raw, Bounds, GpsTimeType, Header, Result, Transform, Vector,
-    Version, Vlr,
+    header::Error,
+    point::Format,
+    raw,
+    waveform::{self, WaveformStorage},
+    Bounds, GpsTimeType, Header, Result, Transform, Vector, Version, Vlr,
 };
 use chrono::NaiveDate;
 use std::{cmp::Ordering, collections::HashMap};
 use uuid::Uuid;
 
 /// Use this structure to build a [Header].
+///
+/// With the `serde` feature, builders can be serialized and deserialized, e.g. to keep header
+/// templates in configuration files. Missing fields take their default values, and padding bytes
+/// are base64 strings:
+///
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # {
+/// use las::{Builder, Version};
+///
+/// let builder: Builder = serde_json::from_str(r#"{
+///     "version": { "major": 1, "minor": 4 },
+///     "system_identifier": "my scanner"
+/// }"#).unwrap();
+/// assert_eq!(Version::new(1, 4), builder.version);
+/// assert_eq!("my scanner", builder.system_identifier);
+/// # }
+/// ```
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Builder {
     /// The date of file creation.
     pub date: Option<NaiveDate>,
@@ -31,6 +57,7 @@ pub struct Builder {
     pub has_wkt_crs: bool,
 
     /// Bytes after the header but before the vlrs.
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_base64"))]
     pub padding: Vec<u8>,
 
     /// The format that the points will be written in.
@@ -39,6 +66,7 @@ pub struct Builder {
     /// The bytes after the points but before any evlrs.
     ///
     /// Discouraged.
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_base64"))]
     pub point_padding: Vec<u8>,
 
     /// The system that generated the points.
@@ -52,6 +80,7 @@ pub struct Builder {
     pub version: Version,
 
     /// The bytes after the vlrs but before the points.
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_base64"))]
     pub vlr_padding: Vec<u8>,
 
     /// The variable length records.
@@ -60,9 +89,13 @@ pub struct Builder {
     /// The extended variable length records.
     pub evlrs: Vec<Vlr>,
 
+    /// Where the waveform data packets are stored, if there are any.
+    pub waveform_storage: Option<WaveformStorage>,
+
     number_of_points_by_return: HashMap<u8, u64>,
     number_of_points: u64,
-    bounds: Bounds,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_empty"))]
+    pub(crate) bounds: Bounds,
 }
 
 impl Builder {
@@ -123,6 +156,13 @@ impl Builder {
             guid: Uuid::from_bytes(raw_header.guid),
             has_synthetic_return_numbers: raw_header.global_encoding & 8 == 8,
             has_wkt_crs: raw_header.global_encoding & 16 == 16,
+            waveform_storage: if raw_header.global_encoding & 2 == 2 {
+                Some(WaveformStorage::Internal)
+            } else if raw_header.global_encoding & 4 == 4 {
+                Some(WaveformStorage::External)
+            } else {
+                None
+            },
             padding: raw_header.padding,
             point_format,
             system_identifier: raw_header
@@ -174,7 +214,7 @@ impl Builder {
     /// ```
     pub fn into_header(mut self) -> Result<Header> {
         use crate::{
-            feature::{Evlrs, FileSourceId, GpsStandardTime, SyntheticReturnNumbers},
+            feature::{Evlrs, FileSourceId, GpsStandardTime, SyntheticReturnNumbers, Waveforms},
             raw::POINT_DATA_START_SIGNATURE,
         };
 
@@ -194,7 +234,9 @@ impl Builder {
         if self.gps_time_type.is_standard() {
             self.version.verify_support_for::<GpsStandardTime>()?;
         }
-        // TODO check waveforms
+        if self.waveform_storage.is_some() {
+            self.version.verify_support_for::<Waveforms>()?;
+        }
         if !self.version.supports_point_format(self.point_format) {
             return Err(Error::UnsupportedFormat {
                 version: self.version,
@@ -204,7 +246,11 @@ impl Builder {
         let mut vlrs = Vec::new();
         let mut evlrs = Vec::new();
         for evlr in self.evlrs {
-            if self.version.supports::<Evlrs>() || evlr.has_large_data() {
+            // Before las 1.4, the waveform data packet record is the only evlr.
+            if self.version.supports::<Evlrs>()
+                || evlr.has_large_data()
+                || waveform::is_data_packets_vlr(&evlr)
+            {
                 evlrs.push(evlr);
             } else {
                 log::warn!("moving Evlr to Vlr because version does not support Evlrs: user_id={}, record_id={}, description={}", evlr.user_id, evlr.record_id, evlr.description);
@@ -218,7 +264,10 @@ impl Builder {
                 vlrs.push(vlr);
             }
         }
-        if !evlrs.is_empty() {
+        if evlrs
+            .iter()
+            .any(|evlr| !waveform::is_data_packets_vlr(evlr))
+        {
             self.version.verify_support_for::<Evlrs>()?;
         } else if !self.point_padding.is_empty() {
             return Err(Error::PointPaddingNotAllowed);
@@ -226,6 +275,7 @@ impl Builder {
         let header = Header {
             bounds: self.bounds,
             date: self.date,
+            decoded_vlrs: Default::default(),
             evlrs,
             file_source_id: self.file_source_id,
             generating_software: self.generating_software,
@@ -239,11 +289,13 @@ impl Builder {
             point_format: self.point_format,
             point_padding: self.point_padding,
             start_of_first_evlr: None,
+            start_of_waveform_data_packet_record: None,
             system_identifier: self.system_identifier,
             transforms: self.transforms,
             version: self.version,
             vlr_padding: self.vlr_padding,
             vlrs,
+            waveform_storage: self.waveform_storage,
         };
         Ok(header)
     }
@@ -302,10 +354,17 @@ impl From<Header> for Builder {
             version: header.version,
             vlr_padding: header.vlr_padding,
             vlrs: header.vlrs,
+            waveform_storage: header.waveform_storage,
         }
     }
 }
 
+/// Empty bounds are infinite, which can't be serialized to e.g. JSON.
+#[cfg(feature = "serde")]
+fn is_empty(bounds: &Bounds) -> bool {
+    bounds.min.x > bounds.max.x
+}
+
 fn number_of_points_hash_map<T: Copy + Into<u64>>(slice: &[T]) -> HashMap<u8, u64> {
     assert!(slice.len() < u8::MAX as usize);
     slice
@@ -360,7 +419,7 @@ mod tests {
     fn evlr_upgrade() {
         let mut builder = Builder::from((1, 4));
         let vlr = Vlr {
-            data: vec![0; ::std::u16::MAX as usize + 1],
+            data: vec![0; u16::MAX as usize + 1],
             ..Default::default()
         };
         builder.vlrs.push(vlr);
@@ -61,8 +61,12 @@
 
 pub use self::builder::Builder;
 use crate::{
-    point::Format, raw, utils::FromLasStr, Bounds, Error, GpsTimeType, Point, Result, Transform,
-    Vector, Version, Vlr,
+    codec::DecodedVlrs,
+    point::Format,
+    raw,
+    utils::FromLasStr,
+    waveform::{self, WaveformStorage},
+    Bounds, Error, GpsTimeType, Point, Result, Transform, Vector, Version, Vlr,
 };
 use chrono::{Datelike, NaiveDate, Utc};
 use std::{collections::HashMap, io::Write, iter::Chain, slice::Iter};
@@ -75,11 +79,20 @@ mod builder;
 ///
 /// Headers include *all* las metadata, including regular and extended variable length records and
 /// any file padding (e.g. extra bytes after the header).
+///
+/// With the `serde` feature, headers are serialized as their [Builder], and are validated when
+/// deserialized.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Builder", into = "Builder")
+)]
 pub struct Header {
     bounds: Bounds,
     date: Option<NaiveDate>,
-    evlrs: Vec<Vlr>,
+    pub(crate) decoded_vlrs: DecodedVlrs,
+    pub(crate) evlrs: Vec<Vlr>,
     file_source_id: u16,
     generating_software: String,
     gps_time_type: GpsTimeType,
@@ -92,11 +105,13 @@ pub struct Header {
     point_format: Format,
     point_padding: Vec<u8>,
     start_of_first_evlr: Option<u64>,
+    pub(crate) start_of_waveform_data_packet_record: Option<u64>,
     system_identifier: String,
     transforms: Vector<Transform>,
     version: Version,
     vlr_padding: Vec<u8>,
     pub(crate) vlrs: Vec<Vlr>,
+    pub(crate) waveform_storage: Option<WaveformStorage>,
 }
 
 /// An iterator over a header's variable length records.
@@ -334,6 +349,10 @@ impl Header {
         &self.transforms
     }
 
+    pub(crate) fn transforms_mut(&mut self) -> &mut Vector<Transform> {
+        &mut self.transforms
+    }
+
     /// Returns the bounds of this header.
     ///
     /// The bounds describe the min and max values in each dimension.
@@ -449,6 +468,48 @@ impl Header {
         Vlrs(self.vlrs.iter().chain(&self.evlrs))
     }
 
+    /// Returns the first vlr or evlr with this user id and record id.
+    ///
+    /// Regular vlrs are searched before extended ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Reader;
+    ///
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let header = reader.header();
+    /// assert!(header.find_vlr("LASF_Projection", 34735).is_some());
+    /// assert!(header.find_vlr("LASF_Spec", 3).is_none());
+    /// ```
+    pub fn find_vlr(&self, user_id: &str, record_id: u16) -> Option<&Vlr> {
+        self.all_vlrs()
+            .find(|vlr| vlr.user_id == user_id && vlr.record_id == record_id)
+    }
+
+    /// Returns an iterator over every vlr and evlr with this user id and record id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Vlr};
+    ///
+    /// let mut builder = Builder::from((1, 4));
+    /// let vlr = Vlr { user_id: "gadomski".to_string(), record_id: 42, ..Default::default() };
+    /// builder.vlrs.push(vlr.clone());
+    /// builder.evlrs.push(vlr);
+    /// let header = builder.into_header().unwrap();
+    /// assert_eq!(2, header.find_vlrs("gadomski", 42).count());
+    /// ```
+    pub fn find_vlrs<'a>(
+        &'a self,
+        user_id: &'a str,
+        record_id: u16,
+    ) -> impl Iterator<Item = &'a Vlr> + 'a {
+        self.all_vlrs()
+            .filter(move |vlr| vlr.user_id == user_id && vlr.record_id == record_id)
+    }
+
     /// Converts this header into a raw header.
     ///
     /// # Examples
@@ -489,8 +550,7 @@ impl Header {
             min_y: bounds.min.y,
             max_z: bounds.max.z,
             min_z: bounds.min.z,
-            // FIXME waveforms
-            start_of_waveform_data_packet_record: None,
+            start_of_waveform_data_packet_record: self.start_of_waveform_data_packet_record,
             evlr: self.evlr()?,
             large_file: self.large_file()?,
             padding: self.padding,
@@ -529,8 +589,21 @@ impl Header {
         self.start_of_first_evlr = Some(start_of_first_evlr);
     }
 
+    /// Removes the waveform data packets evlr, returning its data.
+    pub(crate) fn take_waveform_data_packets(&mut self) -> Vec<u8> {
+        match self.evlrs.iter().position(waveform::is_data_packets_vlr) {
+            Some(i) => self.evlrs.remove(i).data,
+            None => Vec::new(),
+        }
+    }
+
     fn global_encoding(&self) -> u16 {
         let mut bits = self.gps_time_type.into();
+        match self.waveform_storage {
+            Some(WaveformStorage::Internal) => bits |= 2,
+            Some(WaveformStorage::External) => bits |= 4,
+            None => {}
+        }
         if self.has_synthetic_return_numbers {
             bits |= 8;
         }
@@ -673,11 +746,20 @@ impl Header {
     }
 }
 
+impl TryFrom<Builder> for Header {
+    type Error = Error;
+
+    fn try_from(builder: Builder) -> Result<Header> {
+        builder.into_header()
+    }
+}
+
 impl Default for Header {
     fn default() -> Header {
         Header {
             bounds: Default::default(),
             date: Some(Utc::now().date_naive()),
+            decoded_vlrs: Default::default(),
             evlrs: Vec::new(),
             file_source_id: 0,
             generating_software: format!("las-rs {}", env!("CARGO_PKG_VERSION")),
@@ -691,11 +773,13 @@ impl Default for Header {
             point_format: Default::default(),
             point_padding: Vec::new(),
             start_of_first_evlr: None,
+            start_of_waveform_data_packet_record: None,
             system_identifier: "las-rs".to_string(),
             transforms: Default::default(),
             version: Default::default(),
             vlr_padding: Vec::new(),
             vlrs: Vec::new(),
+            waveform_storage: None,
         }
     }
 }
@@ -779,7 +863,6 @@ mod tests {
 
     #[test]
     fn zero_legacy_fields_when_too_large() {
-        use std::u32;
         let mut header = Header::from((1, 4));
         header.number_of_points = u64::from(u32::MAX) + 1;
         let _ = header.number_of_points_by_return.insert(6, 42);
@@ -816,8 +899,6 @@ mod tests {
 
     #[test]
     fn number_of_points_large() {
-        use std::u32;
-
         let mut header = Header::from((1, 2));
         header.number_of_points = u32::MAX as u64 + 1;
         assert!(header.into_raw().is_err());
@@ -837,8 +918,6 @@ mod tests {
 
     #[test]
     fn number_of_points_by_return_large() {
-        use std::u32;
-
         let mut header = Header::from((1, 2));
         let _ = header
             .number_of_points_by_return
@@ -873,7 +952,6 @@ mod tests {
 
     #[test]
     fn header_too_large() {
-        use std::u16;
         let builder = Builder::new(raw::Header {
             padding: vec![0; u16::MAX as usize - 226],
             version: (1, 2).into(),
@@ -885,9 +963,47 @@ mod tests {
 
     #[test]
     fn offset_to_point_data_too_large() {
-        use std::u32;
         let mut builder = Builder::from((1, 2));
         builder.vlr_padding = vec![0; u32::MAX as usize - 226];
         assert!(builder.into_header().unwrap().into_raw().is_err());
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        let header = crate::Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .header()
+            .clone();
+        let json = serde_json::to_string(&header).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(106, value["number_of_points"]);
+        assert!(value["vlrs"][0]["data"].is_string());
+        let roundtrip: Header = serde_json::from_str(&json).unwrap();
+        assert_eq!(header.bounds(), roundtrip.bounds());
+        assert_eq!(header.number_of_points(), roundtrip.number_of_points());
+        assert_eq!(header.vlrs(), roundtrip.vlrs());
+        assert_eq!(header.point_format(), roundtrip.point_format());
+        assert_eq!(header.transforms(), roundtrip.transforms());
+        assert_eq!(header.date(), roundtrip.date());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_empty_bounds() {
+        let header = Header::default();
+        let json = serde_json::to_string(&header).unwrap();
+        let roundtrip: Header = serde_json::from_str(&json).unwrap();
+        assert_eq!(header.bounds(), roundtrip.bounds());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_invalid() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(6).unwrap();
+        let json = serde_json::to_string(&builder).unwrap();
+        assert!(serde_json::from_str::<Builder>(&json).is_ok());
+        assert!(serde_json::from_str::<Header>(&json).is_err());
+    }
 }
@@ -0,0 +1,455 @@
+//! Normalize elevations to heights above the ground.
+//!
+//! A [GroundSurface] is a grid of elevations built from the points classified as
+//! [Ground](crate::point::Classification::Ground). Each cell holds the mean elevation of its
+//! ground points, cells without ground points are filled by inverse-distance weighting from their
+//! neighbors, ring by ring outward from the cells that have them, and elevations between cell
+//! centers are interpolated bilinearly. A surface has at most [MAX_CELLS] cells.
+//!
+//! [normalize] reads a [Reader] twice, once to build the surface and once to write each point's
+//! height above it, either in place of z or in a `HeightAboveGround` extra bytes field:
+//!
+//! ```
+//! use las::{height::{self, Options, Output}, Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let options = Options { cell_size: 100., output: Output::ExtraBytes };
+//! let header = height::builder(reader.header(), &options).unwrap().into_header().unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! height::normalize(&mut reader, &mut writer, &options).unwrap();
+//! ```
+
+use crate::{
+    extra_bytes::{self, DataType, Descriptor},
+    point::Classification,
+    vlr::{LASZIP_RECORD_ID, LASZIP_USER_ID},
+    Builder, Error, Header, Point, Reader, Result, Writer,
+};
+use std::io::{Seek, Write};
+
+/// The maximum number of cells in a [GroundSurface].
+pub const MAX_CELLS: usize = 1 << 26;
+
+/// The name of the extra bytes field that holds the height above ground.
+pub const HEIGHT_ABOVE_GROUND: &str = "HeightAboveGround";
+
+/// Height normalization options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    /// The width and height of the ground surface's cells.
+    pub cell_size: f64,
+
+    /// Where to write the height above ground.
+    pub output: Output,
+}
+
+/// Where to write the height above ground.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Output {
+    /// Replace each point's z.
+    #[default]
+    Z,
+
+    /// Write to a `HeightAboveGround` extra bytes field, keeping z.
+    ExtraBytes,
+}
+
+/// A gridded ground surface.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroundSurface {
+    min_x: f64,
+    min_y: f64,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    elevations: Vec<f64>,
+}
+
+impl GroundSurface {
+    /// Builds a surface from the ground points.
+    ///
+    /// Points with any other classification are ignored. Returns an error if there are no ground
+    /// points, if the cell size isn't positive, or if the surface would have more than
+    /// [MAX_CELLS] cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{height::GroundSurface, point::Classification, Point};
+    ///
+    /// let points: Vec<_> = [(0., 0., 10.), (10., 0., 20.), (0., 10., 10.), (10., 10., 20.)]
+    ///     .into_iter()
+    ///     .map(|(x, y, z)| Point { x, y, z, classification: Classification::Ground, ..Default::default() })
+    ///     .collect();
+    /// let surface = GroundSurface::from_points(&points, 10.).unwrap();
+    /// assert_eq!(15., surface.elevation(10., 10.));
+    /// ```
+    pub fn from_points<'a, I>(points: I, cell_size: f64) -> Result<GroundSurface>
+    where
+        I: IntoIterator<Item = &'a Point>,
+    {
+        if !cell_size.is_finite() || cell_size <= 0. {
+            return Err(Error::InvalidCellSize(cell_size));
+        }
+        let ground: Vec<(f64, f64, f64)> = points
+            .into_iter()
+            .filter(|point| point.classification == Classification::Ground)
+            .map(|point| (point.x, point.y, point.z))
+            .collect();
+        if ground.is_empty() {
+            return Err(Error::NoGroundPoints);
+        }
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y, _) in &ground {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        let columns = ((max_x - min_x) / cell_size).floor() + 1.;
+        let rows = ((max_y - min_y) / cell_size).floor() + 1.;
+        if columns * rows > MAX_CELLS as f64 {
+            return Err(Error::TooManyCells {
+                columns: columns as u64,
+                rows: rows as u64,
+            });
+        }
+        let (columns, rows) = (columns as usize, rows as usize);
+        let mut sums = vec![(0., 0); columns * rows];
+        for &(x, y, z) in &ground {
+            let column = (((x - min_x) / cell_size) as usize).min(columns - 1);
+            let row = (((y - min_y) / cell_size) as usize).min(rows - 1);
+            let sum = &mut sums[row * columns + column];
+            sum.0 += z;
+            sum.1 += 1;
+        }
+        let means: Vec<Option<f64>> = sums
+            .into_iter()
+            .map(|(sum, count)| (count > 0).then(|| sum / f64::from(count)))
+            .collect();
+        Ok(GroundSurface {
+            min_x,
+            min_y,
+            cell_size,
+            columns,
+            rows,
+            elevations: fill(means, columns, rows),
+        })
+    }
+
+    /// Returns the ground elevation at these coordinates.
+    ///
+    /// Coordinates outside of the surface use the elevation at the nearest edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{height::GroundSurface, point::Classification, Point};
+    ///
+    /// let point = Point { z: 42., classification: Classification::Ground, ..Default::default() };
+    /// let surface = GroundSurface::from_points([&point], 1.).unwrap();
+    /// assert_eq!(42., surface.elevation(100., -100.));
+    /// ```
+    pub fn elevation(&self, x: f64, y: f64) -> f64 {
+        let position = |n: f64, min: f64, len: usize| {
+            let n = ((n - min) / self.cell_size - 0.5).clamp(0., (len - 1) as f64);
+            let i = (n.floor() as usize).min(len.saturating_sub(2));
+            (i, (i + 1).min(len - 1), n - i as f64)
+        };
+        let (c0, c1, tx) = position(x, self.min_x, self.columns);
+        let (r0, r1, ty) = position(y, self.min_y, self.rows);
+        let z = |column: usize, row: usize| self.elevations[row * self.columns + column];
+        let bottom = z(c0, r0) * (1. - tx) + z(c1, r0) * tx;
+        let top = z(c0, r1) * (1. - tx) + z(c1, r1) * tx;
+        bottom * (1. - ty) + top * ty
+    }
+
+    /// Returns the height of a point above this surface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{height::GroundSurface, point::Classification, Point};
+    ///
+    /// let ground = Point { z: 10., classification: Classification::Ground, ..Default::default() };
+    /// let surface = GroundSurface::from_points([&ground], 1.).unwrap();
+    /// assert_eq!(5., surface.height(&Point { z: 15., ..Default::default() }));
+    /// ```
+    pub fn height(&self, point: &Point) -> f64 {
+        point.z - self.elevation(point.x, point.y)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            cell_size: 1.,
+            output: Output::default(),
+        }
+    }
+}
+
+/// Creates a builder for the normalized points from the header of the points to be normalized.
+///
+/// If the output is [Output::ExtraBytes] and the header doesn't already have a
+/// `HeightAboveGround` field, a four-byte float field is added after any existing extra bytes.
+///
+/// # Examples
+///
+/// ```
+/// use las::{height::{self, Options, Output}, Header};
+///
+/// let options = Options { output: Output::ExtraBytes, ..Default::default() };
+/// let header = height::builder(&Header::default(), &options).unwrap().into_header().unwrap();
+/// assert_eq!(4, header.point_format().extra_bytes);
+/// assert_eq!("HeightAboveGround", header.extra_bytes().unwrap()[0].name);
+/// ```
+pub fn builder(header: &Header, options: &Options) -> Result<Builder> {
+    let mut descriptors = header.extra_bytes()?;
+    let mut builder = Builder::from(header.clone());
+    builder
+        .vlrs
+        .retain(|vlr| !(vlr.user_id == LASZIP_USER_ID && vlr.record_id == LASZIP_RECORD_ID));
+    if options.output == Output::ExtraBytes
+        && extra_bytes::find(&descriptors, HEIGHT_ABOVE_GROUND).is_none()
+    {
+        let mut descriptor = Descriptor::new(HEIGHT_ABOVE_GROUND, DataType::F32);
+        descriptor.description = "Height above ground".to_string();
+        let offset = descriptors.iter().map(Descriptor::len).sum::<usize>();
+        builder.point_format.extra_bytes = u16::try_from(offset + descriptor.len())?;
+        descriptors.push(descriptor);
+        builder.vlrs.retain(|vlr| {
+            !(vlr.user_id == extra_bytes::USER_ID && vlr.record_id == extra_bytes::RECORD_ID)
+        });
+        builder.vlrs.push(extra_bytes::vlr(&descriptors)?);
+    }
+    Ok(builder)
+}
+
+/// Builds a ground surface from the reader's points, then writes every point with its height
+/// above ground.
+///
+/// The reader is read from its first point, twice. Points are conformed to the writer's point
+/// format, whose header should come from [builder] if the output is [Output::ExtraBytes].
+/// Returns the number of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{height::{self, Options}, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let options = Options { cell_size: 100., ..Default::default() };
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+/// assert_eq!(106, height::normalize(&mut reader, &mut writer, &options).unwrap());
+/// ```
+pub fn normalize<W: 'static + Write + Seek + Send>(
+    reader: &mut Reader,
+    writer: &mut Writer<W>,
+    options: &Options,
+) -> Result<u64> {
+    reader.seek(0)?;
+    let mut ground = Vec::new();
+    for point in reader.points() {
+        let point = point?;
+        if point.classification == Classification::Ground {
+            ground.push(point);
+        }
+    }
+    let surface = GroundSurface::from_points(&ground, options.cell_size)?;
+    drop(ground);
+
+    let format = *writer.header().point_format();
+    let field = match options.output {
+        Output::Z => None,
+        Output::ExtraBytes => {
+            let descriptors = writer.header().extra_bytes()?;
+            let (offset, descriptor) = extra_bytes::find(&descriptors, HEIGHT_ABOVE_GROUND)
+                .ok_or_else(|| Error::UnknownExtraBytes(HEIGHT_ABOVE_GROUND.to_string()))?;
+            Some((offset, descriptor.clone()))
+        }
+    };
+    reader.seek(0)?;
+    let mut count = 0;
+    for point in reader.points() {
+        let mut point = point?;
+        point.conform_to(&format);
+        let height = surface.height(&point);
+        match &field {
+            None => point.z = height,
+            Some((offset, descriptor)) => {
+                descriptor.encode(
+                    height,
+                    &mut point.extra_bytes[*offset..*offset + descriptor.len()],
+                )?;
+            }
+        }
+        writer.write_point(point)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Fills the cells without ground points, ring by ring outward from the cells that have them.
+///
+/// Each empty cell gets the inverse-distance weighted elevation of its neighbors that were
+/// filled in an earlier ring, so every cell is visited a constant number of times.
+fn fill(mut means: Vec<Option<f64>>, columns: usize, rows: usize) -> Vec<f64> {
+    let neighbors = |index: usize| {
+        let (column, row) = ((index % columns) as isize, (index / columns) as isize);
+        (-1..=1isize)
+            .flat_map(|dy| (-1..=1isize).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| dx != 0 || dy != 0)
+            .filter_map(move |(dx, dy)| {
+                let (c, r) = (column + dx, row + dy);
+                (c >= 0 && r >= 0 && c < columns as isize && r < rows as isize).then(|| {
+                    let weight = 1. / (dx * dx + dy * dy) as f64;
+                    (r as usize * columns + c as usize, weight)
+                })
+            })
+    };
+    let mut queued: Vec<bool> = means.iter().map(Option::is_some).collect();
+    let mut ring = Vec::new();
+    for index in 0..means.len() {
+        if means[index].is_none() && neighbors(index).any(|(i, _)| means[i].is_some()) {
+            queued[index] = true;
+            ring.push(index);
+        }
+    }
+    while !ring.is_empty() {
+        let elevations: Vec<f64> = ring
+            .iter()
+            .map(|&index| {
+                let (mut sum, mut weights) = (0., 0.);
+                for (i, weight) in neighbors(index) {
+                    if let Some(mean) = means[i] {
+                        sum += mean * weight;
+                        weights += weight;
+                    }
+                }
+                sum / weights
+            })
+            .collect();
+        for (&index, elevation) in ring.iter().zip(elevations) {
+            means[index] = Some(elevation);
+        }
+        let mut next = Vec::new();
+        for &index in &ring {
+            for (i, _) in neighbors(index) {
+                if !queued[i] {
+                    queued[i] = true;
+                    next.push(i);
+                }
+            }
+        }
+        ring = next;
+    }
+    means
+        .into_iter()
+        .map(|mean| mean.expect("a ground surface always has at least one cell with ground points"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn ground(x: f64, y: f64, z: f64) -> Point {
+        Point {
+            x,
+            y,
+            z,
+            classification: Classification::Ground,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn plane() {
+        let points: Vec<_> = (0..10)
+            .flat_map(|i| (0..10).map(move |j| ground(f64::from(i), f64::from(j), f64::from(i))))
+            .collect();
+        let surface = GroundSurface::from_points(&points, 1.).unwrap();
+        for x in [0.5, 2.25, 7.5] {
+            assert!((surface.elevation(x, 3.) - (x - 0.5)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fill_empty_cells() {
+        let points = [ground(0., 0., 0.), ground(4., 0., 8.)];
+        let surface = GroundSurface::from_points(&points, 1.).unwrap();
+        assert_eq!(5, surface.columns);
+        assert_eq!(4., surface.elevations[2]);
+        assert_eq!(0., surface.elevations[1]);
+        assert_eq!(8., surface.elevations[3]);
+    }
+
+    #[test]
+    fn ignores_other_classes() {
+        let points = [
+            ground(0., 0., 10.),
+            Point {
+                z: 100.,
+                classification: Classification::HighVegetation,
+                ..Default::default()
+            },
+        ];
+        let surface = GroundSurface::from_points(&points, 1.).unwrap();
+        assert_eq!(10., surface.elevation(0., 0.));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(GroundSurface::from_points(&[Point::default()], 1.).is_err());
+        assert!(GroundSurface::from_points(&[ground(0., 0., 0.)], 0.).is_err());
+    }
+
+    #[test]
+    fn too_many_cells() {
+        let points = [ground(0., 0., 0.), ground(1e6, 1e6, 0.)];
+        assert!(matches!(
+            GroundSurface::from_points(&points, 1.).unwrap_err(),
+            Error::TooManyCells { .. }
+        ));
+    }
+
+    #[test]
+    fn fill_sparse_grid() {
+        let points = [ground(0., 0., 10.), ground(300., 300., 10.)];
+        let surface = GroundSurface::from_points(&points, 1.).unwrap();
+        assert!(surface.elevations.iter().all(|&z| z == 10.));
+    }
+
+    #[test]
+    fn normalize_extra_bytes() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let options = Options {
+            cell_size: 50.,
+            output: Output::ExtraBytes,
+        };
+        let header = builder(reader.header(), &options)
+            .unwrap()
+            .into_header()
+            .unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        assert_eq!(106, normalize(&mut reader, &mut writer, &options).unwrap());
+        writer.close().unwrap();
+
+        let mut original = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut normalized = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let descriptors = normalized.header().extra_bytes().unwrap();
+        let originals = original.points().collect::<Result<Vec<_>>>().unwrap();
+        let surface = GroundSurface::from_points(&originals, 50.).unwrap();
+        for (a, b) in originals.iter().zip(normalized.points()) {
+            let b = b.unwrap();
+            assert_eq!(a.z, b.z);
+            let height = extra_bytes::value(&descriptors, HEIGHT_ABOVE_GROUND, &b).unwrap();
+            assert!((height - surface.height(a)).abs() < 1e-3);
+        }
+    }
+}
@@ -1,8 +1,7 @@
 //! Utility functions for working with laszip compressed data.
 
-use crate::{Error, Header, Result, Vlr};
+use crate::{vlr::KnownVlr, Error, Header, Result, Vlr};
 use laz::{LazItemRecordBuilder, LazItemType, LazVlr};
-use std::io::Cursor;
 
 /// Returns true if this [Vlr] is the laszip Vlr.
 ///
@@ -71,14 +70,7 @@ impl Header {
             }
         }
         let laz_vlr = LazVlr::from_laz_items(laz_items.build());
-        let mut cursor = Cursor::new(Vec::<u8>::new());
-        laz_vlr.write_to(&mut cursor)?;
-        let vlr = Vlr {
-            user_id: LazVlr::USER_ID.to_owned(),
-            record_id: LazVlr::RECORD_ID,
-            description: LazVlr::DESCRIPTION.to_owned(),
-            data: cursor.into_inner(),
-        };
+        let vlr = KnownVlr::Laszip(laz_vlr).to_vlr()?;
         self.vlrs.push(vlr);
         Ok(())
     }
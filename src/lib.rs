@@ -175,18 +175,38 @@
 #[cfg(feature = "laz")]
 pub mod laz;
 
+pub mod boundary;
+pub mod classification;
+pub mod codec;
+pub mod crs;
+pub mod density;
+pub mod edit;
+pub mod extra_bytes;
 pub mod feature;
+pub mod filters;
 pub mod header;
+pub mod height;
+pub mod merge;
+pub mod ply;
 pub mod point;
+pub mod raster;
 pub mod raw;
 pub mod reader;
+pub mod reproject;
+pub mod rescale;
+pub mod sort;
+pub mod stats;
+pub mod text;
+pub mod tile;
 pub mod vlr;
+pub mod waveform;
 pub mod writer;
 
 mod bounds;
 mod color;
 mod error;
 mod gps_time_type;
+mod spill;
 mod transform;
 mod utils;
 mod vector;
@@ -201,7 +221,7 @@ pub use crate::{
     header::{Builder, Header},
     point::Point,
     reader::Reader,
-    transform::Transform,
+    transform::{Transform, TransformPolicy},
     vector::Vector,
     version::Version,
     vlr::Vlr,
@@ -213,5 +233,9 @@ pub use {reader::Read, writer::Write};
 /// Crate-specific result type.
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(feature = "cli")]
+use clap as _;
 #[cfg(test)]
 use criterion as _;
+#[cfg(any(test, feature = "cli"))]
+use serde_json as _;
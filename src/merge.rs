@@ -0,0 +1,429 @@
+//! Combine several las files into one.
+//!
+//! Use [builder] to create a [Builder] whose point format, version, transforms, and vlrs can hold
+//! the data from all of the inputs, then [merge] to write every input's points to a single
+//! [Writer]:
+//!
+//! ```
+//! use las::{merge, Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut readers = vec![
+//!     Reader::from_path("tests/data/autzen.las").unwrap(),
+//!     Reader::from_path("tests/data/autzen.las").unwrap(),
+//! ];
+//! let options = merge::Options::default();
+//! let builder = merge::builder(readers.iter().map(|r| r.header()), &options).unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+//! let count = merge::merge(&mut readers, &mut writer, &options).unwrap();
+//! assert_eq!(count, 2 * 106);
+//! ```
+
+use crate::{
+    crs::{self, GeoKeys},
+    vlr::{LASZIP_RECORD_ID, LASZIP_USER_ID},
+    Bounds, Builder, Error, Header, Reader, Result, Vector, Vlr, Writer,
+};
+use std::io::{Seek, Write};
+
+/// Options for merging.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Options {
+    /// How to set each point's source id.
+    pub point_source_id: PointSourceId,
+
+    /// What to do when two inputs have vlrs with the same user id and record id but different
+    /// data, or different coordinate reference systems.
+    pub vlr_conflict: VlrConflict,
+
+    /// The precision of the merged coordinates.
+    ///
+    /// If `None`, uses the finest scale found in the inputs.
+    pub precision: Option<Vector<f64>>,
+}
+
+/// How to set the point source id of merged points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointSourceId {
+    /// Keep each point's source id.
+    #[default]
+    Keep,
+
+    /// Use the file source id from each input's header.
+    FileSourceId,
+
+    /// Use each input's position in the list of inputs, starting at one.
+    Index,
+}
+
+/// What to do with conflicting vlrs.
+///
+/// The coordinate reference system vlrs are treated as a whole: two inputs conflict if their
+/// GeoTIFF keys or WKT differ, or if only one of them has a coordinate reference system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VlrConflict {
+    /// Return [Error::VlrConflict], or [Error::CrsConflict] for coordinate reference systems.
+    #[default]
+    Error,
+
+    /// Keep the vlr, or the coordinate reference system, from the first input that has it.
+    KeepFirst,
+}
+
+/// A header's coordinate reference system.
+#[derive(Debug, PartialEq)]
+struct Crs {
+    geo_keys: Option<GeoKeys>,
+    wkt: Option<String>,
+}
+
+/// Creates a builder that can hold the points from all of the headers.
+///
+/// The point format is the [union](crate::point::Format::union) of all the input formats, the
+/// version is the newest input version (or newer, if the point format requires it), and the
+/// transforms are fit to the combined bounds. Vlrs and evlrs are de-duplicated, except for the
+/// laszip vlr, which is dropped. The compression of the first header is kept.
+///
+/// Returns an error if the gps time types differ, or if vlrs or coordinate reference systems
+/// conflict and `options.vlr_conflict` is [VlrConflict::Error].
+///
+/// # Examples
+///
+/// ```
+/// use las::{merge, point::Format, Builder, Version};
+///
+/// let mut a = Builder::from((1, 2));
+/// a.point_format = Format::new(1).unwrap();
+/// let mut b = Builder::from((1, 4));
+/// b.point_format = Format::new(2).unwrap();
+/// let headers = [a.into_header().unwrap(), b.into_header().unwrap()];
+/// let builder = merge::builder(&headers, &Default::default()).unwrap();
+/// assert_eq!(Format::new(3).unwrap(), builder.point_format);
+/// assert_eq!(Version::new(1, 4), builder.version);
+/// ```
+pub fn builder<'a, I>(headers: I, options: &Options) -> Result<Builder>
+where
+    I: IntoIterator<Item = &'a Header>,
+{
+    let mut builder = Builder::from(Header::default());
+    builder.system_identifier = "MERGE".to_string();
+    let mut first = true;
+    let mut bounds = Bounds::default();
+    let mut precision: Option<Vector<f64>> = None;
+    let mut crs = None;
+    for header in headers {
+        let header_crs = Crs::new(header)?;
+        if first || (crs.is_none() && options.vlr_conflict == VlrConflict::KeepFirst) {
+            if header_crs.is_some() {
+                builder.has_wkt_crs = header.has_wkt_crs();
+                builder
+                    .vlrs
+                    .extend(header.vlrs().iter().filter(|v| is_crs_vlr(v)).cloned());
+                builder
+                    .evlrs
+                    .extend(header.evlrs().iter().filter(|v| is_crs_vlr(v)).cloned());
+            }
+            crs = header_crs;
+        } else if crs != header_crs && options.vlr_conflict == VlrConflict::Error {
+            return Err(Error::CrsConflict);
+        }
+        if first {
+            builder.point_format = *header.point_format();
+            builder.version = header.version();
+            builder.gps_time_type = header.gps_time_type();
+            first = false;
+        } else {
+            builder.point_format = builder.point_format.union(header.point_format());
+            builder.version = builder.version.max(header.version());
+            if builder.gps_time_type != header.gps_time_type() {
+                return Err(Error::GpsTimeTypeMismatch(
+                    builder.gps_time_type,
+                    header.gps_time_type(),
+                ));
+            }
+        }
+        builder.has_synthetic_return_numbers |= header.has_synthetic_return_numbers();
+        bounds = bounds.union(&header.bounds());
+        let transforms = header.transforms();
+        precision = Some(match precision {
+            Some(precision) => Vector {
+                x: precision.x.min(transforms.x.scale),
+                y: precision.y.min(transforms.y.scale),
+                z: precision.z.min(transforms.z.scale),
+            },
+            None => Vector {
+                x: transforms.x.scale,
+                y: transforms.y.scale,
+                z: transforms.z.scale,
+            },
+        });
+        add_vlrs(&mut builder.vlrs, header.vlrs(), options.vlr_conflict)?;
+        add_vlrs(&mut builder.evlrs, header.evlrs(), options.vlr_conflict)?;
+    }
+    if let Some(precision) = options.precision.or(precision) {
+        builder.transforms = bounds.fit_transforms(&precision)?;
+    }
+    if let Some(version) = builder.minimum_supported_version() {
+        builder.version = builder.version.max(version);
+    }
+    Ok(builder)
+}
+
+/// Writes the remaining points from all of the readers, in order, to the writer.
+///
+/// Points are [conformed](crate::Point::conform_to) to the writer's point format. Returns the
+/// number of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{merge, Reader, Writer};
+///
+/// let mut readers = vec![Reader::from_path("tests/data/autzen.las").unwrap()];
+/// let mut writer = Writer::default();
+/// let options = merge::Options {
+///     point_source_id: merge::PointSourceId::Index,
+///     ..Default::default()
+/// };
+/// assert_eq!(106, merge::merge(&mut readers, &mut writer, &options).unwrap());
+/// ```
+pub fn merge<W: 'static + Write + Seek + Send>(
+    readers: &mut [Reader],
+    writer: &mut Writer<W>,
+    options: &Options,
+) -> Result<u64> {
+    let format = *writer.header().point_format();
+    let mut count = 0;
+    for (i, reader) in readers.iter_mut().enumerate() {
+        let point_source_id = match options.point_source_id {
+            PointSourceId::Keep => None,
+            PointSourceId::FileSourceId => Some(reader.header().file_source_id()),
+            PointSourceId::Index => Some(u16::try_from(i + 1)?),
+        };
+        for point in reader.points() {
+            let mut point = point?;
+            point.conform_to(&format);
+            if let Some(point_source_id) = point_source_id {
+                point.point_source_id = point_source_id;
+            }
+            writer.write_point(point)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+impl Crs {
+    fn new(header: &Header) -> Result<Option<Crs>> {
+        let crs = Crs {
+            geo_keys: header.geo_keys()?,
+            wkt: header.wkt()?,
+        };
+        if crs.geo_keys.is_none() && crs.wkt.is_none() {
+            Ok(None)
+        } else {
+            Ok(Some(crs))
+        }
+    }
+}
+
+fn is_crs_vlr(vlr: &Vlr) -> bool {
+    vlr.user_id == crs::USER_ID
+}
+
+fn add_vlrs(vlrs: &mut Vec<Vlr>, new_vlrs: &[Vlr], vlr_conflict: VlrConflict) -> Result<()> {
+    for vlr in new_vlrs {
+        // The coordinate reference system is merged as a whole by `builder`.
+        if (vlr.user_id == LASZIP_USER_ID && vlr.record_id == LASZIP_RECORD_ID) || is_crs_vlr(vlr) {
+            continue;
+        }
+        match vlrs
+            .iter()
+            .find(|v| v.user_id == vlr.user_id && v.record_id == vlr.record_id)
+        {
+            Some(existing) => {
+                if existing.data != vlr.data && vlr_conflict == VlrConflict::Error {
+                    return Err(Error::VlrConflict {
+                        user_id: vlr.user_id.clone(),
+                        record_id: vlr.record_id,
+                    });
+                }
+            }
+            None => vlrs.push(vlr.clone()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, GpsTimeType, Point, Version};
+
+    fn vlr(data: Vec<u8>) -> Vlr {
+        Vlr {
+            user_id: "merge".to_string(),
+            record_id: 42,
+            description: String::new(),
+            data,
+        }
+    }
+
+    fn reader(builder: Builder, points: Vec<Point>) -> Reader {
+        let mut writer = Writer::new(
+            std::io::Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+        )
+        .unwrap();
+        for point in points {
+            writer.write_point(point).unwrap();
+        }
+        Reader::new(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn gps_time_type_mismatch() {
+        let a = Header::default();
+        let mut b = Builder::default();
+        b.gps_time_type = GpsTimeType::Standard;
+        let b = b.into_header().unwrap();
+        assert!(matches!(
+            builder([&a, &b], &Default::default()).unwrap_err(),
+            Error::GpsTimeTypeMismatch(..)
+        ));
+    }
+
+    #[test]
+    fn vlrs() {
+        let mut a = Builder::default();
+        a.vlrs.push(vlr(vec![1]));
+        let a = a.into_header().unwrap();
+        let mut b = Builder::default();
+        b.vlrs.push(vlr(vec![1]));
+        let b = b.into_header().unwrap();
+        let mut c = Builder::default();
+        c.vlrs.push(vlr(vec![2]));
+        let c = c.into_header().unwrap();
+
+        let merged = builder([&a, &b], &Default::default()).unwrap();
+        assert_eq!(1, merged.vlrs.len());
+        assert!(matches!(
+            builder([&a, &c], &Default::default()).unwrap_err(),
+            Error::VlrConflict { record_id: 42, .. }
+        ));
+        let options = Options {
+            vlr_conflict: VlrConflict::KeepFirst,
+            ..Default::default()
+        };
+        let merged = builder([&a, &c], &options).unwrap();
+        assert_eq!(vec![1], merged.vlrs[0].data);
+    }
+
+    fn geo_keys() -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.vlrs = GeoKeys::from_epsg(4326).unwrap().to_vlrs().unwrap();
+        builder.into_header().unwrap()
+    }
+
+    fn wkt() -> Header {
+        let mut builder = Builder::from((1, 4));
+        builder.set_wkt("GEOGCS[\"WGS 84\"]");
+        builder.into_header().unwrap()
+    }
+
+    #[test]
+    fn crs_geo_keys_and_wkt() {
+        let (a, b) = (geo_keys(), wkt());
+        assert!(matches!(
+            builder([&a, &b], &Default::default()).unwrap_err(),
+            Error::CrsConflict
+        ));
+        let options = Options {
+            vlr_conflict: VlrConflict::KeepFirst,
+            ..Default::default()
+        };
+        let merged = builder([&a, &b], &options).unwrap();
+        assert!(!merged.has_wkt_crs);
+        assert!(merged.wkt().unwrap().is_none());
+        let header = merged.into_header().unwrap();
+        assert_eq!(Some(4326), header.geo_keys().unwrap().unwrap().epsg());
+    }
+
+    #[test]
+    fn crs_and_no_crs() {
+        let (a, b) = (Header::default(), wkt());
+        assert!(matches!(
+            builder([&a, &b], &Default::default()).unwrap_err(),
+            Error::CrsConflict
+        ));
+        assert!(matches!(
+            builder([&b, &a], &Default::default()).unwrap_err(),
+            Error::CrsConflict
+        ));
+        let options = Options {
+            vlr_conflict: VlrConflict::KeepFirst,
+            ..Default::default()
+        };
+        let merged = builder([&a, &b], &options).unwrap();
+        assert!(merged.has_wkt_crs);
+        assert_eq!("GEOGCS[\"WGS 84\"]", merged.wkt().unwrap().unwrap());
+    }
+
+    #[test]
+    fn same_crs() {
+        let (a, b) = (wkt(), wkt());
+        let merged = builder([&a, &b], &Default::default()).unwrap();
+        assert_eq!(1, merged.vlrs.len());
+        assert!(merged.has_wkt_crs);
+    }
+
+    #[test]
+    fn format_version_and_transforms() {
+        let mut a = Builder::from((1, 2));
+        a.point_format = Format::new(1).unwrap();
+        let a = reader(
+            a,
+            vec![Point {
+                x: 1.,
+                gps_time: Some(42.),
+                point_source_id: 7,
+                ..Default::default()
+            }],
+        );
+        let mut b = Builder::from((1, 4));
+        b.point_format = Format::new(7).unwrap();
+        b.transforms.x.offset = 4_000_000.;
+        let b = reader(
+            b,
+            vec![Point {
+                x: 4_000_000.,
+                gps_time: Some(43.),
+                color: Some(Default::default()),
+                ..Default::default()
+            }],
+        );
+        let mut readers = vec![a, b];
+        let options = Options {
+            point_source_id: PointSourceId::Index,
+            ..Default::default()
+        };
+        let builder = builder(readers.iter().map(|r| r.header()), &options).unwrap();
+        assert_eq!(Format::new(7).unwrap(), builder.point_format);
+        assert_eq!(Version::new(1, 4), builder.version);
+        assert_eq!(0.001, builder.transforms.x.scale);
+
+        let mut writer = Writer::new(
+            std::io::Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(2, merge(&mut readers, &mut writer, &options).unwrap());
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(1., points[0].x);
+        assert_eq!(1, points[0].point_source_id);
+        assert!(points[0].color.is_some());
+        assert_eq!(4_000_000., points[1].x);
+        assert_eq!(2, points[1].point_source_id);
+    }
+}
@@ -0,0 +1,790 @@
+//! Read and write points as [PLY](https://paulbourke.net/dataformats/ply/) vertices.
+//!
+//! [export] writes every point from a las [Reader](crate::Reader) as a ply vertex, with
+//! properties chosen from the header's point format and extra bytes:
+//!
+//! ```
+//! use las::{ply::{self, Encoding}, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut ply = Vec::new();
+//! let count = ply::export(&mut reader, &mut ply, Encoding::BinaryLittleEndian).unwrap();
+//! assert_eq!(106, count);
+//! ```
+//!
+//! [import] reads ply vertices into a las [Writer], using the point format and transforms of the
+//! writer's header:
+//!
+//! ```
+//! use las::{ply, Writer};
+//!
+//! let ply = "ply
+//! format ascii 1.0
+//! element vertex 2
+//! property float x
+//! property float y
+//! property float z
+//! property uchar classification
+//! end_header
+//! 1 2 3 2
+//! 4 5 6 2
+//! ";
+//! let mut writer = Writer::default();
+//! assert_eq!(2, ply::import(ply.as_bytes(), &mut writer).unwrap());
+//! ```
+//!
+//! Vertex properties are matched to point [Dimension]s by name, ignoring case and underscores,
+//! then to extra bytes fields by name. Other properties and elements are ignored. Values are
+//! stored as they are, except for `uchar` colors, which are scaled between the 8-bit ply range
+//! and the 16-bit las range, e.g. 255 is read as 65535.
+
+use crate::{
+    extra_bytes::{self, DataType, Descriptor},
+    point::Dimension,
+    Error, Point, Reader as LasReader, Result, Writer,
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{BufRead, Read, Seek, Write};
+
+/// How ply data is encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Whitespace-separated text, one element per line.
+    Ascii,
+
+    /// Little-endian binary.
+    #[default]
+    BinaryLittleEndian,
+
+    /// Big-endian binary.
+    BinaryBigEndian,
+}
+
+/// Reads points from the vertices of ply data.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    read: R,
+    encoding: Encoding,
+    properties: Vec<(Kind, Target)>,
+    extra_bytes_len: usize,
+    number_of_vertices: u64,
+    remaining: u64,
+    line: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScalarType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType),
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: u64,
+    properties: Vec<(Kind, String)>,
+}
+
+#[derive(Clone, Debug)]
+enum Target {
+    Dimension(Dimension),
+    ExtraBytes(usize, Descriptor),
+    /// An 8-bit ply color stored as a 16-bit las color.
+    Color(Dimension),
+    Ignore,
+}
+
+/// The dimensions written to ply, if the point format has them.
+const DIMENSIONS: [(Dimension, ScalarType); 14] = [
+    (Dimension::X, ScalarType::Double),
+    (Dimension::Y, ScalarType::Double),
+    (Dimension::Z, ScalarType::Double),
+    (Dimension::Intensity, ScalarType::UShort),
+    (Dimension::ReturnNumber, ScalarType::UChar),
+    (Dimension::NumberOfReturns, ScalarType::UChar),
+    (Dimension::Classification, ScalarType::UChar),
+    (Dimension::ScanAngle, ScalarType::Float),
+    (Dimension::UserData, ScalarType::UChar),
+    (Dimension::PointSourceId, ScalarType::UShort),
+    (Dimension::GpsTime, ScalarType::Double),
+    (Dimension::Red, ScalarType::UChar),
+    (Dimension::Green, ScalarType::UChar),
+    (Dimension::Blue, ScalarType::UChar),
+];
+
+/// Writes every point from a las reader as a ply vertex.
+///
+/// The vertices have x, y, z, intensity, return number, number of returns, classification, scan
+/// angle, user data, and point source id properties, plus gps time, color, and near infrared if
+/// the point format has them, and one property for each numeric extra bytes field. Colors are
+/// scaled down to `uchar`s. Returns the number of points written.
+///
+/// The reader is rewound, so every point is written and the vertex count in the ply header
+/// matches the body. If reading a point fails, the ply data written so far is incomplete.
+///
+/// # Examples
+///
+/// ```
+/// use las::{ply::{self, Encoding}, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut ply = Vec::new();
+/// ply::export(&mut reader, &mut ply, Encoding::Ascii).unwrap();
+/// let ply = String::from_utf8(ply).unwrap();
+/// assert!(ply.contains("element vertex 106\n"));
+/// assert!(ply.contains("property double gps_time\n"));
+/// ```
+pub fn export<W: Write>(reader: &mut LasReader, mut write: W, encoding: Encoding) -> Result<u64> {
+    reader.seek(0)?;
+    let header = reader.header().clone();
+    let format = header.point_format();
+    let transforms = header.transforms();
+    let decimals = [
+        crate::text::decimals(transforms.x.scale),
+        crate::text::decimals(transforms.y.scale),
+        crate::text::decimals(transforms.z.scale),
+    ];
+    let mut properties = Vec::new();
+    for (dimension, scalar_type) in DIMENSIONS {
+        let (has, target) = match dimension {
+            Dimension::GpsTime => (format.has_gps_time, Target::Dimension(dimension)),
+            Dimension::Red | Dimension::Green | Dimension::Blue => {
+                (format.has_color, Target::Color(dimension))
+            }
+            _ => (true, Target::Dimension(dimension)),
+        };
+        if has {
+            properties.push((snake_case(dimension.name()), scalar_type, target));
+        }
+    }
+    if format.has_nir {
+        properties.push((
+            "nir".to_string(),
+            ScalarType::UShort,
+            Target::Dimension(Dimension::Nir),
+        ));
+    }
+    let mut offset = 0;
+    for descriptor in header.extra_bytes()? {
+        let len = descriptor.len();
+        if let Some(scalar_type) = ScalarType::for_descriptor(&descriptor) {
+            properties.push((
+                descriptor.name.clone(),
+                scalar_type,
+                Target::ExtraBytes(offset, descriptor),
+            ));
+        }
+        offset += len;
+    }
+
+    writeln!(write, "ply")?;
+    writeln!(write, "format {} 1.0", encoding.name())?;
+    writeln!(write, "comment written by las-rs")?;
+    writeln!(write, "element vertex {}", header.number_of_points())?;
+    for (name, scalar_type, _) in &properties {
+        writeln!(write, "property {} {}", scalar_type.name(), name)?;
+    }
+    writeln!(write, "end_header")?;
+
+    let mut count = 0;
+    for point in reader.points() {
+        let point = point?;
+        for (i, (_, scalar_type, target)) in properties.iter().enumerate() {
+            let value = match target {
+                Target::Dimension(dimension) => dimension.value(&point),
+                Target::Color(dimension) => dimension.value(&point).map(|n| n / 257.),
+                Target::ExtraBytes(offset, descriptor) => point
+                    .extra_bytes
+                    .get(*offset..)
+                    .and_then(|bytes| descriptor.decode(bytes)),
+                Target::Ignore => None,
+            }
+            .unwrap_or(0.);
+            match encoding {
+                Encoding::Ascii => {
+                    if i > 0 {
+                        write!(write, " ")?;
+                    }
+                    match decimals.get(i) {
+                        Some(decimals) => write!(write, "{:.*}", decimals, value)?,
+                        None => write!(write, "{}", value)?,
+                    }
+                }
+                Encoding::BinaryLittleEndian => {
+                    scalar_type.write::<LittleEndian, _>(&mut write, value)?
+                }
+                Encoding::BinaryBigEndian => {
+                    scalar_type.write::<BigEndian, _>(&mut write, value)?
+                }
+            }
+        }
+        if encoding == Encoding::Ascii {
+            writeln!(write)?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads ply vertices and writes them to a las writer.
+///
+/// Each point is [conformed](Point::conform_to) to the writer's point format, and extra bytes
+/// properties are matched against the writer header's extra bytes fields. Returns the number of
+/// points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{ply::{self, Encoding}, Reader, Writer};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut ply = Vec::new();
+/// ply::export(&mut reader, &mut ply, Encoding::BinaryBigEndian).unwrap();
+/// let mut writer = Writer::default();
+/// assert_eq!(106, ply::import(ply.as_slice(), &mut writer).unwrap());
+/// ```
+pub fn import<R: BufRead, W: 'static + Write + Seek + Send>(
+    read: R,
+    writer: &mut Writer<W>,
+) -> Result<u64> {
+    let format = *writer.header().point_format();
+    let descriptors = writer.header().extra_bytes()?;
+    let mut count = 0;
+    for point in Reader::new(read, &descriptors)? {
+        let mut point = point?;
+        point.conform_to(&format);
+        writer.write_point(point)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Reads the ply header and skips to the vertices.
+    ///
+    /// `extra_bytes` are the extra bytes fields of the points. Properties with those names are
+    /// stored in each point's extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::Reader;
+    ///
+    /// let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty double x\nend_header\n1.5\n";
+    /// let mut reader = Reader::new(ply.as_bytes(), &[]).unwrap();
+    /// assert_eq!(1, reader.number_of_vertices());
+    /// assert_eq!(1.5, reader.next().unwrap().unwrap().x);
+    /// ```
+    pub fn new(mut read: R, extra_bytes: &[Descriptor]) -> Result<Reader<R>> {
+        let (encoding, elements) = read_header(&mut read)?;
+        let mut line = String::new();
+        let mut vertex = None;
+        for element in elements {
+            if element.name == "vertex" {
+                vertex = Some(element);
+                break;
+            }
+            for _ in 0..element.count {
+                skip_element(&mut read, encoding, &element, &mut line)?;
+            }
+        }
+        let vertex = vertex.ok_or_else(|| Error::InvalidPly("no vertex element".to_string()))?;
+        let properties = vertex
+            .properties
+            .into_iter()
+            .map(|(kind, name)| {
+                let target = if let Kind::List(..) = kind {
+                    Target::Ignore
+                } else if let Ok(dimension) = name.replace('_', "").parse() {
+                    match (dimension, kind) {
+                        (
+                            Dimension::Red | Dimension::Green | Dimension::Blue,
+                            Kind::Scalar(ScalarType::UChar),
+                        ) => Target::Color(dimension),
+                        _ => Target::Dimension(dimension),
+                    }
+                } else if let Some((offset, descriptor)) = extra_bytes::find(extra_bytes, &name) {
+                    Target::ExtraBytes(offset, descriptor.clone())
+                } else {
+                    Target::Ignore
+                };
+                (kind, target)
+            })
+            .collect();
+        Ok(Reader {
+            read,
+            encoding,
+            properties,
+            extra_bytes_len: extra_bytes.iter().map(Descriptor::len).sum(),
+            number_of_vertices: vertex.count,
+            remaining: vertex.count,
+            line,
+        })
+    }
+
+    /// Returns the number of vertices in the ply data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::Reader;
+    ///
+    /// let ply = "ply\nformat ascii 1.0\nelement vertex 0\nproperty float x\nend_header\n";
+    /// assert_eq!(0, Reader::new(ply.as_bytes(), &[]).unwrap().number_of_vertices());
+    /// ```
+    pub fn number_of_vertices(&self) -> u64 {
+        self.number_of_vertices
+    }
+
+    fn read_point(&mut self) -> Result<Point> {
+        let mut point = Point {
+            extra_bytes: vec![0; self.extra_bytes_len],
+            ..Default::default()
+        };
+        let mut values = Vec::with_capacity(self.properties.len());
+        match self.encoding {
+            Encoding::Ascii => {
+                let mut tokens = read_tokens(&mut self.read, &mut self.line)?.into_iter();
+                for (kind, _) in &self.properties {
+                    match kind {
+                        Kind::Scalar(_) => values.push(parse_token(tokens.next())?),
+                        Kind::List(..) => {
+                            let n = parse_token(tokens.next())? as usize;
+                            for _ in 0..n {
+                                let _ = parse_token(tokens.next())?;
+                            }
+                            values.push(0.);
+                        }
+                    }
+                }
+            }
+            Encoding::BinaryLittleEndian => {
+                for (kind, _) in &self.properties {
+                    values.push(kind.read::<LittleEndian, _>(&mut self.read)?);
+                }
+            }
+            Encoding::BinaryBigEndian => {
+                for (kind, _) in &self.properties {
+                    values.push(kind.read::<BigEndian, _>(&mut self.read)?);
+                }
+            }
+        }
+        for ((_, target), value) in self.properties.iter().zip(values) {
+            match target {
+                Target::Dimension(dimension) => dimension.set_value(&mut point, value)?,
+                Target::Color(dimension) => dimension.set_value(&mut point, value * 257.)?,
+                Target::ExtraBytes(offset, descriptor) => {
+                    descriptor.encode(value, &mut point.extra_bytes[*offset..])?
+                }
+                Target::Ignore => {}
+            }
+        }
+        Ok(point)
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            Some(self.read_point())
+        }
+    }
+}
+
+impl Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Ascii => "ascii",
+            Encoding::BinaryLittleEndian => "binary_little_endian",
+            Encoding::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+impl ScalarType {
+    fn parse(s: &str) -> Result<ScalarType> {
+        Ok(match s {
+            "char" | "int8" => ScalarType::Char,
+            "uchar" | "uint8" => ScalarType::UChar,
+            "short" | "int16" => ScalarType::Short,
+            "ushort" | "uint16" => ScalarType::UShort,
+            "int" | "int32" => ScalarType::Int,
+            "uint" | "uint32" => ScalarType::UInt,
+            "float" | "float32" => ScalarType::Float,
+            "double" | "float64" => ScalarType::Double,
+            _ => return Err(Error::InvalidPly(format!("unknown type: {}", s))),
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ScalarType::Char => "char",
+            ScalarType::UChar => "uchar",
+            ScalarType::Short => "short",
+            ScalarType::UShort => "ushort",
+            ScalarType::Int => "int",
+            ScalarType::UInt => "uint",
+            ScalarType::Float => "float",
+            ScalarType::Double => "double",
+        }
+    }
+
+    /// Scaled fields and fields wider than 32 bits are written as doubles.
+    fn for_descriptor(descriptor: &Descriptor) -> Option<ScalarType> {
+        if descriptor.scale.is_some() || descriptor.offset.is_some() {
+            return match descriptor.data_type {
+                DataType::Undocumented(_) | DataType::Deprecated(_) => None,
+                _ => Some(ScalarType::Double),
+            };
+        }
+        match descriptor.data_type {
+            DataType::U8 => Some(ScalarType::UChar),
+            DataType::I8 => Some(ScalarType::Char),
+            DataType::U16 => Some(ScalarType::UShort),
+            DataType::I16 => Some(ScalarType::Short),
+            DataType::U32 => Some(ScalarType::UInt),
+            DataType::I32 => Some(ScalarType::Int),
+            DataType::F32 => Some(ScalarType::Float),
+            DataType::U64 | DataType::I64 | DataType::F64 => Some(ScalarType::Double),
+            DataType::Undocumented(_) | DataType::Deprecated(_) => None,
+        }
+    }
+
+    fn read<B: byteorder::ByteOrder, R: Read>(&self, mut read: R) -> Result<f64> {
+        Ok(match self {
+            ScalarType::Char => read.read_i8()?.into(),
+            ScalarType::UChar => read.read_u8()?.into(),
+            ScalarType::Short => read.read_i16::<B>()?.into(),
+            ScalarType::UShort => read.read_u16::<B>()?.into(),
+            ScalarType::Int => read.read_i32::<B>()?.into(),
+            ScalarType::UInt => read.read_u32::<B>()?.into(),
+            ScalarType::Float => read.read_f32::<B>()?.into(),
+            ScalarType::Double => read.read_f64::<B>()?,
+        })
+    }
+
+    fn write<B: byteorder::ByteOrder, W: Write>(&self, mut write: W, n: f64) -> Result<()> {
+        match self {
+            ScalarType::Char => write.write_i8(n.round() as i8)?,
+            ScalarType::UChar => write.write_u8(n.round() as u8)?,
+            ScalarType::Short => write.write_i16::<B>(n.round() as i16)?,
+            ScalarType::UShort => write.write_u16::<B>(n.round() as u16)?,
+            ScalarType::Int => write.write_i32::<B>(n.round() as i32)?,
+            ScalarType::UInt => write.write_u32::<B>(n.round() as u32)?,
+            ScalarType::Float => write.write_f32::<B>(n as f32)?,
+            ScalarType::Double => write.write_f64::<B>(n)?,
+        }
+        Ok(())
+    }
+}
+
+impl Kind {
+    fn read<B: byteorder::ByteOrder, R: Read>(&self, mut read: R) -> Result<f64> {
+        match self {
+            Kind::Scalar(scalar_type) => scalar_type.read::<B, _>(read),
+            Kind::List(count_type, item_type) => {
+                let n = count_type.read::<B, _>(&mut read)? as usize;
+                for _ in 0..n {
+                    let _ = item_type.read::<B, _>(&mut read)?;
+                }
+                Ok(0.)
+            }
+        }
+    }
+}
+
+fn read_header<R: BufRead>(read: &mut R) -> Result<(Encoding, Vec<Element>)> {
+    let mut line = String::new();
+    let mut lines = 0;
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        line.clear();
+        if read.read_line(&mut line)? == 0 {
+            return Err(Error::InvalidPly("no end_header".to_string()));
+        }
+        lines += 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if lines == 1 {
+            if words != ["ply"] {
+                return Err(Error::InvalidPly("missing magic number".to_string()));
+            }
+            continue;
+        }
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", name, _] => {
+                encoding = Some(match *name {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::BinaryLittleEndian,
+                    "binary_big_endian" => Encoding::BinaryBigEndian,
+                    _ => return Err(Error::InvalidPly(format!("unknown format: {}", name))),
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| Error::InvalidPly(format!("invalid count: {}", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => elements
+                .last_mut()
+                .ok_or_else(|| Error::InvalidPly("property before element".to_string()))?
+                .properties
+                .push((
+                    Kind::List(
+                        ScalarType::parse(count_type)?,
+                        ScalarType::parse(item_type)?,
+                    ),
+                    name.to_string(),
+                )),
+            ["property", scalar_type, name] => elements
+                .last_mut()
+                .ok_or_else(|| Error::InvalidPly("property before element".to_string()))?
+                .properties
+                .push((
+                    Kind::Scalar(ScalarType::parse(scalar_type)?),
+                    name.to_string(),
+                )),
+            _ => {
+                return Err(Error::InvalidPly(format!(
+                    "invalid header line: {}",
+                    line.trim()
+                )))
+            }
+        }
+    }
+    let encoding = encoding.ok_or_else(|| Error::InvalidPly("no format".to_string()))?;
+    Ok((encoding, elements))
+}
+
+fn skip_element<R: BufRead>(
+    read: &mut R,
+    encoding: Encoding,
+    element: &Element,
+    line: &mut String,
+) -> Result<()> {
+    match encoding {
+        Encoding::Ascii => {
+            let _ = read_tokens(read, line)?;
+        }
+        Encoding::BinaryLittleEndian => {
+            for (kind, _) in &element.properties {
+                let _ = kind.read::<LittleEndian, _>(&mut *read)?;
+            }
+        }
+        Encoding::BinaryBigEndian => {
+            for (kind, _) in &element.properties {
+                let _ = kind.read::<BigEndian, _>(&mut *read)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_tokens<R: BufRead>(read: &mut R, line: &mut String) -> Result<Vec<String>> {
+    loop {
+        line.clear();
+        if read.read_line(line)? == 0 {
+            return Err(Error::InvalidPly("unexpected end of data".to_string()));
+        }
+        if !line.trim().is_empty() {
+            return Ok(line.split_whitespace().map(String::from).collect());
+        }
+    }
+}
+
+fn parse_token(token: Option<String>) -> Result<f64> {
+    let token = token.ok_or_else(|| Error::InvalidPly("too few values".to_string()))?;
+    token
+        .parse()
+        .map_err(|_| Error::InvalidPly(format!("not a number: {}", token)))
+}
+
+fn snake_case(name: &str) -> String {
+    let mut s = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                s.push('_');
+            }
+            s.push(c.to_ascii_lowercase());
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Reader as LasReader};
+    use std::io::Cursor;
+
+    fn roundtrip(encoding: Encoding) {
+        let mut reader = LasReader::from_path("tests/data/autzen.las").unwrap();
+        let mut ply = Vec::new();
+        let _ = export(&mut reader, &mut ply, encoding).unwrap();
+
+        let header = reader.header().clone();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        assert_eq!(106, import(ply.as_slice(), &mut writer).unwrap());
+        let mut roundtrip = LasReader::new(writer.into_inner().unwrap()).unwrap();
+        let mut original = LasReader::from_path("tests/data/autzen.las").unwrap();
+        for (expected, actual) in original.points().zip(roundtrip.points()) {
+            let expected = expected.unwrap();
+            let actual = actual.unwrap();
+            assert!((expected.x - actual.x).abs() < 1e-6);
+            assert!((expected.z - actual.z).abs() < 1e-6);
+            assert_eq!(expected.intensity, actual.intensity);
+            assert_eq!(expected.classification, actual.classification);
+            assert_eq!(expected.return_number, actual.return_number);
+            assert_eq!(expected.point_source_id, actual.point_source_id);
+            assert_eq!(expected.gps_time, actual.gps_time);
+        }
+    }
+
+    #[test]
+    fn roundtrip_ascii() {
+        roundtrip(Encoding::Ascii);
+    }
+
+    #[test]
+    fn roundtrip_binary_little_endian() {
+        roundtrip(Encoding::BinaryLittleEndian);
+    }
+
+    #[test]
+    fn roundtrip_binary_big_endian() {
+        roundtrip(Encoding::BinaryBigEndian);
+    }
+
+    #[test]
+    fn skips_other_elements() {
+        let mut ply = b"ply
+format binary_little_endian 1.0
+element face 1
+property list uchar int vertex_indices
+element vertex 1
+property float x
+property float nx
+property uchar red
+property uchar height
+end_header
+"
+        .to_vec();
+        ply.push(2);
+        ply.extend(7i32.to_le_bytes());
+        ply.extend(8i32.to_le_bytes());
+        ply.extend(1.5f32.to_le_bytes());
+        ply.extend(0.5f32.to_le_bytes());
+        ply.push(200);
+        ply.push(42);
+        let descriptors = [Descriptor::new("height", DataType::U8)];
+        let points: Vec<Point> = Reader::new(ply.as_slice(), &descriptors)
+            .unwrap()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(1, points.len());
+        assert_eq!(1.5, points[0].x);
+        assert_eq!(200 * 257, points[0].color.unwrap().red);
+        assert_eq!(vec![42], points[0].extra_bytes);
+    }
+
+    #[test]
+    fn colors() {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(2).unwrap();
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let color = crate::Color::new(65535, 257, 0);
+        writer
+            .write_point(Point {
+                color: Some(color),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut reader = LasReader::new(writer.into_inner().unwrap()).unwrap();
+        let mut ply = Vec::new();
+        let _ = export(&mut reader, &mut ply, Encoding::Ascii).unwrap();
+        let text = String::from_utf8(ply.clone()).unwrap();
+        assert!(text.contains("property uchar red\n"));
+        assert!(text.trim_end().ends_with(" 255 1 0"));
+
+        let header = reader.header().clone();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        assert_eq!(1, import(ply.as_slice(), &mut writer).unwrap());
+        let mut reader = LasReader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(Some(color), reader.read_point().unwrap().unwrap().color);
+    }
+
+    #[test]
+    fn export_rewinds() {
+        let mut reader = LasReader::from_path("tests/data/autzen.las").unwrap();
+        let _ = reader.read_point().unwrap();
+        let mut ply = Vec::new();
+        assert_eq!(106, export(&mut reader, &mut ply, Encoding::Ascii).unwrap());
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut descriptor = Descriptor::new("height", DataType::I16);
+        descriptor.scale = Some(0.1);
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = Format {
+            extra_bytes: 2,
+            ..Format::new(1).unwrap()
+        };
+        builder.vlrs.push(extra_bytes::vlr(&[descriptor]).unwrap());
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty double x\nproperty double height\nend_header\n1 -4.2\n";
+        assert_eq!(1, import(ply.as_bytes(), &mut writer).unwrap());
+
+        let mut reader = LasReader::new(writer.into_inner().unwrap()).unwrap();
+        let mut ply = Vec::new();
+        let _ = export(&mut reader, &mut ply, Encoding::Ascii).unwrap();
+        let ply = String::from_utf8(ply).unwrap();
+        assert!(ply.contains("property double height\n"));
+        assert!(ply.trim_end().ends_with(" -4.2"));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Reader::new("foo\n".as_bytes(), &[]).is_err());
+        assert!(Reader::new("ply\nformat ascii 1.0\n".as_bytes(), &[]).is_err());
+        assert!(Reader::new("ply\nformat ascii 1.0\nend_header\n".as_bytes(), &[]).is_err());
+        assert!(Reader::new(
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty quad x\nend_header\n".as_bytes(),
+            &[]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!("gps_time", snake_case("GpsTime"));
+        assert_eq!("x", snake_case("X"));
+        assert_eq!("number_of_returns", snake_case("NumberOfReturns"));
+    }
+}
@@ -29,6 +29,7 @@ use crate::{point::Error, Result};
 /// assert!(Classification::new(12).is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum Classification {
     #[default]
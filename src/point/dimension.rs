@@ -0,0 +1,277 @@
+use crate::{
+    point::{Classification, ScanDirection},
+    Color, Error, Point, Result,
+};
+use std::{fmt, str::FromStr};
+
+/// A named, numeric attribute of a [Point].
+///
+/// Dimensions can be parsed from their names, ignoring case:
+///
+/// ```
+/// use las::point::Dimension;
+///
+/// assert_eq!(Dimension::ReturnNumber, "ReturnNumber".parse().unwrap());
+/// assert_eq!(Dimension::GpsTime, "gpstime".parse().unwrap());
+/// assert!("Foo".parse::<Dimension>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Dimension {
+    X,
+    Y,
+    Z,
+    Intensity,
+    ReturnNumber,
+    NumberOfReturns,
+    ScanDirectionFlag,
+    EdgeOfFlightLine,
+    Classification,
+    Synthetic,
+    KeyPoint,
+    Withheld,
+    Overlap,
+    ScannerChannel,
+    ScanAngle,
+    UserData,
+    PointSourceId,
+    GpsTime,
+    Red,
+    Green,
+    Blue,
+    Nir,
+}
+
+impl Dimension {
+    /// All dimensions, in point record order.
+    pub const ALL: [Dimension; 22] = [
+        Dimension::X,
+        Dimension::Y,
+        Dimension::Z,
+        Dimension::Intensity,
+        Dimension::ReturnNumber,
+        Dimension::NumberOfReturns,
+        Dimension::ScanDirectionFlag,
+        Dimension::EdgeOfFlightLine,
+        Dimension::Classification,
+        Dimension::Synthetic,
+        Dimension::KeyPoint,
+        Dimension::Withheld,
+        Dimension::Overlap,
+        Dimension::ScannerChannel,
+        Dimension::ScanAngle,
+        Dimension::UserData,
+        Dimension::PointSourceId,
+        Dimension::GpsTime,
+        Dimension::Red,
+        Dimension::Green,
+        Dimension::Blue,
+        Dimension::Nir,
+    ];
+
+    /// Returns this dimension's name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Dimension;
+    /// assert_eq!("PointSourceId", Dimension::PointSourceId.name());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dimension::X => "X",
+            Dimension::Y => "Y",
+            Dimension::Z => "Z",
+            Dimension::Intensity => "Intensity",
+            Dimension::ReturnNumber => "ReturnNumber",
+            Dimension::NumberOfReturns => "NumberOfReturns",
+            Dimension::ScanDirectionFlag => "ScanDirectionFlag",
+            Dimension::EdgeOfFlightLine => "EdgeOfFlightLine",
+            Dimension::Classification => "Classification",
+            Dimension::Synthetic => "Synthetic",
+            Dimension::KeyPoint => "KeyPoint",
+            Dimension::Withheld => "Withheld",
+            Dimension::Overlap => "Overlap",
+            Dimension::ScannerChannel => "ScannerChannel",
+            Dimension::ScanAngle => "ScanAngle",
+            Dimension::UserData => "UserData",
+            Dimension::PointSourceId => "PointSourceId",
+            Dimension::GpsTime => "GpsTime",
+            Dimension::Red => "Red",
+            Dimension::Green => "Green",
+            Dimension::Blue => "Blue",
+            Dimension::Nir => "Nir",
+        }
+    }
+
+    /// Returns this dimension's value for a point.
+    ///
+    /// Flags are one or zero. Returns `None` if the point doesn't have this dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Point};
+    ///
+    /// let point = Point { z: 42., is_withheld: true, ..Default::default() };
+    /// assert_eq!(Some(42.), Dimension::Z.value(&point));
+    /// assert_eq!(Some(1.), Dimension::Withheld.value(&point));
+    /// assert_eq!(None, Dimension::GpsTime.value(&point));
+    /// ```
+    pub fn value(&self, point: &Point) -> Option<f64> {
+        let flag = |b: bool| if b { 1. } else { 0. };
+        Some(match self {
+            Dimension::X => point.x,
+            Dimension::Y => point.y,
+            Dimension::Z => point.z,
+            Dimension::Intensity => point.intensity.into(),
+            Dimension::ReturnNumber => point.return_number.into(),
+            Dimension::NumberOfReturns => point.number_of_returns.into(),
+            Dimension::ScanDirectionFlag => {
+                flag(point.scan_direction == ScanDirection::LeftToRight)
+            }
+            Dimension::EdgeOfFlightLine => flag(point.is_edge_of_flight_line),
+            Dimension::Classification => u8::from(point.classification).into(),
+            Dimension::Synthetic => flag(point.is_synthetic),
+            Dimension::KeyPoint => flag(point.is_key_point),
+            Dimension::Withheld => flag(point.is_withheld),
+            Dimension::Overlap => flag(point.is_overlap),
+            Dimension::ScannerChannel => point.scanner_channel.into(),
+            Dimension::ScanAngle => point.scan_angle.into(),
+            Dimension::UserData => point.user_data.into(),
+            Dimension::PointSourceId => point.point_source_id.into(),
+            Dimension::GpsTime => point.gps_time?,
+            Dimension::Red => point.color?.red.into(),
+            Dimension::Green => point.color?.green.into(),
+            Dimension::Blue => point.color?.blue.into(),
+            Dimension::Nir => point.nir?.into(),
+        })
+    }
+
+    /// Sets this dimension's value for a point.
+    ///
+    /// Flags are set if the value isn't zero, and integer values are rounded and saturated to
+    /// their type's range. Setting a color channel on a point without a color gives it a black
+    /// color first. Returns an error if the value is not a valid classification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, Point};
+    ///
+    /// let mut point = Point::default();
+    /// Dimension::Intensity.set_value(&mut point, 42.).unwrap();
+    /// Dimension::Red.set_value(&mut point, 255.).unwrap();
+    /// assert_eq!(42, point.intensity);
+    /// assert_eq!(255, point.color.unwrap().red);
+    /// assert!(Dimension::Classification.set_value(&mut point, 12.).is_err());
+    /// ```
+    pub fn set_value(&self, point: &mut Point, value: f64) -> Result<()> {
+        let flag = value != 0.;
+        let int = value.round();
+        match self {
+            Dimension::X => point.x = value,
+            Dimension::Y => point.y = value,
+            Dimension::Z => point.z = value,
+            Dimension::Intensity => point.intensity = int as u16,
+            Dimension::ReturnNumber => point.return_number = int as u8,
+            Dimension::NumberOfReturns => point.number_of_returns = int as u8,
+            Dimension::ScanDirectionFlag => {
+                point.scan_direction = if flag {
+                    ScanDirection::LeftToRight
+                } else {
+                    ScanDirection::RightToLeft
+                }
+            }
+            Dimension::EdgeOfFlightLine => point.is_edge_of_flight_line = flag,
+            Dimension::Classification => point.classification = Classification::new(int as u8)?,
+            Dimension::Synthetic => point.is_synthetic = flag,
+            Dimension::KeyPoint => point.is_key_point = flag,
+            Dimension::Withheld => point.is_withheld = flag,
+            Dimension::Overlap => point.is_overlap = flag,
+            Dimension::ScannerChannel => point.scanner_channel = int as u8,
+            Dimension::ScanAngle => point.scan_angle = value as f32,
+            Dimension::UserData => point.user_data = int as u8,
+            Dimension::PointSourceId => point.point_source_id = int as u16,
+            Dimension::GpsTime => point.gps_time = Some(value),
+            Dimension::Red => point.color.get_or_insert_with(Color::default).red = int as u16,
+            Dimension::Green => point.color.get_or_insert_with(Color::default).green = int as u16,
+            Dimension::Blue => point.color.get_or_insert_with(Color::default).blue = int as u16,
+            Dimension::Nir => point.nir = Some(int as u16),
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Dimension> {
+        Dimension::ALL
+            .iter()
+            .find(|dimension| dimension.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| Error::UnknownDimension(s.to_string()))
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_names() {
+        for dimension in Dimension::ALL {
+            assert_eq!(dimension, dimension.to_string().parse().unwrap());
+            assert_eq!(dimension, dimension.name().to_uppercase().parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn optional_values() {
+        let point = Point {
+            color: Some(Color::new(1, 2, 3)),
+            nir: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(Some(2.), Dimension::Green.value(&point));
+        assert_eq!(Some(4.), Dimension::Nir.value(&point));
+        assert_eq!(None, Dimension::GpsTime.value(&point));
+    }
+
+    #[test]
+    fn set_value_roundtrip() {
+        let mut point = Point::default();
+        for (i, dimension) in Dimension::ALL.into_iter().enumerate() {
+            let value = match dimension {
+                Dimension::ScanDirectionFlag
+                | Dimension::EdgeOfFlightLine
+                | Dimension::Synthetic
+                | Dimension::KeyPoint
+                | Dimension::Withheld
+                | Dimension::Overlap
+                | Dimension::ScannerChannel => 1.,
+                Dimension::X | Dimension::Y | Dimension::Z | Dimension::GpsTime => i as f64 + 0.25,
+                _ => i as f64,
+            };
+            dimension.set_value(&mut point, value).unwrap();
+            assert_eq!(Some(value), dimension.value(&point), "{}", dimension);
+        }
+    }
+
+    #[test]
+    fn set_value_rounds_and_saturates() {
+        let mut point = Point::default();
+        Dimension::Intensity.set_value(&mut point, 1.6).unwrap();
+        assert_eq!(2, point.intensity);
+        Dimension::UserData.set_value(&mut point, 300.).unwrap();
+        assert_eq!(255, point.user_data);
+    }
+}
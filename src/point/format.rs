@@ -54,6 +54,7 @@ fn point_format_id_uncompressed_to_compressed(point_format_id: u8) -> u8 {
 /// assert!(format.to_u8().is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Format {
     /// Does this point format include gps time?
     pub has_gps_time: bool,
@@ -127,6 +128,45 @@ impl Format {
         self.is_extended = true;
     }
 
+    /// Returns the smallest valid point format that can hold every attribute of both formats.
+    ///
+    /// Extra bytes are set to the larger of the two counts. Compression is taken from `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    ///
+    /// let format = Format::new(1).unwrap().union(&Format::new(2).unwrap());
+    /// assert_eq!(3, format.to_u8().unwrap());
+    ///
+    /// // There's no extended format with color and waveforms but without nir.
+    /// let format = Format::new(7).unwrap().union(&Format::new(4).unwrap());
+    /// assert_eq!(10, format.to_u8().unwrap());
+    /// ```
+    pub fn union(&self, other: &Format) -> Format {
+        let mut format = Format {
+            has_gps_time: self.has_gps_time || other.has_gps_time,
+            has_color: self.has_color || other.has_color,
+            is_extended: self.is_extended || other.is_extended,
+            has_waveform: self.has_waveform || other.has_waveform,
+            has_nir: self.has_nir || other.has_nir,
+            extra_bytes: self.extra_bytes.max(other.extra_bytes),
+            is_compressed: self.is_compressed,
+        };
+        if format.has_waveform || format.is_extended {
+            format.has_gps_time = true;
+        }
+        if format.has_nir {
+            format.is_extended = true;
+            format.has_color = true;
+        }
+        if format.is_extended && format.has_waveform && format.has_color {
+            format.has_nir = true;
+        }
+        format
+    }
+
     /// Returns this point format's length.
     ///
     /// # Examples
@@ -422,6 +462,32 @@ mod tests {
         assert_eq!(21, format.len());
     }
 
+    #[test]
+    fn union_is_valid() {
+        for a in 0..=10 {
+            for b in 0..=10 {
+                let a = Format::new(a).unwrap();
+                let b = Format::new(b).unwrap();
+                let union = a.union(&b);
+                assert!(union.to_u8().is_ok(), "{:?} {:?}", a, b);
+                assert!(union.to_u8().unwrap() >= a.to_u8().unwrap().min(b.to_u8().unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn union_extra_bytes() {
+        let a = Format {
+            extra_bytes: 2,
+            ..Default::default()
+        };
+        let b = Format {
+            extra_bytes: 4,
+            ..Default::default()
+        };
+        assert_eq!(4, a.union(&b).extra_bytes);
+    }
+
     #[test]
     fn is_compressed() {
         let format = Format {
@@ -13,15 +13,20 @@
 //! scale and offset from the header are applied.
 
 mod classification;
+mod dimension;
 mod format;
 mod scan_direction;
 
-pub use self::{classification::Classification, format::Format, scan_direction::ScanDirection};
+pub use self::{
+    classification::Classification, dimension::Dimension, format::Format,
+    scan_direction::ScanDirection,
+};
 use crate::{raw, raw::point::Waveform, Color, Error, Result, Transform, Vector};
 use thiserror::Error;
 
 /// A three dimensional point.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// The x coordinate, as a float.
     pub x: f64,
@@ -222,6 +227,48 @@ impl Point {
         }
     }
 
+    /// Adds or removes optional attributes so that this point matches the point format.
+    ///
+    /// Missing attributes are filled with their default values, extra bytes are zero-padded or
+    /// truncated, and attributes that the format doesn't have are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Format;
+    /// use las::Point;
+    ///
+    /// let format = Format::new(3).unwrap();
+    /// let mut point = Point::default();
+    /// assert!(!point.matches(&format));
+    /// point.conform_to(&format);
+    /// assert!(point.matches(&format));
+    /// assert_eq!(Some(0.), point.gps_time);
+    /// ```
+    pub fn conform_to(&mut self, format: &Format) {
+        if format.has_gps_time {
+            self.gps_time = self.gps_time.or(Some(0.));
+        } else {
+            self.gps_time = None;
+        }
+        if format.has_color {
+            self.color = self.color.or_else(|| Some(Color::default()));
+        } else {
+            self.color = None;
+        }
+        if format.has_waveform {
+            self.waveform = self.waveform.or_else(|| Some(Waveform::default()));
+        } else {
+            self.waveform = None;
+        }
+        if format.has_nir {
+            self.nir = self.nir.or(Some(0));
+        } else {
+            self.nir = None;
+        }
+        self.extra_bytes.resize(usize::from(format.extra_bytes), 0);
+    }
+
     /// Returns true if this point matches the point format.
     ///
     /// "Matches" means that the set of optional attributes is exactly the same.
@@ -285,6 +332,35 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn conform_to_drops_attributes() {
+        let mut point = Point {
+            gps_time: Some(42.),
+            color: Some(Color::new(1, 2, 3)),
+            nir: Some(4),
+            extra_bytes: vec![1, 2, 3],
+            ..Default::default()
+        };
+        let format = Format {
+            extra_bytes: 1,
+            ..Default::default()
+        };
+        point.conform_to(&format);
+        assert!(point.matches(&format));
+        assert_eq!(vec![1], point.extra_bytes);
+    }
+
+    #[test]
+    fn conform_to_keeps_values() {
+        let mut point = Point {
+            gps_time: Some(42.),
+            ..Default::default()
+        };
+        point.conform_to(&Format::new(10).unwrap());
+        assert_eq!(Some(42.), point.gps_time);
+        assert!(point.matches(&Format::new(10).unwrap()));
+    }
+
     #[test]
     fn overlap() {
         use crate::raw::point::Flags;
@@ -297,4 +373,19 @@ mod tests {
         assert_eq!(Classification::Unclassified, point.classification);
         assert!(point.is_overlap);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        let point = Point {
+            x: 1.,
+            gps_time: Some(2.),
+            color: Some(Color::new(1, 2, 3)),
+            classification: Classification::Ground,
+            extra_bytes: vec![4],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(point, serde_json::from_str(&json).unwrap());
+    }
 }
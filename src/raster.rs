@@ -0,0 +1,967 @@
+//! Bin points into raster grids, e.g. to make elevation models.
+//!
+//! A [Grid] divides the bounds of las data into square cells, with row zero at the top (north),
+//! as rasters are usually stored. A [Raster] bins one [Dimension] of the points into a grid and
+//! keeps enough per-cell statistics to produce the minimum, maximum, mean, count, or an
+//! inverse-distance weighted value of each cell:
+//!
+//! ```
+//! use las::{point::Dimension, raster::{Options, Raster, Statistic}, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let options = Options {
+//!     resolution: 100.,
+//!     dimension: Dimension::Z,
+//!     classes: vec![2], // ground only, for a DEM
+//!     ..Default::default()
+//! };
+//! let raster = Raster::from_reader(&mut reader, &options).unwrap();
+//! let dem = raster.values(Statistic::Min);
+//! assert_eq!(raster.grid().len(), dem.len());
+//! ```
+//!
+//! Rasters can be written as ESRI ASCII grids or as single-band 32-bit float GeoTIFFs. GeoTIFFs
+//! carry the GeoTIFF keys of the las data. If the las data has a WKT CRS, [Raster::write_to_path]
+//! writes it to a `.prj` file next to ASCII grids and to a GDAL `.aux.xml` file next to GeoTIFFs.
+//! Cells without a value are written as [NODATA].
+
+use crate::{
+    crs::{GeoKeys, GEO_ASCII_PARAMS_RECORD_ID, GEO_DOUBLE_PARAMS_RECORD_ID},
+    point::Dimension,
+    Bounds, Error, Point, Reader, Result,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// The value written for cells without data.
+pub const NODATA: f64 = -9999.;
+
+/// A grid of square cells, with row zero at the top.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid {
+    /// The x coordinate of the left edge of the grid.
+    pub min_x: f64,
+
+    /// The y coordinate of the top edge of the grid.
+    pub max_y: f64,
+
+    /// The width and height of each cell.
+    pub resolution: f64,
+
+    /// The number of columns.
+    pub columns: usize,
+
+    /// The number of rows.
+    pub rows: usize,
+}
+
+/// Rasterization options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// The width and height of each cell.
+    pub resolution: f64,
+
+    /// The dimension to bin.
+    pub dimension: Dimension,
+
+    /// Only bin points with these classifications, or all points if empty.
+    pub classes: Vec<u8>,
+
+    /// Points within this distance of a cell's center contribute to its inverse-distance
+    /// weighted value. Defaults to the resolution.
+    pub idw_radius: Option<f64>,
+
+    /// The power of the inverse-distance weights.
+    pub idw_power: f64,
+}
+
+/// A per-cell statistic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Statistic {
+    /// The smallest value in the cell.
+    Min,
+
+    /// The largest value in the cell.
+    Max,
+
+    /// The mean of the values in the cell.
+    Mean,
+
+    /// The number of points in the cell.
+    Count,
+
+    /// The inverse-distance weighted mean of the values near the cell's center.
+    Idw,
+}
+
+/// Per-cell statistics of one dimension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster {
+    grid: Grid,
+    options: Options,
+    cells: Vec<Cell>,
+    geo_keys: Option<GeoKeys>,
+    wkt: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cell {
+    count: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    idw_sum: f64,
+    idw_weight: f64,
+}
+
+impl Grid {
+    /// Creates a grid that covers the xy extent of the bounds.
+    ///
+    /// The grid starts at the bounds' minimum x and maximum y and has just enough cells to reach
+    /// the maximum x and minimum y. It always has at least one row and one column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::Grid, Bounds, Vector};
+    ///
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 10., y: 5., z: 0. },
+    /// };
+    /// let grid = Grid::new(&bounds, 2.).unwrap();
+    /// assert_eq!((5, 3), (grid.columns, grid.rows));
+    /// ```
+    pub fn new(bounds: &Bounds, resolution: f64) -> Result<Grid> {
+        if !resolution.is_finite() || resolution <= 0. {
+            return Err(Error::InvalidCellSize(resolution));
+        }
+        let cells = |min: f64, max: f64| {
+            if max > min {
+                (((max - min) / resolution).ceil() as usize).max(1)
+            } else {
+                1
+            }
+        };
+        let min_x = if bounds.min.x.is_finite() {
+            bounds.min.x
+        } else {
+            0.
+        };
+        let max_y = if bounds.max.y.is_finite() {
+            bounds.max.y
+        } else {
+            0.
+        };
+        Ok(Grid {
+            min_x,
+            max_y,
+            resolution,
+            columns: cells(bounds.min.x, bounds.max.x),
+            rows: cells(bounds.min.y, bounds.max.y),
+        })
+    }
+
+    /// Returns the column and row of the cell that contains these coordinates.
+    ///
+    /// Coordinates on the right or bottom edge of the grid are in the last column or row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 1., columns: 10, rows: 10 };
+    /// assert_eq!(Some((2, 0)), grid.cell(2.5, 9.5));
+    /// assert_eq!(Some((9, 9)), grid.cell(10., 0.));
+    /// assert_eq!(None, grid.cell(-1., 5.));
+    /// ```
+    pub fn cell(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let index = |offset: f64, len: usize| {
+            let n = offset / self.resolution;
+            if n >= 0. && n < len as f64 {
+                Some(n.floor() as usize)
+            } else if n == len as f64 && len > 0 {
+                Some(len - 1)
+            } else {
+                None
+            }
+        };
+        Some((
+            index(x - self.min_x, self.columns)?,
+            index(self.max_y - y, self.rows)?,
+        ))
+    }
+
+    /// Returns the index of a cell in row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 1., columns: 10, rows: 10 };
+    /// assert_eq!(12, grid.index(2, 1));
+    /// ```
+    pub fn index(&self, column: usize, row: usize) -> usize {
+        row * self.columns + column
+    }
+
+    /// Returns the coordinates of a cell's center.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 1., columns: 10, rows: 10 };
+    /// assert_eq!((0.5, 9.5), grid.center(0, 0));
+    /// ```
+    pub fn center(&self, column: usize, row: usize) -> (f64, f64) {
+        (
+            self.min_x + (column as f64 + 0.5) * self.resolution,
+            self.max_y - (row as f64 + 0.5) * self.resolution,
+        )
+    }
+
+    /// Returns the y coordinate of the bottom edge of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 2., columns: 10, rows: 3 };
+    /// assert_eq!(4., grid.min_y());
+    /// ```
+    pub fn min_y(&self) -> f64 {
+        self.max_y - self.rows as f64 * self.resolution
+    }
+
+    /// Returns the area of one cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 2., columns: 10, rows: 3 };
+    /// assert_eq!(4., grid.cell_area());
+    /// ```
+    pub fn cell_area(&self) -> f64 {
+        self.resolution * self.resolution
+    }
+
+    /// Returns the number of cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 2., columns: 10, rows: 3 };
+    /// assert_eq!(30, grid.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// Returns true if the grid has no cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raster::Grid;
+    ///
+    /// let grid = Grid { min_x: 0., max_y: 10., resolution: 2., columns: 0, rows: 0 };
+    /// assert!(grid.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Raster {
+    /// Creates an empty raster that covers the bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::Raster, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// assert_eq!(1, raster.grid().len());
+    /// ```
+    pub fn new(bounds: &Bounds, options: &Options) -> Result<Raster> {
+        let grid = Grid::new(bounds, options.resolution)?;
+        if let Some(radius) = options.idw_radius {
+            if !radius.is_finite() || radius <= 0. {
+                return Err(Error::InvalidCellSize(radius));
+            }
+        }
+        Ok(Raster {
+            grid,
+            options: options.clone(),
+            cells: vec![Cell::default(); grid.len()],
+            geo_keys: None,
+            wkt: None,
+        })
+    }
+
+    /// Bins the reader's remaining points into a raster that covers the header's bounds.
+    ///
+    /// The raster keeps the header's coordinate reference system for writing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Options, Raster, Statistic}, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = Options { resolution: 100., ..Default::default() };
+    /// let raster = Raster::from_reader(&mut reader, &options).unwrap();
+    /// let count: f64 = raster.values(Statistic::Count).into_iter().flatten().sum();
+    /// assert_eq!(106., count);
+    /// ```
+    pub fn from_reader(reader: &mut Reader, options: &Options) -> Result<Raster> {
+        let header = reader.header();
+        let mut raster = Raster::new(&header.bounds(), options)?;
+        raster.geo_keys = header.geo_keys()?;
+        raster.wkt = header.wkt()?;
+        for point in reader.points() {
+            raster.add(&point?);
+        }
+        Ok(raster)
+    }
+
+    /// Adds a point to the raster.
+    ///
+    /// Points that are outside of the grid, that don't have one of the chosen classifications,
+    /// or that don't have the chosen dimension are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Options, Raster, Statistic}, Bounds, Point, Vector};
+    ///
+    /// let bounds = Bounds { min: Vector::default(), max: Vector { x: 1., y: 1., z: 0. } };
+    /// let options = Options { resolution: 2., ..Default::default() };
+    /// let mut raster = Raster::new(&bounds, &options).unwrap();
+    /// raster.add(&Point { z: 1., ..Default::default() });
+    /// raster.add(&Point { z: 3., ..Default::default() });
+    /// assert_eq!(Some(2.), raster.value(0, 0, Statistic::Mean));
+    /// ```
+    pub fn add(&mut self, point: &Point) {
+        if !self.options.classes.is_empty()
+            && !self
+                .options
+                .classes
+                .contains(&u8::from(point.classification))
+        {
+            return;
+        }
+        let value = match self.options.dimension.value(point) {
+            Some(value) => value,
+            None => return,
+        };
+        if let Some((column, row)) = self.grid.cell(point.x, point.y) {
+            let cell = &mut self.cells[self.grid.index(column, row)];
+            cell.count += 1;
+            cell.min = cell.min.min(value);
+            cell.max = cell.max.max(value);
+            cell.sum += value;
+        }
+
+        let radius = self.options.idw_radius.unwrap_or(self.grid.resolution);
+        let range = |n: f64, origin: f64, len: usize| {
+            let first = ((n - radius - origin) / self.grid.resolution)
+                .floor()
+                .max(0.);
+            let last = ((n + radius - origin) / self.grid.resolution)
+                .floor()
+                .min(len as f64 - 1.);
+            first as usize..=last.max(-1.) as usize
+        };
+        if point.x + radius < self.grid.min_x || point.y - radius > self.grid.max_y {
+            return;
+        }
+        for column in range(point.x, self.grid.min_x, self.grid.columns) {
+            for row in range(-point.y, -self.grid.max_y, self.grid.rows) {
+                let (x, y) = self.grid.center(column, row);
+                let distance = (x - point.x).hypot(y - point.y);
+                if distance <= radius {
+                    let weight = distance.max(f64::EPSILON).powf(-self.options.idw_power);
+                    let cell = &mut self.cells[self.grid.index(column, row)];
+                    cell.idw_sum += weight * value;
+                    cell.idw_weight += weight;
+                }
+            }
+        }
+    }
+
+    /// Returns this raster's grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::Raster, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// assert_eq!(1., raster.grid().resolution);
+    /// ```
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Returns a statistic of a cell, or `None` if the cell has no points.
+    ///
+    /// The count is zero, not `None`, for cells without points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Raster, Statistic}, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// assert_eq!(None, raster.value(0, 0, Statistic::Mean));
+    /// assert_eq!(Some(0.), raster.value(0, 0, Statistic::Count));
+    /// ```
+    pub fn value(&self, column: usize, row: usize, statistic: Statistic) -> Option<f64> {
+        self.cells[self.grid.index(column, row)].value(statistic)
+    }
+
+    /// Returns a statistic of every cell, in row-major order from the top row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Raster, Statistic}, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// assert_eq!(vec![None], raster.values(Statistic::Max));
+    /// ```
+    pub fn values(&self, statistic: Statistic) -> Vec<Option<f64>> {
+        self.cells
+            .iter()
+            .map(|cell| cell.value(statistic))
+            .collect()
+    }
+
+    /// Writes a statistic as an ESRI ASCII grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Raster, Statistic}, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// let mut asc = Vec::new();
+    /// raster.write_asc(&mut asc, Statistic::Count).unwrap();
+    /// assert!(String::from_utf8(asc).unwrap().starts_with("ncols 1\n"));
+    /// ```
+    pub fn write_asc<W: Write>(&self, write: W, statistic: Statistic) -> Result<()> {
+        write_asc(write, &self.grid, &self.values(statistic))
+    }
+
+    /// Writes a statistic as a GeoTIFF with this raster's GeoTIFF keys, if it has any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Raster, Statistic}, Header};
+    ///
+    /// let raster = Raster::new(&Header::default().bounds(), &Default::default()).unwrap();
+    /// let mut tiff = Vec::new();
+    /// raster.write_geotiff(&mut tiff, Statistic::Count).unwrap();
+    /// assert_eq!(b"II*\0", &tiff[0..4]);
+    /// ```
+    pub fn write_geotiff<W: Write>(&self, write: W, statistic: Statistic) -> Result<()> {
+        write_geotiff(
+            write,
+            &self.grid,
+            &self.values(statistic),
+            self.geo_keys.as_ref(),
+        )
+    }
+
+    /// Writes a statistic to a path, as a GeoTIFF if the extension is `.tif` or `.tiff` and as an
+    /// ESRI ASCII grid otherwise.
+    ///
+    /// If the raster has WKT, ASCII grids get a `.prj` file with it and GeoTIFFs get a
+    /// `.aux.xml` file with it, which GDAL reads as the raster's CRS. ASCII grids have no way to
+    /// carry GeoTIFF keys, so a warning is logged if the raster has GeoTIFF keys but no WKT.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raster::{Options, Raster, Statistic}, Reader};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let options = Options { resolution: 100., ..Default::default() };
+    /// let raster = Raster::from_reader(&mut reader, &options).unwrap();
+    /// let path = std::env::temp_dir().join("las-raster-example.tif");
+    /// raster.write_to_path(&path, Statistic::Max).unwrap();
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P, statistic: Statistic) -> Result<()> {
+        let path = path.as_ref();
+        let is_tiff = path.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("tif") || extension.eq_ignore_ascii_case("tiff")
+        });
+        let write = BufWriter::new(File::create(path)?);
+        if is_tiff {
+            self.write_geotiff(write, statistic)?;
+            if let Some(wkt) = &self.wkt {
+                let mut aux = path.as_os_str().to_owned();
+                aux.push(".aux.xml");
+                std::fs::write(aux, aux_xml(wkt))?;
+            }
+        } else {
+            self.write_asc(write, statistic)?;
+            if let Some(wkt) = &self.wkt {
+                std::fs::write(path.with_extension("prj"), wkt)?;
+            } else if self.geo_keys.is_some() {
+                log::warn!(
+                    "the CRS is only in GeoTIFF keys, which can't be written to an ASCII grid: {}",
+                    path.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            resolution: 1.,
+            dimension: Dimension::Z,
+            classes: Vec::new(),
+            idw_radius: None,
+            idw_power: 2.,
+        }
+    }
+}
+
+impl Cell {
+    fn value(&self, statistic: Statistic) -> Option<f64> {
+        if statistic == Statistic::Count {
+            return Some(self.count as f64);
+        }
+        if statistic == Statistic::Idw {
+            return (self.idw_weight > 0.).then(|| self.idw_sum / self.idw_weight);
+        }
+        if self.count == 0 {
+            return None;
+        }
+        Some(match statistic {
+            Statistic::Min => self.min,
+            Statistic::Max => self.max,
+            _ => self.sum / self.count as f64,
+        })
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.,
+            idw_sum: 0.,
+            idw_weight: 0.,
+        }
+    }
+}
+
+/// Writes values as an ESRI ASCII grid.
+///
+/// Values are in row-major order from the top row, and missing values are written as [NODATA].
+///
+/// # Examples
+///
+/// ```
+/// use las::raster::{self, Grid};
+///
+/// let grid = Grid { min_x: 0., max_y: 2., resolution: 1., columns: 2, rows: 2 };
+/// let mut asc = Vec::new();
+/// raster::write_asc(&mut asc, &grid, &[Some(1.), None, Some(3.), Some(4.5)]).unwrap();
+/// assert!(String::from_utf8(asc).unwrap().ends_with("1 -9999\n3 4.5\n"));
+/// ```
+pub fn write_asc<W: Write>(mut write: W, grid: &Grid, values: &[Option<f64>]) -> Result<()> {
+    check_len(grid, values)?;
+    writeln!(write, "ncols {}", grid.columns)?;
+    writeln!(write, "nrows {}", grid.rows)?;
+    writeln!(write, "xllcorner {}", grid.min_x)?;
+    writeln!(write, "yllcorner {}", grid.min_y())?;
+    writeln!(write, "cellsize {}", grid.resolution)?;
+    writeln!(write, "NODATA_value {}", NODATA)?;
+    for row in values.chunks(grid.columns.max(1)) {
+        let line = row
+            .iter()
+            .map(|value| value.unwrap_or(NODATA).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(write, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Writes values as a single-band 32-bit float GeoTIFF.
+///
+/// Values are in row-major order from the top row, and missing values are written as [NODATA],
+/// which is recorded in the GDAL_NODATA tag. The GeoTIFF keys, if provided, are written as they
+/// are stored in las vlrs.
+///
+/// # Examples
+///
+/// ```
+/// use las::{crs::GeoKeys, raster::{self, Grid}};
+///
+/// let grid = Grid { min_x: 500000., max_y: 4000000., resolution: 1., columns: 2, rows: 1 };
+/// let mut tiff = Vec::new();
+/// let geo_keys = GeoKeys::from_epsg(32610).unwrap();
+/// raster::write_geotiff(&mut tiff, &grid, &[Some(1.), None], Some(&geo_keys)).unwrap();
+/// assert_eq!(b"II*\0", &tiff[0..4]);
+/// ```
+pub fn write_geotiff<W: Write>(
+    mut write: W,
+    grid: &Grid,
+    values: &[Option<f64>],
+    geo_keys: Option<&GeoKeys>,
+) -> Result<()> {
+    check_len(grid, values)?;
+    let width = u32::try_from(grid.columns)?;
+    let height = u32::try_from(grid.rows)?;
+    let image_len = u32::try_from(values.len() * 4)?;
+    let mut entries = vec![
+        TiffEntry::longs(256, &[width]),
+        TiffEntry::longs(257, &[height]),
+        TiffEntry::shorts(258, &[32]),
+        TiffEntry::shorts(259, &[1]),
+        TiffEntry::shorts(262, &[1]),
+        TiffEntry::longs(273, &[0]),
+        TiffEntry::shorts(277, &[1]),
+        TiffEntry::longs(278, &[height]),
+        TiffEntry::longs(279, &[image_len]),
+        TiffEntry::shorts(284, &[1]),
+        TiffEntry::shorts(339, &[3]),
+        TiffEntry::doubles(33550, &[grid.resolution, grid.resolution, 0.]),
+        TiffEntry::doubles(33922, &[0., 0., 0., grid.min_x, grid.max_y, 0.]),
+    ];
+    if let Some(geo_keys) = geo_keys {
+        for vlr in geo_keys.to_vlrs()? {
+            entries.push(match vlr.record_id {
+                GEO_DOUBLE_PARAMS_RECORD_ID => TiffEntry::raw(vlr.record_id, 12, vlr.data),
+                GEO_ASCII_PARAMS_RECORD_ID => TiffEntry::raw(vlr.record_id, 2, vlr.data),
+                _ => TiffEntry::raw(vlr.record_id, 3, vlr.data),
+            });
+        }
+    }
+    entries.push(TiffEntry::ascii(42113, &NODATA.to_string()));
+
+    const HEADER_LEN: u32 = 8;
+    let ifd_len = u32::try_from(2 + entries.len() * 12 + 4)?;
+    let mut data_offset = HEADER_LEN + ifd_len;
+    let mut offsets = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        if entry.data.len() > 4 {
+            offsets.push(Some(data_offset));
+            data_offset += u32::try_from(entry.data.len().next_multiple_of(2))?;
+        } else {
+            offsets.push(None);
+        }
+    }
+    let image_offset = data_offset;
+    entries[5] = TiffEntry::longs(273, &[image_offset]);
+
+    write.write_all(b"II*\0")?;
+    write.write_u32::<LittleEndian>(HEADER_LEN)?;
+    write.write_u16::<LittleEndian>(u16::try_from(entries.len())?)?;
+    for (entry, offset) in entries.iter().zip(&offsets) {
+        write.write_u16::<LittleEndian>(entry.tag)?;
+        write.write_u16::<LittleEndian>(entry.field_type)?;
+        write.write_u32::<LittleEndian>(entry.count)?;
+        match offset {
+            Some(offset) => write.write_u32::<LittleEndian>(*offset)?,
+            None => {
+                let mut value = [0; 4];
+                value[..entry.data.len()].copy_from_slice(&entry.data);
+                write.write_all(&value)?;
+            }
+        }
+    }
+    write.write_u32::<LittleEndian>(0)?;
+    for entry in entries.iter().filter(|entry| entry.data.len() > 4) {
+        write.write_all(&entry.data)?;
+        if entry.data.len() % 2 == 1 {
+            write.write_u8(0)?;
+        }
+    }
+    for value in values {
+        write.write_f32::<LittleEndian>(value.unwrap_or(NODATA) as f32)?;
+    }
+    Ok(())
+}
+
+fn aux_xml(wkt: &str) -> String {
+    let srs = wkt
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<PAMDataset>\n  <SRS>{srs}</SRS>\n</PAMDataset>\n")
+}
+
+fn check_len(grid: &Grid, values: &[Option<f64>]) -> Result<()> {
+    if values.len() == grid.len() {
+        Ok(())
+    } else {
+        Err(Error::InvalidRaster(format!(
+            "{} values for a grid of {} cells",
+            values.len(),
+            grid.len()
+        )))
+    }
+}
+
+struct TiffEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    data: Vec<u8>,
+}
+
+impl TiffEntry {
+    fn shorts(tag: u16, values: &[u16]) -> TiffEntry {
+        let data = values.iter().flat_map(|n| n.to_le_bytes()).collect();
+        TiffEntry::raw(tag, 3, data)
+    }
+
+    fn longs(tag: u16, values: &[u32]) -> TiffEntry {
+        let data = values.iter().flat_map(|n| n.to_le_bytes()).collect();
+        TiffEntry::raw(tag, 4, data)
+    }
+
+    fn doubles(tag: u16, values: &[f64]) -> TiffEntry {
+        let data = values.iter().flat_map(|n| n.to_le_bytes()).collect();
+        TiffEntry::raw(tag, 12, data)
+    }
+
+    fn ascii(tag: u16, s: &str) -> TiffEntry {
+        let mut data = s.as_bytes().to_vec();
+        data.push(0);
+        TiffEntry::raw(tag, 2, data)
+    }
+
+    fn raw(tag: u16, field_type: u16, data: Vec<u8>) -> TiffEntry {
+        let size = match field_type {
+            3 => 2,
+            4 => 4,
+            12 => 8,
+            _ => 1,
+        };
+        TiffEntry {
+            tag,
+            field_type,
+            count: (data.len() / size) as u32,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, spill::TempDir, Vector};
+
+    fn bounds(max_x: f64, max_y: f64) -> Bounds {
+        Bounds {
+            min: Vector::default(),
+            max: Vector {
+                x: max_x,
+                y: max_y,
+                z: 0.,
+            },
+        }
+    }
+
+    fn point(x: f64, y: f64, z: f64) -> Point {
+        Point {
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let options = Options {
+            resolution: 1.,
+            ..Default::default()
+        };
+        let mut raster = Raster::new(&bounds(1.5, 1.5), &options).unwrap();
+        raster.add(&point(0.2, 1.4, 1.));
+        raster.add(&point(0.8, 0.8, 3.));
+        raster.add(&point(1.5, 0.5, 10.));
+        assert_eq!((2, 2), (raster.grid().columns, raster.grid().rows));
+        assert_eq!(Some(1.), raster.value(0, 0, Statistic::Min));
+        assert_eq!(Some(3.), raster.value(0, 0, Statistic::Max));
+        assert_eq!(Some(2.), raster.value(0, 0, Statistic::Mean));
+        assert_eq!(Some(2.), raster.value(0, 0, Statistic::Count));
+        assert_eq!(Some(10.), raster.value(1, 1, Statistic::Max));
+        assert_eq!(None, raster.value(1, 0, Statistic::Max));
+        assert_eq!(Some(0.), raster.value(1, 0, Statistic::Count));
+        assert!(raster.value(1, 0, Statistic::Idw).is_some());
+    }
+
+    #[test]
+    fn idw() {
+        let options = Options {
+            resolution: 1.,
+            idw_radius: Some(2.),
+            ..Default::default()
+        };
+        let mut raster = Raster::new(&bounds(2.5, 0.5), &options).unwrap();
+        raster.add(&point(0.5, 0.5, 0.));
+        raster.add(&point(2.5, 0.5, 10.));
+        assert_eq!(Some(5.), raster.value(1, 0, Statistic::Idw));
+        assert!(raster.value(0, 0, Statistic::Idw).unwrap() < 1e-9);
+    }
+
+    #[test]
+    fn classes() {
+        let options = Options {
+            classes: vec![2],
+            ..Default::default()
+        };
+        let mut raster = Raster::new(&bounds(0., 0.), &options).unwrap();
+        raster.add(&point(0., 0., 1.));
+        raster.add(&Point {
+            classification: Classification::Ground,
+            ..point(0., 0., 5.)
+        });
+        assert_eq!(Some(5.), raster.value(0, 0, Statistic::Mean));
+    }
+
+    #[test]
+    fn geotiff_layout() {
+        let grid = Grid {
+            min_x: 10.,
+            max_y: 20.,
+            resolution: 2.,
+            columns: 3,
+            rows: 2,
+        };
+        let values = [Some(1.), Some(2.), None, Some(4.), Some(5.), Some(6.)];
+        let mut tiff = Vec::new();
+        write_geotiff(
+            &mut tiff,
+            &grid,
+            &values,
+            Some(&GeoKeys::from_epsg(32610).unwrap()),
+        )
+        .unwrap();
+        let u16_at = |i: usize| u16::from_le_bytes([tiff[i], tiff[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(tiff[i..i + 4].try_into().unwrap());
+        assert_eq!(8, u32_at(4));
+        let n = usize::from(u16_at(8));
+        let entry = |tag: u16| {
+            (0..n)
+                .map(|i| 10 + i * 12)
+                .find(|&offset| u16_at(offset) == tag)
+                .unwrap()
+        };
+        assert_eq!(3, u32_at(entry(256) + 8));
+        assert_eq!(2, u32_at(entry(257) + 8));
+        let image = u32_at(entry(273) + 8) as usize;
+        assert_eq!(tiff.len(), image + 24);
+        let pixel = |i: usize| {
+            f32::from_le_bytes(tiff[image + i * 4..image + i * 4 + 4].try_into().unwrap())
+        };
+        assert_eq!(1., pixel(0));
+        assert_eq!(-9999., pixel(2));
+        assert_eq!(6., pixel(5));
+        let tiepoint = u32_at(entry(33922) + 8) as usize;
+        let x = f64::from_le_bytes(tiff[tiepoint + 24..tiepoint + 32].try_into().unwrap());
+        assert_eq!(10., x);
+        let _ = entry(34735);
+        let tags: Vec<u16> = (0..n).map(|i| u16_at(10 + i * 12)).collect();
+        assert!(tags.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn asc() {
+        let grid = Grid {
+            min_x: 10.,
+            max_y: 20.,
+            resolution: 2.,
+            columns: 2,
+            rows: 1,
+        };
+        let mut asc = Vec::new();
+        write_asc(&mut asc, &grid, &[Some(1.5), None]).unwrap();
+        assert_eq!(
+            "ncols 2\nnrows 1\nxllcorner 10\nyllcorner 18\ncellsize 2\nNODATA_value -9999\n1.5 -9999\n",
+            String::from_utf8(asc).unwrap()
+        );
+        assert!(write_asc(Vec::new(), &grid, &[None]).is_err());
+    }
+
+    #[test]
+    fn aligned_grid() {
+        let grid = Grid::new(&bounds(1000., 1000.), 10.).unwrap();
+        assert_eq!((100, 100), (grid.columns, grid.rows));
+        assert_eq!(Some((0, 0)), grid.cell(0., 1000.));
+        assert_eq!(Some((99, 99)), grid.cell(1000., 0.));
+        assert_eq!(None, grid.cell(1000.1, 0.));
+    }
+
+    #[test]
+    fn invalid_resolution() {
+        let options = Options {
+            resolution: 0.,
+            ..Default::default()
+        };
+        assert!(Raster::new(&bounds(1., 1.), &options).is_err());
+    }
+
+    #[test]
+    fn geotiff_with_wkt() {
+        let dir = TempDir::new().unwrap();
+        let mut raster = Raster::new(&bounds(1., 1.), &Options::default()).unwrap();
+        raster.wkt = Some("PROJCS[\"A & B\"]".to_string());
+        let path = dir.path().join("raster.tif");
+        raster.write_to_path(&path, Statistic::Count).unwrap();
+        assert_eq!(
+            "<PAMDataset>\n  <SRS>PROJCS[\"A &amp; B\"]</SRS>\n</PAMDataset>\n",
+            std::fs::read_to_string(dir.path().join("raster.tif.aux.xml")).unwrap()
+        );
+        assert!(!dir.path().join("raster.prj").exists());
+    }
+
+    #[test]
+    fn asc_with_geo_keys() {
+        let dir = TempDir::new().unwrap();
+        let mut raster = Raster::new(&bounds(1., 1.), &Options::default()).unwrap();
+        raster.geo_keys = Some(GeoKeys::from_epsg(32610).unwrap());
+        let path = dir.path().join("raster.asc");
+        raster.write_to_path(&path, Statistic::Count).unwrap();
+        assert!(path.exists());
+        assert!(!dir.path().join("raster.prj").exists());
+
+        raster.wkt = Some("PROJCS[\"WGS 84 / UTM zone 10N\"]".to_string());
+        raster.write_to_path(&path, Statistic::Count).unwrap();
+        assert_eq!(
+            raster.wkt.as_deref().unwrap(),
+            std::fs::read_to_string(dir.path().join("raster.prj")).unwrap()
+        );
+    }
+}
@@ -244,6 +244,7 @@ pub struct Point {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Waveform {
     /// This value plus 99 is the Record ID of the Waveform Packet Descriptor and indicates the
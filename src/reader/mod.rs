@@ -52,8 +52,12 @@
 mod las;
 #[cfg(feature = "laz")]
 mod laz;
+mod reproject;
 
-use crate::{raw, Builder, Error, Header, Point, Result, Vlr};
+use crate::{
+    feature::Evlrs, raw, reproject::CoordinateTransform, waveform::WaveformStorage, Builder, Error,
+    Header, Point, Result, Vlr,
+};
 use std::{
     cmp::Ordering,
     fs::File,
@@ -181,6 +185,7 @@ impl Reader {
         let offset_to_point_data = u64::from(raw_header.offset_to_point_data);
         let offset_to_end_of_points = raw_header.offset_to_end_of_points();
         let evlr = raw_header.evlr;
+        let start_of_waveform_data_packet_record = raw_header.start_of_waveform_data_packet_record;
 
         let mut builder = Builder::new(raw_header)?;
 
@@ -234,9 +239,23 @@ impl Reader {
                 }
             }
             let _ = read.seek(SeekFrom::Start(evlr.start_of_first_evlr))?;
-            builder
-                .evlrs
-                .push(raw::Vlr::read_from(&mut read, true).map(Vlr::new)?);
+            for _ in 0..evlr.number_of_evlrs {
+                builder
+                    .evlrs
+                    .push(raw::Vlr::read_from(&mut read, true).map(Vlr::new)?);
+            }
+        }
+
+        // Before las 1.4, the waveform data packet record isn't counted as an evlr, so it has to
+        // be found through its own offset.
+        let mut waveform_data_packets = None;
+        if !builder.version.supports::<Evlrs>()
+            && builder.waveform_storage == Some(WaveformStorage::Internal)
+        {
+            if let Some(start) = start_of_waveform_data_packet_record.filter(|&start| start > 0) {
+                let _ = read.seek(SeekFrom::Start(start))?;
+                waveform_data_packets = Some(raw::Vlr::read_from(&mut read, true).map(Vlr::new)?);
+            }
         }
 
         let _ = read.seek(SeekFrom::Start(offset_to_point_data))?;
@@ -251,7 +270,9 @@ impl Reader {
                 builder.version = version;
             }
         }
-        let header = builder.into_header()?;
+        let mut header = builder.into_header()?;
+        header.start_of_waveform_data_packet_record = start_of_waveform_data_packet_record;
+        header.evlrs.extend(waveform_data_packets);
 
         if header.point_format().is_compressed {
             #[cfg(feature = "laz")]
@@ -381,7 +402,7 @@ impl Reader {
     /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
     /// let mut points = Vec::new();
     /// let count = reader.read_all_points(&mut points).unwrap();
-    /// assert_eq!(points.len(), count.try_into().unwrap());
+    /// assert_eq!(points.len(), usize::try_from(count).unwrap());
     /// ```
     pub fn read_all_points_into(&mut self, points: &mut Vec<Point>) -> Result<u64> {
         let point_count = self.point_reader.header().number_of_points();
@@ -427,6 +448,33 @@ impl Reader {
             point_reader: &mut *self.point_reader,
         }
     }
+
+    /// Transforms this reader's points into another coordinate reference system as they're read.
+    ///
+    /// The header's coordinate reference system vlrs, bounds, and transforms are replaced to
+    /// match, as described in [reproject::header](crate::reproject::header).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reproject::{Hemisphere, Utm, UtmToGeographic}, Reader, Writer};
+    ///
+    /// let utm = Utm::new(10, Hemisphere::North).unwrap();
+    /// let reader = Reader::new(Writer::default().into_inner().unwrap())
+    ///     .unwrap()
+    ///     .with_coordinate_transform(UtmToGeographic(utm))
+    ///     .unwrap();
+    /// assert_eq!(Some(4326), reader.header().geo_keys().unwrap().unwrap().epsg());
+    /// ```
+    pub fn with_coordinate_transform<T: CoordinateTransform + 'static>(
+        self,
+        transform: T,
+    ) -> Result<Reader> {
+        let point_reader = reproject::PointReader::new(self.point_reader, Box::new(transform))?;
+        Ok(Reader {
+            point_reader: Box::new(point_reader),
+        })
+    }
 }
 
 #[allow(deprecated)]
@@ -0,0 +1,55 @@
+use super::ReadPoints;
+use crate::{
+    reproject::{self, CoordinateTransform},
+    Header, Point, Result,
+};
+
+/// Transforms points into another coordinate reference system as they're read.
+pub(crate) struct PointReader {
+    inner: Box<dyn ReadPoints>,
+    transform: Box<dyn CoordinateTransform>,
+    header: Header,
+}
+
+impl PointReader {
+    pub(crate) fn new(
+        inner: Box<dyn ReadPoints>,
+        transform: Box<dyn CoordinateTransform>,
+    ) -> Result<PointReader> {
+        let header = reproject::header(inner.header(), transform.as_ref())?;
+        Ok(PointReader {
+            inner,
+            transform,
+            header,
+        })
+    }
+}
+
+impl ReadPoints for PointReader {
+    fn read_point(&mut self) -> Result<Option<Point>> {
+        match self.inner.read_point()? {
+            Some(mut point) => {
+                reproject::transform_point(&mut point, self.transform.as_ref())?;
+                Ok(Some(point))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn read_points(&mut self, n: u64, points: &mut Vec<Point>) -> Result<u64> {
+        let start = points.len();
+        let count = self.inner.read_points(n, points)?;
+        for point in &mut points[start..] {
+            reproject::transform_point(point, self.transform.as_ref())?;
+        }
+        Ok(count)
+    }
+
+    fn seek(&mut self, index: u64) -> Result<()> {
+        self.inner.seek(index)
+    }
+
+    fn header(&self) -> &Header {
+        &self.header
+    }
+}
@@ -0,0 +1,670 @@
+//! Reproject points between coordinate reference systems.
+//!
+//! A [CoordinateTransform] moves x, y, and z from one coordinate reference system to another.
+//! Attach one to a [Reader](crate::Reader) with
+//! [Reader::with_coordinate_transform](crate::Reader::with_coordinate_transform) to transform
+//! points as they're read, or to a [Writer](crate::Writer) with
+//! [Writer::with_coordinate_transform](crate::Writer::with_coordinate_transform) to transform
+//! points before they're written. Either way, the header's coordinate reference system vlrs,
+//! bounds, and transforms are updated to match the target coordinate reference system.
+//!
+//! This crate includes pure-Rust transforms between WGS 84 UTM zones and WGS 84 geographic
+//! coordinates. Implement [CoordinateTransform] yourself, e.g. on top of PROJ, for anything else.
+//!
+//! ```
+//! use las::{
+//!     crs::GeoKeys,
+//!     reproject::{GeographicToUtm, Hemisphere, Utm},
+//!     Builder, Point, Writer,
+//! };
+//! use std::io::Cursor;
+//!
+//! let mut builder = Builder::default();
+//! builder.vlrs.extend(GeoKeys::from_epsg(4326).unwrap().to_vlrs().unwrap());
+//! let utm = Utm::new(10, Hemisphere::North).unwrap();
+//! let mut writer = Writer::with_coordinate_transform(
+//!     Cursor::new(Vec::new()),
+//!     builder.into_header().unwrap(),
+//!     GeographicToUtm(utm),
+//! )
+//! .unwrap();
+//! writer.write_point(Point { x: -123., y: 0., ..Default::default() }).unwrap();
+//! writer.close().unwrap();
+//! let header = writer.header();
+//! assert_eq!(Some(32610), header.geo_keys().unwrap().unwrap().epsg());
+//! assert!((header.bounds().min.x - 500_000.).abs() < 1e-3);
+//! ```
+
+use crate::{
+    crs::{self, GeoKeys},
+    Bounds, Builder, Error, Header, Point, Result, TransformPolicy, Vector,
+};
+
+/// Transforms coordinates from one coordinate reference system to another.
+pub trait CoordinateTransform: Send {
+    /// Transforms a single coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reproject::{CoordinateTransform, Hemisphere, Utm, UtmToGeographic}, Vector};
+    ///
+    /// let transform = UtmToGeographic(Utm::new(10, Hemisphere::North).unwrap());
+    /// let geographic = transform.transform(Vector { x: 500_000., y: 0., z: 0. }).unwrap();
+    /// assert!((geographic.x + 123.).abs() < 1e-9);
+    /// ```
+    fn transform(&self, xyz: Vector<f64>) -> Result<Vector<f64>>;
+
+    /// Returns the precision of coordinates in the target coordinate reference system, used to
+    /// choose the output header's transforms.
+    fn precision(&self) -> Vector<f64>;
+
+    /// Returns the GeoTIFF keys of the target coordinate reference system, if known.
+    ///
+    /// These are written for point formats 0 through 5.
+    fn geo_keys(&self) -> Option<GeoKeys> {
+        None
+    }
+
+    /// Returns the OGC well-known text of the target coordinate reference system, if known.
+    ///
+    /// This is written for point formats 6 through 10, which can't use GeoTIFF keys.
+    fn wkt(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A hemisphere, which decides the false northing of a UTM zone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hemisphere {
+    /// The northern hemisphere, with a false northing of zero.
+    North,
+
+    /// The southern hemisphere, with a false northing of 10,000,000 meters.
+    South,
+}
+
+/// A WGS 84 Universal Transverse Mercator zone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Utm {
+    zone: u8,
+    hemisphere: Hemisphere,
+}
+
+/// Transforms WGS 84 UTM coordinates, in meters, to WGS 84 longitude and latitude, in degrees.
+///
+/// Heights are ellipsoidal in both systems, so z is unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UtmToGeographic(pub Utm);
+
+/// Transforms WGS 84 longitude and latitude, in degrees, to WGS 84 UTM coordinates, in meters.
+///
+/// Heights are ellipsoidal in both systems, so z is unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeographicToUtm(pub Utm);
+
+const SEMI_MAJOR_AXIS: f64 = 6_378_137.;
+const FLATTENING: f64 = 1. / 298.257_223_563;
+const SCALE_FACTOR: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.;
+const FALSE_NORTHING_SOUTH: f64 = 10_000_000.;
+const WGS84_EPSG: u16 = 4326;
+const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]],PRIMEM["Greenwich",0,AUTHORITY["EPSG","8901"]],UNIT["degree",0.0174532925199433,AUTHORITY["EPSG","9122"]],AUTHORITY["EPSG","4326"]]"#;
+
+impl Utm {
+    /// Creates a new UTM zone.
+    ///
+    /// Returns an error if the zone isn't between 1 and 60.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    ///
+    /// let utm = Utm::new(10, Hemisphere::North).unwrap();
+    /// assert!(Utm::new(61, Hemisphere::North).is_err());
+    /// ```
+    pub fn new(zone: u8, hemisphere: Hemisphere) -> Result<Utm> {
+        if (1..=60).contains(&zone) {
+            Ok(Utm { zone, hemisphere })
+        } else {
+            Err(Error::InvalidUtmZone(zone))
+        }
+    }
+
+    /// Returns the UTM zone for a WGS 84 UTM EPSG code, e.g. 32610 or 32755.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    ///
+    /// assert_eq!(Utm::new(55, Hemisphere::South).ok(), Utm::from_epsg(32755));
+    /// assert!(Utm::from_epsg(4326).is_none());
+    /// ```
+    pub fn from_epsg(code: u16) -> Option<Utm> {
+        let (hemisphere, zone) = match code {
+            32601..=32660 => (Hemisphere::North, code - 32600),
+            32701..=32760 => (Hemisphere::South, code - 32700),
+            _ => return None,
+        };
+        Utm::new(u8::try_from(zone).ok()?, hemisphere).ok()
+    }
+
+    /// Returns this zone's number, between 1 and 60.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    /// assert_eq!(10, Utm::new(10, Hemisphere::North).unwrap().zone());
+    /// ```
+    pub fn zone(&self) -> u8 {
+        self.zone
+    }
+
+    /// Returns this zone's hemisphere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    /// let utm = Utm::new(10, Hemisphere::North).unwrap();
+    /// assert_eq!(Hemisphere::North, utm.hemisphere());
+    /// ```
+    pub fn hemisphere(&self) -> Hemisphere {
+        self.hemisphere
+    }
+
+    /// Returns this zone's EPSG code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    /// assert_eq!(32610, Utm::new(10, Hemisphere::North).unwrap().epsg());
+    /// ```
+    pub fn epsg(&self) -> u16 {
+        match self.hemisphere {
+            Hemisphere::North => 32600 + u16::from(self.zone),
+            Hemisphere::South => 32700 + u16::from(self.zone),
+        }
+    }
+
+    /// Returns this zone's central meridian, in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    /// assert_eq!(-123., Utm::new(10, Hemisphere::North).unwrap().central_meridian());
+    /// ```
+    pub fn central_meridian(&self) -> f64 {
+        f64::from(self.zone) * 6. - 183.
+    }
+
+    /// Returns this zone's OGC well-known text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::reproject::{Hemisphere, Utm};
+    /// let wkt = Utm::new(10, Hemisphere::North).unwrap().wkt();
+    /// assert!(wkt.starts_with("PROJCS[\"WGS 84 / UTM zone 10N\""));
+    /// ```
+    pub fn wkt(&self) -> String {
+        let (letter, false_northing) = match self.hemisphere {
+            Hemisphere::North => ('N', 0.),
+            Hemisphere::South => ('S', FALSE_NORTHING_SOUTH),
+        };
+        format!(
+            r#"PROJCS["WGS 84 / UTM zone {}{}",{},PROJECTION["Transverse_Mercator"],PARAMETER["latitude_of_origin",0],PARAMETER["central_meridian",{}],PARAMETER["scale_factor",{}],PARAMETER["false_easting",{}],PARAMETER["false_northing",{}],UNIT["metre",1,AUTHORITY["EPSG","9001"]],AXIS["Easting",EAST],AXIS["Northing",NORTH],AUTHORITY["EPSG","{}"]]"#,
+            self.zone,
+            letter,
+            WGS84_WKT,
+            self.central_meridian(),
+            SCALE_FACTOR,
+            FALSE_EASTING,
+            false_northing,
+            self.epsg()
+        )
+    }
+
+    fn false_northing(&self) -> f64 {
+        match self.hemisphere {
+            Hemisphere::North => 0.,
+            Hemisphere::South => FALSE_NORTHING_SOUTH,
+        }
+    }
+}
+
+impl CoordinateTransform for UtmToGeographic {
+    fn transform(&self, xyz: Vector<f64>) -> Result<Vector<f64>> {
+        let utm = &self.0;
+        let series = Series::new();
+        let xi = (xyz.y - utm.false_northing()) / (SCALE_FACTOR * series.a);
+        let eta = (xyz.x - FALSE_EASTING) / (SCALE_FACTOR * series.a);
+        let mut xi_prime = xi;
+        let mut eta_prime = eta;
+        for (j, beta) in (1..).zip(series.beta) {
+            let j = f64::from(j) * 2.;
+            xi_prime -= beta * (j * xi).sin() * (j * eta).cosh();
+            eta_prime -= beta * (j * xi).cos() * (j * eta).sinh();
+        }
+        let tau_prime = xi_prime.sin() / eta_prime.sinh().hypot(xi_prime.cos());
+        let latitude = geodetic_tangent(tau_prime, series.e).atan();
+        let longitude =
+            utm.central_meridian().to_radians() + eta_prime.sinh().atan2(xi_prime.cos());
+        finite(Vector {
+            x: longitude.to_degrees(),
+            y: latitude.to_degrees(),
+            z: xyz.z,
+        })
+    }
+
+    fn precision(&self) -> Vector<f64> {
+        Vector {
+            x: 1e-7,
+            y: 1e-7,
+            z: 0.001,
+        }
+    }
+
+    fn geo_keys(&self) -> Option<GeoKeys> {
+        GeoKeys::from_epsg(WGS84_EPSG).ok()
+    }
+
+    fn wkt(&self) -> Option<String> {
+        Some(WGS84_WKT.to_string())
+    }
+}
+
+impl CoordinateTransform for GeographicToUtm {
+    fn transform(&self, xyz: Vector<f64>) -> Result<Vector<f64>> {
+        if !(-90. ..=90.).contains(&xyz.y) {
+            return Err(Error::CoordinateTransform(format!(
+                "latitude is out of range: {}",
+                xyz.y
+            )));
+        }
+        let utm = &self.0;
+        let series = Series::new();
+        let latitude = xyz.y.to_radians();
+        let longitude = (xyz.x - utm.central_meridian()).to_radians();
+        let e = series.e;
+        let t = (latitude.sin().atanh() - e * (e * latitude.sin()).atanh()).sinh();
+        let xi_prime = t.atan2(longitude.cos());
+        let eta_prime = (longitude.sin() / (1. + t * t).sqrt()).atanh();
+        let mut xi = xi_prime;
+        let mut eta = eta_prime;
+        for (j, alpha) in (1..).zip(series.alpha) {
+            let j = f64::from(j) * 2.;
+            xi += alpha * (j * xi_prime).sin() * (j * eta_prime).cosh();
+            eta += alpha * (j * xi_prime).cos() * (j * eta_prime).sinh();
+        }
+        finite(Vector {
+            x: FALSE_EASTING + SCALE_FACTOR * series.a * eta,
+            y: utm.false_northing() + SCALE_FACTOR * series.a * xi,
+            z: xyz.z,
+        })
+    }
+
+    fn precision(&self) -> Vector<f64> {
+        Vector {
+            x: 0.001,
+            y: 0.001,
+            z: 0.001,
+        }
+    }
+
+    fn geo_keys(&self) -> Option<GeoKeys> {
+        GeoKeys::from_epsg(self.0.epsg()).ok()
+    }
+
+    fn wkt(&self) -> Option<String> {
+        Some(self.0.wkt())
+    }
+}
+
+/// The eccentricity, rectifying radius, and coefficients of Krüger's series, to third order in
+/// the third flattening.
+struct Series {
+    e: f64,
+    a: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+}
+
+impl Series {
+    fn new() -> Series {
+        let n = FLATTENING / (2. - FLATTENING);
+        let n2 = n * n;
+        let n3 = n2 * n;
+        Series {
+            e: 2. * n.sqrt() / (1. + n),
+            a: SEMI_MAJOR_AXIS / (1. + n) * (1. + n2 / 4. + n2 * n2 / 64.),
+            alpha: [
+                n / 2. - 2. * n2 / 3. + 5. * n3 / 16.,
+                13. * n2 / 48. - 3. * n3 / 5.,
+                61. * n3 / 240.,
+            ],
+            beta: [
+                n / 2. - 2. * n2 / 3. + 37. * n3 / 96.,
+                n2 / 48. + n3 / 15.,
+                17. * n3 / 480.,
+            ],
+        }
+    }
+}
+
+/// Returns a copy of this header in the transform's target coordinate reference system.
+///
+/// The coordinate reference system vlrs and evlrs are replaced with the transform's WKT, for
+/// point formats 6 through 10, or GeoTIFF keys, for the other formats. The bounds are transformed
+/// by sampling points along their edges. If the bounds aren't empty, the transforms are fit to
+/// them at the transform's [precision](CoordinateTransform::precision), otherwise the transforms
+/// are left as they are and should be chosen when the points are written, e.g. with
+/// [Writer::with_transform_policy](crate::Writer::with_transform_policy).
+///
+/// # Examples
+///
+/// ```
+/// use las::{reproject::{self, Hemisphere, Utm, UtmToGeographic}, Builder};
+///
+/// let utm = Utm::new(10, Hemisphere::North).unwrap();
+/// let header = reproject::header(&Builder::default().into_header().unwrap(), &UtmToGeographic(utm))
+///     .unwrap();
+/// assert_eq!(Some(4326), header.geo_keys().unwrap().unwrap().epsg());
+/// ```
+pub fn header(header: &Header, transform: &dyn CoordinateTransform) -> Result<Header> {
+    let mut builder = Builder::from(header.clone());
+    builder.vlrs.retain(|vlr| vlr.user_id != crs::USER_ID);
+    builder.evlrs.retain(|vlr| vlr.user_id != crs::USER_ID);
+    builder.has_wkt_crs = false;
+    if builder.point_format.is_extended {
+        if let Some(wkt) = transform.wkt() {
+            builder.set_wkt(&wkt);
+        }
+    } else if let Some(geo_keys) = transform.geo_keys() {
+        builder.vlrs.extend(geo_keys.to_vlrs()?);
+    }
+    let bounds = header.bounds();
+    if !is_empty(&bounds) {
+        builder.bounds = transform_bounds(&bounds, transform)?;
+        builder.transforms = builder.bounds.fit_transforms(&transform.precision())?;
+    }
+    builder.into_header()
+}
+
+/// Returns the policy a writer should use for the reprojected header.
+///
+/// If the header has no bounds, the transforms can only be fit to the written points.
+pub(crate) fn transform_policy(
+    header: &Header,
+    transform: &dyn CoordinateTransform,
+) -> TransformPolicy {
+    if is_empty(&header.bounds()) {
+        TransformPolicy::Auto {
+            precision: transform.precision(),
+        }
+    } else {
+        TransformPolicy::Fixed
+    }
+}
+
+/// Transforms a point's coordinates in place.
+pub(crate) fn transform_point(
+    point: &mut Point,
+    transform: &dyn CoordinateTransform,
+) -> Result<()> {
+    let xyz = transform.transform(Vector {
+        x: point.x,
+        y: point.y,
+        z: point.z,
+    })?;
+    point.x = xyz.x;
+    point.y = xyz.y;
+    point.z = xyz.z;
+    Ok(())
+}
+
+/// Returns the tangent of the geodetic latitude for the tangent of the conformal latitude, using
+/// Newton's method.
+fn geodetic_tangent(tau_prime: f64, e: f64) -> f64 {
+    let e2 = e * e;
+    let conformal = |tau: f64| {
+        let sigma = (e * (e * tau / (1. + tau * tau).sqrt()).atanh()).sinh();
+        tau * (1. + sigma * sigma).sqrt() - sigma * (1. + tau * tau).sqrt()
+    };
+    let mut tau = tau_prime;
+    for _ in 0..5 {
+        let tau_i_prime = conformal(tau);
+        let delta = (tau_prime - tau_i_prime) / (1. + tau_i_prime * tau_i_prime).sqrt()
+            * (1. + (1. - e2) * tau * tau)
+            / ((1. - e2) * (1. + tau * tau).sqrt());
+        tau += delta;
+        if delta.abs() <= f64::EPSILON * tau.abs().max(1.) {
+            break;
+        }
+    }
+    tau
+}
+
+fn transform_bounds(bounds: &Bounds, transform: &dyn CoordinateTransform) -> Result<Bounds> {
+    const STEPS: u32 = 10;
+    let lerp = |min: f64, max: f64, i: u32| min + (max - min) * f64::from(i) / f64::from(STEPS);
+    let mut transformed = Bounds::default();
+    for z in [bounds.min.z, bounds.max.z] {
+        for i in 0..=STEPS {
+            let x = lerp(bounds.min.x, bounds.max.x, i);
+            let y = lerp(bounds.min.y, bounds.max.y, i);
+            for xyz in [
+                Vector {
+                    x,
+                    y: bounds.min.y,
+                    z,
+                },
+                Vector {
+                    x,
+                    y: bounds.max.y,
+                    z,
+                },
+                Vector {
+                    x: bounds.min.x,
+                    y,
+                    z,
+                },
+                Vector {
+                    x: bounds.max.x,
+                    y,
+                    z,
+                },
+            ] {
+                let xyz = transform.transform(xyz)?;
+                transformed.grow(&Point {
+                    x: xyz.x,
+                    y: xyz.y,
+                    z: xyz.z,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    Ok(transformed)
+}
+
+fn is_empty(bounds: &Bounds) -> bool {
+    !(bounds.min.x.is_finite() && bounds.max.x.is_finite()) || bounds.min.x > bounds.max.x
+}
+
+fn finite(xyz: Vector<f64>) -> Result<Vector<f64>> {
+    if xyz.x.is_finite() && xyz.y.is_finite() && xyz.z.is_finite() {
+        Ok(xyz)
+    } else {
+        Err(Error::CoordinateTransform(format!(
+            "result is not finite: ({}, {}, {})",
+            xyz.x, xyz.y, xyz.z
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Transform, Writer};
+    use std::io::Cursor;
+
+    fn zone_10n() -> Utm {
+        Utm::new(10, Hemisphere::North).unwrap()
+    }
+
+    fn utm_reader() -> Reader {
+        let mut builder = Builder::default();
+        builder.transforms = Vector {
+            x: Transform {
+                scale: 0.01,
+                offset: 500_000.,
+            },
+            y: Transform {
+                scale: 0.01,
+                offset: 5_000_000.,
+            },
+            z: Transform {
+                scale: 0.01,
+                offset: 0.,
+            },
+        };
+        builder
+            .vlrs
+            .extend(GeoKeys::from_epsg(32610).unwrap().to_vlrs().unwrap());
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        for i in 0..10 {
+            writer
+                .write_point(Point {
+                    x: 500_000. + f64::from(i) * 100.,
+                    y: 4_982_950.4 + f64::from(i) * 100.,
+                    z: 100.,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        Reader::new(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn utm_to_geographic() {
+        let transform = UtmToGeographic(zone_10n());
+        let xyz = transform
+            .transform(Vector {
+                x: 500_000.,
+                y: 4_982_950.4,
+                z: 1.,
+            })
+            .unwrap();
+        assert!((xyz.x + 123.).abs() < 1e-9);
+        assert!((xyz.y - 45.).abs() < 1e-7);
+        assert_eq!(1., xyz.z);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for (zone, hemisphere, longitude, latitude) in [
+            (10, Hemisphere::North, -121.3, 44.05),
+            (55, Hemisphere::South, 148.9, -35.3),
+            (31, Hemisphere::North, 0.1, 0.),
+        ] {
+            let utm = Utm::new(zone, hemisphere).unwrap();
+            let xyz = Vector {
+                x: longitude,
+                y: latitude,
+                z: 10.,
+            };
+            let projected = GeographicToUtm(utm).transform(xyz).unwrap();
+            let geographic = UtmToGeographic(utm).transform(projected).unwrap();
+            assert!((geographic.x - longitude).abs() < 1e-9);
+            assert!(
+                (geographic.y - latitude).abs() < 1e-9,
+                "{} {}",
+                geographic.y,
+                latitude
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_latitude() {
+        let transform = GeographicToUtm(zone_10n());
+        assert!(transform
+            .transform(Vector {
+                x: -123.,
+                y: 91.,
+                z: 0.
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn reader() {
+        let mut reader = utm_reader()
+            .with_coordinate_transform(UtmToGeographic(zone_10n()))
+            .unwrap();
+        let header = reader.header();
+        assert_eq!(Some(4326), header.geo_keys().unwrap().unwrap().epsg());
+        assert_eq!(1e-7, header.transforms().x.scale);
+        assert!((header.bounds().min.x + 123.).abs() < 1e-6);
+        assert!((header.bounds().min.y - 45.).abs() < 1e-6);
+        let points = reader.read_points(10).unwrap();
+        assert!((points[0].x + 123.).abs() < 1e-6);
+        assert!((points[0].y - 45.).abs() < 1e-6);
+        for point in points {
+            let bounds = reader.header().bounds();
+            assert!(point.x >= bounds.min.x && point.x <= bounds.max.x);
+            assert!(point.y >= bounds.min.y && point.y <= bounds.max.y);
+        }
+    }
+
+    #[test]
+    fn writer() {
+        let mut reader = utm_reader();
+        let header = reader.header().clone();
+        let mut writer = Writer::with_coordinate_transform(
+            Cursor::new(Vec::new()),
+            header,
+            UtmToGeographic(zone_10n()),
+        )
+        .unwrap();
+        for point in reader.points() {
+            writer.write_point(point.unwrap()).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap())
+            .unwrap()
+            .with_coordinate_transform(GeographicToUtm(zone_10n()))
+            .unwrap();
+        assert_eq!(
+            Some(32610),
+            reader.header().geo_keys().unwrap().unwrap().epsg()
+        );
+        let point = reader.read_point().unwrap().unwrap();
+        assert!((point.x - 500_000.).abs() < 0.01);
+        assert!((point.y - 4_982_950.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn extended_format_uses_wkt() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format = crate::point::Format::new(6).unwrap();
+        builder.set_wkt(&zone_10n().wkt());
+        let header = header(
+            &builder.into_header().unwrap(),
+            &UtmToGeographic(zone_10n()),
+        )
+        .unwrap();
+        assert!(header.geo_keys().unwrap().is_none());
+        assert!(header
+            .wkt()
+            .unwrap()
+            .unwrap()
+            .starts_with("GEOGCS[\"WGS 84\""));
+    }
+}
@@ -0,0 +1,284 @@
+//! Move point data onto a new set of scales and offsets.
+//!
+//! [Point::into_raw](crate::Point::into_raw) fails with
+//! [Error::InvalidInverseTransform](crate::Error::InvalidInverseTransform) if a coordinate can't be
+//! represented with the header's [Transform]s, which is common when merging data from different
+//! sources. Use [header] to choose new transforms for the data's bounds at a given precision, then
+//! [rescale] to write the points through those transforms while keeping track of how much
+//! precision was lost:
+//!
+//! ```
+//! use las::{rescale, Reader, Vector, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let precision = Vector { x: 0.1, y: 0.1, z: 0.1 };
+//! let header = rescale::header(reader.header(), &precision).unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! let loss = rescale::rescale(&mut reader, &mut writer).unwrap();
+//! assert!(loss.max.x <= 0.05 + 1e-9);
+//! ```
+
+use crate::{Builder, Header, Point, Reader, Result, Transform, Vector, Writer};
+use std::io::{Seek, Write};
+
+/// Requantizes points onto a set of transforms, accumulating [PrecisionLoss] as it goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rescaler {
+    transforms: Vector<Transform>,
+    precision_loss: PrecisionLoss,
+}
+
+/// How far points moved when they were requantized.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrecisionLoss {
+    /// The number of points that have been rescaled.
+    pub number_of_points: u64,
+
+    /// The largest absolute change in each dimension.
+    pub max: Vector<f64>,
+
+    /// The sum of the absolute changes in each dimension.
+    pub sum: Vector<f64>,
+}
+
+impl Rescaler {
+    /// Creates a new rescaler for the given transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::rescale::Rescaler;
+    /// let rescaler = Rescaler::new(Default::default());
+    /// ```
+    pub fn new(transforms: Vector<Transform>) -> Rescaler {
+        Rescaler {
+            transforms,
+            precision_loss: PrecisionLoss::default(),
+        }
+    }
+
+    /// Returns this rescaler's transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::rescale::Rescaler;
+    /// let rescaler = Rescaler::new(Default::default());
+    /// assert_eq!(0.001, rescaler.transforms().x.scale);
+    /// ```
+    pub fn transforms(&self) -> &Vector<Transform> {
+        &self.transforms
+    }
+
+    /// Moves a point's coordinates onto the nearest values representable by the transforms.
+    ///
+    /// Returns an error, and leaves the point untouched, if a coordinate can't be represented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{rescale::Rescaler, Point, Transform, Vector};
+    ///
+    /// let transform = Transform { scale: 0.1, offset: 0. };
+    /// let mut rescaler = Rescaler::new(Vector { x: transform, y: transform, z: transform });
+    /// let mut point = Point { x: 1.04, ..Default::default() };
+    /// rescaler.rescale(&mut point).unwrap();
+    /// assert_eq!(1., point.x);
+    /// assert!((rescaler.precision_loss().max.x - 0.04).abs() < 1e-9);
+    /// ```
+    pub fn rescale(&mut self, point: &mut Point) -> Result<()> {
+        let x = requantize(point.x, &self.transforms.x)?;
+        let y = requantize(point.y, &self.transforms.y)?;
+        let z = requantize(point.z, &self.transforms.z)?;
+        self.precision_loss.add(&Vector {
+            x: (x - point.x).abs(),
+            y: (y - point.y).abs(),
+            z: (z - point.z).abs(),
+        });
+        point.x = x;
+        point.y = y;
+        point.z = z;
+        Ok(())
+    }
+
+    /// Returns the precision lost by all points rescaled so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::rescale::Rescaler;
+    /// let rescaler = Rescaler::new(Default::default());
+    /// assert_eq!(0, rescaler.precision_loss().number_of_points);
+    /// ```
+    pub fn precision_loss(&self) -> &PrecisionLoss {
+        &self.precision_loss
+    }
+}
+
+impl PrecisionLoss {
+    /// Returns the mean absolute change in each dimension.
+    ///
+    /// Returns zeros if no points have been rescaled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{rescale::PrecisionLoss, Vector};
+    /// let loss = PrecisionLoss {
+    ///     number_of_points: 2,
+    ///     sum: Vector { x: 1., y: 2., z: 3. },
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(Vector { x: 0.5, y: 1., z: 1.5 }, loss.mean());
+    /// ```
+    pub fn mean(&self) -> Vector<f64> {
+        if self.number_of_points == 0 {
+            Vector::default()
+        } else {
+            let n = self.number_of_points as f64;
+            Vector {
+                x: self.sum.x / n,
+                y: self.sum.y / n,
+                z: self.sum.z / n,
+            }
+        }
+    }
+
+    fn add(&mut self, delta: &Vector<f64>) {
+        self.number_of_points += 1;
+        self.max.x = self.max.x.max(delta.x);
+        self.max.y = self.max.y.max(delta.y);
+        self.max.z = self.max.z.max(delta.z);
+        self.sum.x += delta.x;
+        self.sum.y += delta.y;
+        self.sum.z += delta.z;
+    }
+}
+
+/// Returns a copy of this header with transforms fit to its bounds at the given precision.
+///
+/// See [Bounds::fit_transforms](crate::Bounds::fit_transforms) for how the transforms are chosen.
+///
+/// # Examples
+///
+/// ```
+/// use las::{rescale, Reader, Vector};
+///
+/// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let precision = Vector { x: 0.01, y: 0.01, z: 0.01 };
+/// let header = rescale::header(reader.header(), &precision).unwrap();
+/// assert_eq!(0.01, header.transforms().x.scale);
+/// ```
+pub fn header(header: &Header, precision: &Vector<f64>) -> Result<Header> {
+    let mut builder = Builder::from(header.clone());
+    builder.transforms = header.bounds().fit_transforms(precision)?;
+    builder.into_header()
+}
+
+/// Writes all of the reader's remaining points through the writer's transforms.
+///
+/// Returns the precision lost while moving the points onto the writer's transforms.
+///
+/// # Examples
+///
+/// ```
+/// use las::{rescale, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let header = reader.header().clone();
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+/// let loss = rescale::rescale(&mut reader, &mut writer).unwrap();
+/// assert_eq!(0., loss.max.x);
+/// ```
+pub fn rescale<W: 'static + Write + Seek + Send>(
+    reader: &mut Reader,
+    writer: &mut Writer<W>,
+) -> Result<PrecisionLoss> {
+    let mut rescaler = Rescaler::new(*writer.header().transforms());
+    for point in reader.points() {
+        let mut point = point?;
+        rescaler.rescale(&mut point)?;
+        writer.write_point(point)?;
+    }
+    Ok(rescaler.precision_loss)
+}
+
+fn requantize(n: f64, transform: &Transform) -> Result<f64> {
+    transform.inverse(n).map(|i| transform.direct(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transforms(scale: f64, offset: f64) -> Vector<Transform> {
+        let transform = Transform { scale, offset };
+        Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        }
+    }
+
+    #[test]
+    fn rescale_out_of_range() {
+        let mut rescaler = Rescaler::new(transforms(0.001, 0.));
+        let mut point = Point {
+            x: 4_000_000.,
+            ..Default::default()
+        };
+        assert!(rescaler.rescale(&mut point).is_err());
+        assert_eq!(4_000_000., point.x);
+        assert_eq!(0, rescaler.precision_loss().number_of_points);
+    }
+
+    #[test]
+    fn header_fits_large_coordinates() {
+        let mut header = Header::default();
+        let point = Point {
+            x: 4_000_000.,
+            y: 5_000_000.,
+            z: 100.,
+            ..Default::default()
+        };
+        header.add_point(&point);
+        assert!(point.clone().into_raw(header.transforms()).is_err());
+        let precision = Vector {
+            x: 0.001,
+            y: 0.001,
+            z: 0.001,
+        };
+        let header = super::header(&header, &precision).unwrap();
+        assert!(point.into_raw(header.transforms()).is_ok());
+    }
+
+    #[test]
+    fn rescale_to_writer() {
+        let mut writer = Writer::default();
+        for x in [1.04, 2.01, 3.] {
+            writer
+                .write_point(Point {
+                    x,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let mut builder = Builder::from(reader.header().clone());
+        builder.transforms = transforms(0.1, 0.);
+        let mut writer = Writer::new(
+            std::io::Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+        )
+        .unwrap();
+        let loss = rescale(&mut reader, &mut writer).unwrap();
+        assert_eq!(3, loss.number_of_points);
+        assert!((loss.max.x - 0.04).abs() < 1e-9);
+        assert!((loss.mean().x - 0.05 / 3.).abs() < 1e-9);
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(1., reader.header().bounds().min.x);
+        assert_eq!(1., reader.read_point().unwrap().unwrap().x);
+    }
+}
@@ -0,0 +1,449 @@
+//! Sort point records by location or time.
+//!
+//! Points that are close together in the file compress better and are quicker to query. [sort]
+//! reads all of a [Reader]'s remaining points, orders them by a [SortKey], and writes them to a
+//! [Writer]. If the points don't fit in the memory budget, sorted runs are written to temporary
+//! files and merged.
+//!
+//! ```
+//! use las::{sort, Reader, Writer};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let header = reader.header().clone();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! let options = sort::Options {
+//!     key: sort::SortKey::Hilbert,
+//!     ..Default::default()
+//! };
+//! assert_eq!(106, sort::sort(&mut reader, &mut writer, &options).unwrap());
+//! ```
+
+use crate::{spill::TempDir, Bounds, Point, Reader, Result, Writer};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+/// The value points are sorted by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Gps time, with points without a gps time first.
+    GpsTime,
+
+    /// The [morton] code of the point's x and y in the reader's bounds.
+    #[default]
+    Morton,
+
+    /// The [hilbert] index of the point's x and y in the reader's bounds.
+    Hilbert,
+}
+
+/// Sort options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// The sort key.
+    pub key: SortKey,
+
+    /// The approximate number of bytes of point data to hold in memory at once.
+    ///
+    /// Each sorted run of this size is written to its own temporary file. At most [MAX_RUNS]
+    /// runs are open at the same time; if there are more, they are merged in several passes.
+    pub memory_limit: usize,
+}
+
+/// The most run files that are merged at once.
+pub const MAX_RUNS: usize = 64;
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            key: SortKey::default(),
+            memory_limit: 256 * 1024 * 1024,
+        }
+    }
+}
+
+impl SortKey {
+    fn value(&self, point: &Point, bounds: &Bounds) -> u64 {
+        match self {
+            SortKey::GpsTime => point.gps_time.map(sortable_f64).unwrap_or(0),
+            SortKey::Morton => morton(
+                quantize(point.x, bounds.min.x, bounds.max.x),
+                quantize(point.y, bounds.min.y, bounds.max.y),
+            ),
+            SortKey::Hilbert => hilbert(
+                quantize(point.x, bounds.min.x, bounds.max.x),
+                quantize(point.y, bounds.min.y, bounds.max.y),
+            ),
+        }
+    }
+}
+
+/// Interleaves the bits of x and y into a z-order curve code.
+///
+/// # Examples
+///
+/// ```
+/// use las::sort::morton;
+///
+/// assert_eq!(0b01, morton(1, 0));
+/// assert_eq!(0b10, morton(0, 1));
+/// assert_eq!(0b1111, morton(3, 3));
+/// ```
+pub fn morton(x: u32, y: u32) -> u64 {
+    spread(x) | (spread(y) << 1)
+}
+
+/// Returns the distance along a Hilbert curve that fills the 2<sup>32</sup> by 2<sup>32</sup>
+/// grid.
+///
+/// Unlike [morton] codes, consecutive indices are always neighboring cells.
+///
+/// # Examples
+///
+/// ```
+/// use las::sort::hilbert;
+///
+/// assert_eq!(0, hilbert(0, 0));
+/// assert_eq!(u64::MAX, hilbert(u32::MAX, 0));
+/// ```
+pub fn hilbert(x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (x, y);
+    let mut d = 0u64;
+    let mut s = 1u32 << 31;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += u64::from(s) * u64::from(s) * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = u32::MAX - x;
+                y = u32::MAX - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+/// Sorts the reader's remaining points and writes them to the writer.
+///
+/// Spatial keys are computed from the reader header's bounds. Points with equal keys keep their
+/// original order. The writer's point format must match the reader's. Returns the number of
+/// points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{sort, Reader, Writer};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let header = reader.header().clone();
+/// let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+/// let options = sort::Options {
+///     key: sort::SortKey::GpsTime,
+///     memory_limit: 1024,
+/// };
+/// let _ = sort::sort(&mut reader, &mut writer, &options).unwrap();
+/// ```
+pub fn sort<W: 'static + Write + Seek + Send>(
+    reader: &mut Reader,
+    writer: &mut Writer<W>,
+    options: &Options,
+) -> Result<u64> {
+    let header = reader.header().clone();
+    let bounds = header.bounds();
+    let transforms = *header.transforms();
+    let format = *header.point_format();
+    let len = usize::from(format.len());
+    let capacity = (options.memory_limit / (len + 16)).max(1);
+
+    let mut run = Run::default();
+    let mut runs = Vec::new();
+    let mut dir = None;
+    let mut index = 0;
+    let mut files = 0;
+    for point in reader.points() {
+        let point = point?;
+        let key = options.key.value(&point, &bounds);
+        point
+            .into_raw(&transforms)?
+            .write_to(&mut run.data, &format)?;
+        run.keys.push((key, index));
+        index += 1;
+        if run.keys.len() >= capacity {
+            let dir = match dir.as_ref() {
+                Some(dir) => dir,
+                None => dir.insert(TempDir::new()?),
+            };
+            let path = run_path(dir.path(), &mut files);
+            run.write_sorted(BufWriter::new(File::create(&path)?), len)?;
+            runs.push(path);
+            run = Run::default();
+        }
+    }
+
+    if runs.is_empty() {
+        for (_, i) in run.sorted() {
+            let start = i as usize * len;
+            let raw_point = crate::raw::Point::read_from(&run.data[start..start + len], &format)?;
+            writer.write_point(Point::new(raw_point, &transforms))?;
+        }
+        return Ok(index);
+    }
+    let dir = dir
+        .as_ref()
+        .expect("the temporary directory is created with the first run")
+        .path();
+    if !run.keys.is_empty() {
+        let path = run_path(dir, &mut files);
+        run.write_sorted(BufWriter::new(File::create(&path)?), len)?;
+        runs.push(path);
+    }
+
+    while runs.len() > MAX_RUNS {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(MAX_RUNS));
+        for chunk in runs.chunks(MAX_RUNS) {
+            let path = run_path(dir, &mut files);
+            let mut write = BufWriter::new(File::create(&path)?);
+            merge_runs(chunk, len, |(key, index), data| {
+                write.write_all(&key.to_le_bytes())?;
+                write.write_all(&index.to_le_bytes())?;
+                write.write_all(data)?;
+                Ok(())
+            })?;
+            write.flush()?;
+            for path in chunk {
+                std::fs::remove_file(path)?;
+            }
+            merged.push(path);
+        }
+        runs = merged;
+    }
+    merge_runs(&runs, len, |_, data| {
+        let raw_point = crate::raw::Point::read_from(data, &format)?;
+        writer.write_point(Point::new(raw_point, &transforms))
+    })?;
+    Ok(index)
+}
+
+/// Merges sorted run files, calling `f` with each point's key and data in order.
+fn merge_runs<F>(runs: &[PathBuf], len: usize, mut f: F) -> Result<()>
+where
+    F: FnMut((u64, u64), &[u8]) -> Result<()>,
+{
+    let mut readers = Vec::with_capacity(runs.len());
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    let mut data = vec![0; len];
+    for (i, path) in runs.iter().enumerate() {
+        let mut read = BufReader::new(File::open(path)?);
+        if let Some(key) = read_key(&mut read)? {
+            heap.push(Reverse((key, i)));
+        }
+        readers.push(read);
+    }
+    while let Some(Reverse((key, i))) = heap.pop() {
+        let read = &mut readers[i];
+        read.read_exact(&mut data)?;
+        f(key, &data)?;
+        if let Some(key) = read_key(read)? {
+            heap.push(Reverse((key, i)));
+        }
+    }
+    Ok(())
+}
+
+fn run_path(dir: &Path, files: &mut usize) -> PathBuf {
+    let path = dir.join(files.to_string());
+    *files += 1;
+    path
+}
+
+/// Points, as raw bytes, and their keys.
+///
+/// Keys are paired with the point's index in the input so the sort is stable across runs.
+#[derive(Debug, Default)]
+struct Run {
+    keys: Vec<(u64, u64)>,
+    data: Vec<u8>,
+}
+
+impl Run {
+    fn sorted(&self) -> Vec<(u64, u64)> {
+        let first = self.keys.first().map(|&(_, i)| i).unwrap_or(0);
+        let mut keys: Vec<(u64, u64)> = self.keys.iter().map(|&(k, i)| (k, i - first)).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn write_sorted<W: Write>(&self, mut write: W, len: usize) -> Result<()> {
+        let first = self.keys.first().map(|&(_, i)| i).unwrap_or(0);
+        for (key, i) in self.sorted() {
+            let start = i as usize * len;
+            write.write_all(&key.to_le_bytes())?;
+            write.write_all(&(first + i).to_le_bytes())?;
+            write.write_all(&self.data[start..start + len])?;
+        }
+        write.flush()?;
+        Ok(())
+    }
+}
+
+fn read_key<R: Read>(mut read: R) -> Result<Option<(u64, u64)>> {
+    let mut bytes = [0; 16];
+    match read.read_exact(&mut bytes) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    let (key, index) = bytes.split_at(8);
+    Ok(Some((
+        u64::from_le_bytes(key.try_into().expect("eight bytes")),
+        u64::from_le_bytes(index.try_into().expect("eight bytes")),
+    )))
+}
+
+fn spread(n: u32) -> u64 {
+    let mut n = u64::from(n);
+    n = (n | (n << 16)) & 0x0000_FFFF_0000_FFFF;
+    n = (n | (n << 8)) & 0x00FF_00FF_00FF_00FF;
+    n = (n | (n << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    n = (n | (n << 2)) & 0x3333_3333_3333_3333;
+    (n | (n << 1)) & 0x5555_5555_5555_5555
+}
+
+fn quantize(n: f64, min: f64, max: f64) -> u32 {
+    if max > min {
+        (((n - min) / (max - min)).clamp(0., 1.) * f64::from(u32::MAX)) as u32
+    } else {
+        0
+    }
+}
+
+/// Maps a float to an integer with the same ordering.
+fn sortable_f64(n: f64) -> u64 {
+    let bits = n.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder};
+
+    fn sorted_autzen(options: &Options) -> Vec<Point> {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer =
+            Writer::new(std::io::Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        assert_eq!(106, sort(&mut reader, &mut writer, options).unwrap());
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        reader.points().collect::<Result<Vec<_>>>().unwrap()
+    }
+
+    #[test]
+    fn hilbert_neighbors() {
+        let mut cells = Vec::new();
+        for x in 0..8 {
+            for y in 0..8 {
+                cells.push((hilbert(x, y), x as i64, y as i64));
+            }
+        }
+        cells.sort();
+        for (i, window) in cells.windows(2).enumerate() {
+            assert_eq!(i as u64, window[0].0);
+            let (_, x0, y0) = window[0];
+            let (_, x1, y1) = window[1];
+            assert_eq!(1, (x1 - x0).abs() + (y1 - y0).abs());
+        }
+    }
+
+    #[test]
+    fn sortable_f64_order() {
+        let values = [f64::NEG_INFINITY, -2., -0.5, 0., 0.5, 2., f64::INFINITY];
+        for window in values.windows(2) {
+            assert!(sortable_f64(window[0]) < sortable_f64(window[1]));
+        }
+    }
+
+    #[test]
+    fn external_matches_in_memory() {
+        for key in [SortKey::GpsTime, SortKey::Morton, SortKey::Hilbert] {
+            let in_memory = sorted_autzen(&Options {
+                key,
+                ..Default::default()
+            });
+            let external = sorted_autzen(&Options {
+                key,
+                memory_limit: 1000,
+            });
+            assert_eq!(in_memory, external);
+        }
+    }
+
+    #[test]
+    fn multiple_merge_passes() {
+        // One point per run gives more runs than can be merged at once.
+        for key in [SortKey::GpsTime, SortKey::Morton, SortKey::Hilbert] {
+            let in_memory = sorted_autzen(&Options {
+                key,
+                ..Default::default()
+            });
+            let external = sorted_autzen(&Options {
+                key,
+                memory_limit: 1,
+            });
+            assert_eq!(in_memory, external);
+        }
+    }
+
+    #[test]
+    fn gps_time() {
+        let points = sorted_autzen(&Options {
+            key: SortKey::GpsTime,
+            memory_limit: 500,
+        });
+        for window in points.windows(2) {
+            assert!(window[0].gps_time <= window[1].gps_time);
+        }
+    }
+
+    #[test]
+    fn stable() {
+        let mut builder = Builder::default();
+        builder.point_format = Format::new(1).unwrap();
+        let header = builder.into_header().unwrap();
+        let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header.clone()).unwrap();
+        for (i, gps_time) in [2., 1., 2., 1.].into_iter().enumerate() {
+            writer
+                .write_point(Point {
+                    gps_time: Some(gps_time),
+                    point_source_id: i as u16,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        let options = Options {
+            key: SortKey::GpsTime,
+            memory_limit: 1,
+        };
+        let _ = sort(&mut reader, &mut writer, &options).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let ids: Vec<u16> = reader
+            .points()
+            .map(|p| p.unwrap().point_source_id)
+            .collect();
+        assert_eq!(vec![1, 3, 0, 2], ids);
+    }
+}
@@ -0,0 +1,179 @@
+//! Temporary on-disk storage for data that doesn't fit in memory.
+
+use crate::Result;
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufReader, Cursor, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory under [std::env::temp_dir] that is removed, with its contents, when dropped.
+#[derive(Debug)]
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+/// Byte streams, keyed by `K`, that are kept in memory until they get too big and then appended
+/// to files in a [TempDir].
+#[derive(Debug)]
+pub(crate) struct Spill<K> {
+    dir: TempDir,
+    buffers: BTreeMap<K, Vec<u8>>,
+    files: BTreeMap<K, PathBuf>,
+    next_file: usize,
+    buffered: usize,
+    limit: usize,
+}
+
+impl TempDir {
+    pub(crate) fn new() -> Result<TempDir> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let path = std::env::temp_dir().join(format!(
+            "las-rs-{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ));
+        std::fs::create_dir(&path)?;
+        Ok(TempDir { path })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+impl<K: Ord + Copy> Spill<K> {
+    /// Creates a new spill that writes to disk once more than `limit` bytes are buffered.
+    pub(crate) fn new(limit: usize) -> Result<Spill<K>> {
+        Ok(Spill {
+            dir: TempDir::new()?,
+            buffers: BTreeMap::new(),
+            files: BTreeMap::new(),
+            next_file: 0,
+            buffered: 0,
+            limit,
+        })
+    }
+
+    /// Appends bytes to the stream for this key.
+    pub(crate) fn write(&mut self, key: K, bytes: &[u8]) -> Result<()> {
+        self.buffers
+            .entry(key)
+            .or_default()
+            .extend_from_slice(bytes);
+        self.buffered += bytes.len();
+        if self.buffered > self.limit {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Returns all of the keys that have been written to, in order.
+    pub(crate) fn keys(&self) -> Vec<K> {
+        let mut keys: Vec<K> = self
+            .buffers
+            .keys()
+            .chain(self.files.keys())
+            .copied()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Removes and returns the stream for this key.
+    pub(crate) fn take(&mut self, key: K) -> Result<Box<dyn Read>> {
+        let buffer = Cursor::new(self.buffers.remove(&key).unwrap_or_default());
+        self.buffered -= buffer.get_ref().len();
+        match self.files.remove(&key) {
+            Some(path) => {
+                let file = File::open(&path)?;
+                // If this fails, the file is removed with the directory.
+                let _ = std::fs::remove_file(&path);
+                Ok(Box::new(BufReader::new(file).chain(buffer)))
+            }
+            None => Ok(Box::new(buffer)),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        for (key, buffer) in &mut self.buffers {
+            if buffer.is_empty() {
+                continue;
+            }
+            let path = self.files.entry(*key).or_insert_with(|| {
+                // Not the number of files, which goes down when streams are taken.
+                let n = self.next_file;
+                self.next_file += 1;
+                self.dir.path().join(n.to_string())
+            });
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            file.write_all(buffer)?;
+            buffer.clear();
+        }
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_dir_removed_on_drop() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().to_path_buf();
+        assert!(path.is_dir());
+        std::fs::write(path.join("a"), b"a").unwrap();
+        drop(dir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn spill() {
+        let mut spill = Spill::new(4).unwrap();
+        spill.write(2, b"abc").unwrap();
+        spill.write(1, b"de").unwrap();
+        spill.write(2, b"f").unwrap();
+        assert_eq!(vec![1, 2], spill.keys());
+        let mut s = String::new();
+        let _ = spill.take(2).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!("abcf", s);
+        assert_eq!(vec![1], spill.keys());
+        s.clear();
+        let _ = spill.take(1).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!("de", s);
+        assert!(spill.keys().is_empty());
+    }
+
+    #[test]
+    fn take_then_spill_new_key() {
+        let mut spill = Spill::new(0).unwrap();
+        spill.write(1, b"a").unwrap();
+        spill.write(2, b"b").unwrap();
+        let _ = spill.take(1).unwrap();
+        spill.write(3, b"c").unwrap();
+        let mut s = String::new();
+        let _ = spill.take(2).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!("b", s);
+        s.clear();
+        let _ = spill.take(3).unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!("c", s);
+    }
+}
@@ -0,0 +1,400 @@
+//! Summarize point data.
+//!
+//! [Stats] accumulates per-dimension statistics and histograms in a single pass:
+//!
+//! ```
+//! use las::{point::Dimension, stats::Stats, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut stats = Stats::default();
+//! for point in reader.points() {
+//!     stats.add(&point.unwrap());
+//! }
+//! assert_eq!(106, stats.number_of_points);
+//! let z = stats.dimension(Dimension::Z).unwrap();
+//! assert!(z.min <= z.mean && z.mean <= z.max);
+//! ```
+//!
+//! Stats from different threads can be [merged](Stats::merge). With the `serde` feature, all of
+//! the structures in this module can be serialized, e.g. to JSON.
+
+use crate::{classification::ClassificationScheme, point::Dimension, Point};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The largest return number plus one, for the extended point formats.
+const MAX_RETURNS: usize = 16;
+
+/// Statistics and histograms for a set of points.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    /// The number of points.
+    pub number_of_points: u64,
+
+    /// Statistics for each dimension that at least one point has.
+    pub dimensions: BTreeMap<Dimension, DimensionStats>,
+
+    /// The number of points with each classification code.
+    pub classifications: BTreeMap<u8, u64>,
+
+    /// The number of points for each number of returns (first index) and return number (second
+    /// index).
+    pub returns: [[u64; MAX_RETURNS]; MAX_RETURNS],
+
+    /// The number of points from each scanner channel.
+    pub scanner_channels: [u64; 4],
+}
+
+/// Running statistics for one dimension.
+///
+/// Uses Welford's algorithm, so the mean and variance are stable over many points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DimensionStats {
+    /// The number of values.
+    pub count: u64,
+
+    /// The smallest value.
+    pub min: f64,
+
+    /// The largest value.
+    pub max: f64,
+
+    /// The mean value.
+    pub mean: f64,
+
+    /// The sum of the squared differences from the mean.
+    pub m2: f64,
+}
+
+impl Stats {
+    /// Adds a point to these stats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{stats::Stats, Point};
+    ///
+    /// let mut stats = Stats::default();
+    /// stats.add(&Point { return_number: 1, number_of_returns: 2, ..Default::default() });
+    /// assert_eq!(1, stats.number_of_points);
+    /// assert_eq!(1, stats.returns[2][1]);
+    /// ```
+    pub fn add(&mut self, point: &Point) {
+        self.number_of_points += 1;
+        for dimension in Dimension::ALL {
+            if let Some(value) = dimension.value(point) {
+                self.dimensions.entry(dimension).or_default().add(value);
+            }
+        }
+        *self
+            .classifications
+            .entry(point.classification.into())
+            .or_default() += 1;
+        let number_of_returns = usize::from(point.number_of_returns).min(MAX_RETURNS - 1);
+        let return_number = usize::from(point.return_number).min(MAX_RETURNS - 1);
+        self.returns[number_of_returns][return_number] += 1;
+        self.scanner_channels[usize::from(point.scanner_channel).min(3)] += 1;
+    }
+
+    /// Merges other stats into these.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, stats::Stats, Point};
+    ///
+    /// let mut a = Stats::default();
+    /// a.add(&Point { z: 1., ..Default::default() });
+    /// let mut b = Stats::default();
+    /// b.add(&Point { z: 3., ..Default::default() });
+    /// a.merge(&b);
+    /// assert_eq!(2, a.number_of_points);
+    /// assert_eq!(2., a.dimension(Dimension::Z).unwrap().mean);
+    /// ```
+    pub fn merge(&mut self, other: &Stats) {
+        self.number_of_points += other.number_of_points;
+        for (dimension, stats) in &other.dimensions {
+            self.dimensions.entry(*dimension).or_default().merge(stats);
+        }
+        for (classification, count) in &other.classifications {
+            *self.classifications.entry(*classification).or_default() += count;
+        }
+        for (row, other_row) in self.returns.iter_mut().zip(&other.returns) {
+            for (count, other_count) in row.iter_mut().zip(other_row) {
+                *count += other_count;
+            }
+        }
+        for (count, other_count) in self
+            .scanner_channels
+            .iter_mut()
+            .zip(&other.scanner_channels)
+        {
+            *count += other_count;
+        }
+    }
+
+    /// Returns the statistics for a dimension, if any point had it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{point::Dimension, stats::Stats, Point};
+    ///
+    /// let mut stats = Stats::default();
+    /// stats.add(&Point::default());
+    /// assert!(stats.dimension(Dimension::X).is_some());
+    /// assert!(stats.dimension(Dimension::GpsTime).is_none());
+    /// ```
+    pub fn dimension(&self, dimension: Dimension) -> Option<&DimensionStats> {
+        self.dimensions.get(&dimension)
+    }
+
+    /// Returns the smallest and largest gps times, if any point had a gps time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{stats::Stats, Point};
+    ///
+    /// let mut stats = Stats::default();
+    /// stats.add(&Point { gps_time: Some(2.), ..Default::default() });
+    /// stats.add(&Point { gps_time: Some(1.), ..Default::default() });
+    /// assert_eq!(Some((1., 2.)), stats.gps_time_range());
+    /// ```
+    pub fn gps_time_range(&self) -> Option<(f64, f64)> {
+        self.dimension(Dimension::GpsTime)
+            .map(|stats| (stats.min, stats.max))
+    }
+
+    /// Returns the number of points with each classification, labeled by a classification
+    /// scheme.
+    ///
+    /// See [ClassificationScheme::label] for how codes without a name are labeled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{classification::ClassificationScheme, point::Classification, stats::Stats, Point};
+    ///
+    /// let mut stats = Stats::default();
+    /// stats.add(&Point { classification: Classification::Ground, ..Default::default() });
+    /// let counts = stats.classifications_by_name(&ClassificationScheme::default());
+    /// assert_eq!(Some(&1), counts.get("Ground"));
+    /// ```
+    pub fn classifications_by_name(&self, scheme: &ClassificationScheme) -> BTreeMap<String, u64> {
+        let mut counts = BTreeMap::new();
+        for (&code, &count) in &self.classifications {
+            *counts.entry(scheme.label(code)).or_default() += count;
+        }
+        counts
+    }
+}
+
+impl Extend<Point> for Stats {
+    fn extend<I: IntoIterator<Item = Point>>(&mut self, points: I) {
+        for point in points {
+            self.add(&point);
+        }
+    }
+}
+
+impl<'a> Extend<&'a Point> for Stats {
+    fn extend<I: IntoIterator<Item = &'a Point>>(&mut self, points: I) {
+        for point in points {
+            self.add(point);
+        }
+    }
+}
+
+impl FromIterator<Point> for Stats {
+    fn from_iter<I: IntoIterator<Item = Point>>(points: I) -> Stats {
+        let mut stats = Stats::default();
+        stats.extend(points);
+        stats
+    }
+}
+
+impl DimensionStats {
+    /// Adds a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::DimensionStats;
+    ///
+    /// let mut stats = DimensionStats::default();
+    /// stats.add(1.);
+    /// stats.add(3.);
+    /// assert_eq!(2., stats.mean);
+    /// assert_eq!(1., stats.variance());
+    /// ```
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Merges other stats into these.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::DimensionStats;
+    ///
+    /// let mut a = DimensionStats::default();
+    /// a.add(1.);
+    /// let mut b = DimensionStats::default();
+    /// b.add(3.);
+    /// a.merge(&b);
+    /// assert_eq!(1., a.variance());
+    /// ```
+    pub fn merge(&mut self, other: &DimensionStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Returns the population variance, or zero if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::DimensionStats;
+    /// assert_eq!(0., DimensionStats::default().variance());
+    /// ```
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Returns the population standard deviation, or zero if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::DimensionStats;
+    ///
+    /// let mut stats = DimensionStats::default();
+    /// stats.add(1.);
+    /// stats.add(3.);
+    /// assert_eq!(1., stats.std_dev());
+    /// ```
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+impl Default for DimensionStats {
+    fn default() -> DimensionStats {
+        DimensionStats {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.,
+            m2: 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Classification, Reader};
+
+    fn autzen() -> Vec<Point> {
+        Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .map(|p| p.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn merge_matches_single_pass() {
+        let points = autzen();
+        let all: Stats = points.iter().cloned().collect();
+        let (a, b) = points.split_at(40);
+        let mut merged = Stats::default();
+        merged.extend(a);
+        let mut other = Stats::default();
+        other.extend(b);
+        merged.merge(&other);
+
+        assert_eq!(all.number_of_points, merged.number_of_points);
+        assert_eq!(all.classifications, merged.classifications);
+        assert_eq!(all.returns, merged.returns);
+        for (dimension, stats) in &all.dimensions {
+            let other = merged.dimension(*dimension).unwrap();
+            assert_eq!(stats.count, other.count);
+            assert_eq!(stats.min, other.min);
+            assert_eq!(stats.max, other.max);
+            assert!((stats.mean - other.mean).abs() < 1e-6);
+            assert!((stats.std_dev() - other.std_dev()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn histograms() {
+        let mut stats = Stats::default();
+        stats.add(&Point {
+            classification: Classification::Ground,
+            return_number: 1,
+            number_of_returns: 1,
+            scanner_channel: 2,
+            ..Default::default()
+        });
+        stats.add(&Point {
+            classification: Classification::Ground,
+            ..Default::default()
+        });
+        assert_eq!(Some(&2), stats.classifications.get(&2));
+        assert_eq!(1, stats.returns[1][1]);
+        assert_eq!(1, stats.returns[0][0]);
+        assert_eq!([1, 0, 1, 0], stats.scanner_channels);
+        assert_eq!(None, stats.gps_time_range());
+    }
+
+    #[test]
+    fn merge_empty() {
+        let mut stats: Stats = autzen().into_iter().collect();
+        let before = stats.clone();
+        stats.merge(&Stats::default());
+        assert_eq!(before, stats);
+        let mut empty = Stats::default();
+        empty.merge(&before);
+        assert_eq!(before, empty);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn json() {
+        let stats: Stats = autzen().into_iter().collect();
+        let json = serde_json::to_string(&stats).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(106, value["number_of_points"]);
+        assert!(value["dimensions"]["GpsTime"]["max"].is_number());
+        let roundtrip: Stats = serde_json::from_str(&json).unwrap();
+        assert_eq!(stats.classifications, roundtrip.classifications);
+        assert_eq!(stats.returns, roundtrip.returns);
+    }
+}
@@ -0,0 +1,730 @@
+//! Read and write points as delimited text.
+//!
+//! The columns of the text are described by a spec, either as a string of [LAStools-style
+//! letters](columns), e.g. `xyzirc`, or as comma-separated [Dimension] and extra bytes names,
+//! e.g. `x,y,z,gps_time,height`.
+//!
+//! Use a [Writer] or [export] to write las points as text:
+//!
+//! ```
+//! use las::{text, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let options = text::Options {
+//!     columns: text::columns("xyzi").unwrap(),
+//!     ..Default::default()
+//! };
+//! let mut text = Vec::new();
+//! let count = text::export(&mut reader, &mut text, &options).unwrap();
+//! assert_eq!(106, count);
+//! ```
+//!
+//! Importing is done in two passes, so that memory use doesn't grow with the size of the text.
+//! [builder] scans the text to find the bounds and choose a point format and transforms, then
+//! [import] writes the points:
+//!
+//! ```
+//! use las::{text, Vector, Writer};
+//! use std::io::Cursor;
+//!
+//! let text = "1.0 2.0 3.0 42\n4.0 5.0 6.0 43\n";
+//! let options = text::Options {
+//!     columns: text::columns("xyzi").unwrap(),
+//!     ..Default::default()
+//! };
+//! let precision = Vector { x: 0.01, y: 0.01, z: 0.01 };
+//! let builder = text::builder(text.as_bytes(), &options, &precision).unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+//! let count = text::import(text.as_bytes(), &options, &mut writer).unwrap();
+//! assert_eq!(2, count);
+//! ```
+
+use crate::{
+    extra_bytes::{self, Descriptor},
+    point::{Dimension, Format},
+    Bounds, Builder, Error, Header, Point, Reader as LasReader, Result, Vector,
+    Writer as LasWriter,
+};
+use std::io::{BufRead, Seek, Write};
+
+/// One column of delimited text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    /// A point dimension.
+    Dimension(Dimension),
+
+    /// The named extra bytes field.
+    ExtraBytes(String),
+
+    /// A column that is ignored when reading.
+    ///
+    /// When writing, it is written as `0` if the delimiter is whitespace, and left empty otherwise.
+    Skip,
+}
+
+/// Options for reading and writing delimited text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// The columns, in order.
+    ///
+    /// Defaults to x, y, and z.
+    pub columns: Vec<Column>,
+
+    /// The delimiter between columns.
+    ///
+    /// If `None`, reading splits on whitespace and commas, and writing uses a space. Since
+    /// splitting on whitespace skips empty fields, reading text with empty fields needs a
+    /// delimiter. Empty fields and `nan` are read as missing values.
+    pub delimiter: Option<char>,
+
+    /// The number of lines to skip at the start of the text when reading, e.g. for a header row.
+    ///
+    /// Empty lines and lines starting with `#` are always skipped.
+    pub skip_lines: usize,
+
+    /// The extra bytes fields of each point, used to read [Column::ExtraBytes] columns.
+    ///
+    /// When writing, the extra bytes fields are taken from the las header.
+    pub extra_bytes: Vec<Descriptor>,
+}
+
+/// Reads points from delimited text.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    read: R,
+    columns: Vec<Resolved>,
+    delimiter: Option<char>,
+    skip_lines: usize,
+    extra_bytes_len: usize,
+    line: String,
+    line_number: u64,
+}
+
+/// Writes points as delimited text.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    write: W,
+    columns: Vec<Resolved>,
+    delimiter: char,
+    decimals: Vector<usize>,
+    line: String,
+}
+
+#[derive(Clone, Debug)]
+enum Resolved {
+    Dimension(Dimension),
+    ExtraBytes(usize, Descriptor),
+    Skip,
+}
+
+/// Parses a column spec.
+///
+/// If the spec contains a comma, each comma-separated name is a [Dimension] name, ignoring case
+/// and underscores, `skip`, or the name of an extra bytes field. Otherwise, each character is one
+/// column:
+///
+/// | Letter | Column |
+/// | -- | -- |
+/// | `x`, `y`, `z` | coordinates |
+/// | `i` | intensity |
+/// | `r` | return number |
+/// | `n` | number of returns |
+/// | `d` | scan direction flag |
+/// | `e` | edge of flight line |
+/// | `c` | classification |
+/// | `g` | synthetic |
+/// | `k` | key point |
+/// | `h` | withheld |
+/// | `o` | overlap |
+/// | `l` | scanner channel |
+/// | `a` | scan angle |
+/// | `u` | user data |
+/// | `p` | point source id |
+/// | `t` | gps time |
+/// | `R`, `G`, `B` | color |
+/// | `I` | near infrared |
+/// | `s` | skip |
+///
+/// # Examples
+///
+/// ```
+/// use las::{point::Dimension, text::{self, Column}};
+///
+/// let columns = text::columns("xyzt").unwrap();
+/// assert_eq!(Column::Dimension(Dimension::GpsTime), columns[3]);
+/// let columns = text::columns("x,y,z,gps_time,height").unwrap();
+/// assert_eq!(Column::Dimension(Dimension::GpsTime), columns[3]);
+/// assert_eq!(Column::ExtraBytes("height".to_string()), columns[4]);
+/// assert!(text::columns("xyq").is_err());
+/// ```
+pub fn columns(spec: &str) -> Result<Vec<Column>> {
+    if spec.contains(',') {
+        spec.split(',')
+            .map(|name| {
+                let name = name.trim();
+                if name.is_empty() {
+                    Err(Error::InvalidColumns(spec.to_string()))
+                } else if name.eq_ignore_ascii_case("skip") {
+                    Ok(Column::Skip)
+                } else if let Ok(dimension) = name.replace('_', "").parse() {
+                    Ok(Column::Dimension(dimension))
+                } else {
+                    Ok(Column::ExtraBytes(name.to_string()))
+                }
+            })
+            .collect()
+    } else if spec.is_empty() {
+        Err(Error::InvalidColumns(spec.to_string()))
+    } else {
+        spec.chars()
+            .map(|c| {
+                Ok(Column::Dimension(match c {
+                    'x' => Dimension::X,
+                    'y' => Dimension::Y,
+                    'z' => Dimension::Z,
+                    'i' => Dimension::Intensity,
+                    'r' => Dimension::ReturnNumber,
+                    'n' => Dimension::NumberOfReturns,
+                    'd' => Dimension::ScanDirectionFlag,
+                    'e' => Dimension::EdgeOfFlightLine,
+                    'c' => Dimension::Classification,
+                    'g' => Dimension::Synthetic,
+                    'k' => Dimension::KeyPoint,
+                    'h' => Dimension::Withheld,
+                    'o' => Dimension::Overlap,
+                    'l' => Dimension::ScannerChannel,
+                    'a' => Dimension::ScanAngle,
+                    'u' => Dimension::UserData,
+                    'p' => Dimension::PointSourceId,
+                    't' => Dimension::GpsTime,
+                    'R' => Dimension::Red,
+                    'G' => Dimension::Green,
+                    'B' => Dimension::Blue,
+                    'I' => Dimension::Nir,
+                    's' => return Ok(Column::Skip),
+                    _ => return Err(Error::InvalidColumns(spec.to_string())),
+                }))
+            })
+            .collect()
+    }
+}
+
+/// Scans delimited text and returns a builder that can hold its points.
+///
+/// The builder has the smallest point format that holds every column, extra bytes and an extra
+/// bytes vlr for `options.extra_bytes`, transforms fit to the bounds of the points at the given
+/// precision, and the smallest version that supports all of that. Change the point format or
+/// transforms on the builder before creating the writer if you need something else.
+///
+/// # Examples
+///
+/// ```
+/// use las::{text, Vector};
+///
+/// let options = text::Options {
+///     columns: text::columns("xyzRGB").unwrap(),
+///     ..Default::default()
+/// };
+/// let precision = Vector { x: 0.001, y: 0.001, z: 0.01 };
+/// let builder = text::builder("1 2 3 255 0 0".as_bytes(), &options, &precision).unwrap();
+/// assert_eq!(2, builder.point_format.to_u8().unwrap());
+/// assert_eq!(0.001, builder.transforms.x.scale);
+/// ```
+pub fn builder<R: BufRead>(read: R, options: &Options, precision: &Vector<f64>) -> Result<Builder> {
+    let mut bounds = Bounds::default();
+    for point in Reader::new(read, options)? {
+        bounds.grow(&point?);
+    }
+    let has = |dimension: Dimension| options.columns.contains(&Column::Dimension(dimension));
+    let has_color = has(Dimension::Red) || has(Dimension::Green) || has(Dimension::Blue);
+    let is_extended = has(Dimension::Overlap) || has(Dimension::ScannerChannel);
+    let mut format = Format::new(match (has(Dimension::Nir), is_extended, has_color) {
+        (true, _, _) => 8,
+        (false, true, true) => 7,
+        (false, true, false) => 6,
+        (false, false, true) if has(Dimension::GpsTime) => 3,
+        (false, false, true) => 2,
+        (false, false, false) if has(Dimension::GpsTime) => 1,
+        (false, false, false) => 0,
+    })?;
+    let mut builder = Builder::default();
+    if !options.extra_bytes.is_empty() {
+        format.extra_bytes = u16::try_from(
+            options
+                .extra_bytes
+                .iter()
+                .map(Descriptor::len)
+                .sum::<usize>(),
+        )?;
+        builder.vlrs.push(extra_bytes::vlr(&options.extra_bytes)?);
+    }
+    builder.point_format = format;
+    builder.transforms = bounds.fit_transforms(precision)?;
+    if let Some(version) = builder.minimum_supported_version() {
+        builder.version = version;
+    }
+    Ok(builder)
+}
+
+/// Reads points from delimited text and writes them to a las writer.
+///
+/// Each point is [conformed](Point::conform_to) to the writer's point format. Returns the number
+/// of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{text, Writer};
+///
+/// let mut writer = Writer::default();
+/// let count = text::import("1,2,3\n4,5,6\n".as_bytes(), &Default::default(), &mut writer).unwrap();
+/// assert_eq!(2, count);
+/// ```
+pub fn import<R: BufRead, W: 'static + Write + Seek + Send>(
+    read: R,
+    options: &Options,
+    writer: &mut LasWriter<W>,
+) -> Result<u64> {
+    let format = *writer.header().point_format();
+    let mut count = 0;
+    for point in Reader::new(read, options)? {
+        let mut point = point?;
+        point.conform_to(&format);
+        writer.write_point(point)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Writes every point from a las reader as delimited text.
+///
+/// Returns the number of points written.
+///
+/// # Examples
+///
+/// ```
+/// use las::{text, Reader};
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let mut text = Vec::new();
+/// text::export(&mut reader, &mut text, &Default::default()).unwrap();
+/// assert!(String::from_utf8(text).unwrap().starts_with("636083.30 849398.65 407.35\n"));
+/// ```
+pub fn export<W: Write>(reader: &mut LasReader, write: W, options: &Options) -> Result<u64> {
+    let mut writer = Writer::new(write, options, reader.header())?;
+    let mut count = 0;
+    for point in reader.points() {
+        writer.write_point(&point?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader.
+    ///
+    /// Returns an error if a column names an extra bytes field that isn't in
+    /// `options.extra_bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, Reader};
+    ///
+    /// let options = text::Options {
+    ///     columns: text::columns("x,y,z,height").unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// assert!(Reader::new("1 2 3 4".as_bytes(), &options).is_err());
+    /// ```
+    pub fn new(read: R, options: &Options) -> Result<Reader<R>> {
+        Ok(Reader {
+            read,
+            columns: resolve(&options.columns, &options.extra_bytes)?,
+            delimiter: options.delimiter,
+            skip_lines: options.skip_lines,
+            extra_bytes_len: options.extra_bytes.iter().map(Descriptor::len).sum(),
+            line: String::new(),
+            line_number: 0,
+        })
+    }
+
+    fn parse(&self) -> Result<Point> {
+        let mut point = Point {
+            extra_bytes: vec![0; self.extra_bytes_len],
+            ..Default::default()
+        };
+        let line = self.line.trim();
+        let mut fields: Box<dyn Iterator<Item = &str>> = match self.delimiter {
+            Some(delimiter) => Box::new(line.split(delimiter).map(str::trim)),
+            None => Box::new(
+                line.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty()),
+            ),
+        };
+        for column in &self.columns {
+            let field = fields.next().ok_or_else(|| self.error("too few columns"))?;
+            if let Resolved::Skip = column {
+                continue;
+            }
+            if field.is_empty() || field.eq_ignore_ascii_case("nan") {
+                continue;
+            }
+            let value: f64 = field
+                .parse()
+                .map_err(|_| self.error(&format!("not a number: {}", field)))?;
+            match column {
+                Resolved::Dimension(dimension) => dimension
+                    .set_value(&mut point, value)
+                    .map_err(|err| self.error(&err.to_string()))?,
+                Resolved::ExtraBytes(offset, descriptor) => {
+                    descriptor.encode(value, &mut point.extra_bytes[*offset..])?
+                }
+                Resolved::Skip => unreachable!(),
+            }
+        }
+        Ok(point)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidText {
+            line: self.line_number,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        loop {
+            self.line.clear();
+            match self.read.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(err) => return Some(Err(err.into())),
+            }
+            if self.skip_lines > 0 {
+                self.skip_lines -= 1;
+                continue;
+            }
+            let line = self.line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return Some(self.parse());
+        }
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer for points described by this header.
+    ///
+    /// Coordinates are written with as many decimal places as the header's scales need. Returns
+    /// an error if a column names an extra bytes field that isn't in the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{text::Writer, Header, Point};
+    ///
+    /// let mut writer = Writer::new(Vec::new(), &Default::default(), &Header::default()).unwrap();
+    /// writer.write_point(&Point { x: 1., y: 2., z: 3.5, ..Default::default() }).unwrap();
+    /// assert_eq!(b"1.000 2.000 3.500\n".to_vec(), writer.into_inner());
+    /// ```
+    pub fn new(write: W, options: &Options, header: &Header) -> Result<Writer<W>> {
+        let descriptors = header.extra_bytes()?;
+        let transforms = header.transforms();
+        Ok(Writer {
+            write,
+            columns: resolve(&options.columns, &descriptors)?,
+            delimiter: options.delimiter.unwrap_or(' '),
+            decimals: Vector {
+                x: decimals(transforms.x.scale),
+                y: decimals(transforms.y.scale),
+                z: decimals(transforms.z.scale),
+            },
+            line: String::new(),
+        })
+    }
+
+    /// Writes one point as a line of text.
+    ///
+    /// Attributes that the point doesn't have, like a missing gps time, are left empty. If the
+    /// delimiter is whitespace, they're written as `nan` instead, so the columns still line up
+    /// when the text is read back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{text::{self, Writer}, Header, Point};
+    ///
+    /// let options = text::Options {
+    ///     columns: text::columns("xt").unwrap(),
+    ///     delimiter: Some(','),
+    ///     ..Default::default()
+    /// };
+    /// let mut writer = Writer::new(Vec::new(), &options, &Header::default()).unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// assert_eq!(b"0.000,\n".to_vec(), writer.into_inner());
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<()> {
+        use std::fmt::Write as _;
+
+        self.line.clear();
+        let placeholders = self.delimiter.is_whitespace();
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                self.line.push(self.delimiter);
+            }
+            let start = self.line.len();
+            let _ = match column {
+                Resolved::Dimension(Dimension::X) => {
+                    write!(self.line, "{:.*}", self.decimals.x, point.x)
+                }
+                Resolved::Dimension(Dimension::Y) => {
+                    write!(self.line, "{:.*}", self.decimals.y, point.y)
+                }
+                Resolved::Dimension(Dimension::Z) => {
+                    write!(self.line, "{:.*}", self.decimals.z, point.z)
+                }
+                Resolved::Dimension(dimension) => match dimension.value(point) {
+                    Some(value) => write!(self.line, "{}", value),
+                    None => Ok(()),
+                },
+                Resolved::ExtraBytes(offset, descriptor) => {
+                    match point
+                        .extra_bytes
+                        .get(*offset..)
+                        .and_then(|bytes| descriptor.decode(bytes))
+                    {
+                        Some(value) => write!(self.line, "{}", value),
+                        None => Ok(()),
+                    }
+                }
+                Resolved::Skip => {
+                    if placeholders {
+                        self.line.push('0');
+                    }
+                    Ok(())
+                }
+            };
+            if placeholders && self.line.len() == start {
+                self.line.push_str("nan");
+            }
+        }
+        self.line.push('\n');
+        self.write.write_all(self.line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{text::Writer, Header};
+    ///
+    /// let writer = Writer::new(Vec::new(), &Default::default(), &Header::default()).unwrap();
+    /// assert!(writer.into_inner().is_empty());
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.write
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            columns: vec![
+                Column::Dimension(Dimension::X),
+                Column::Dimension(Dimension::Y),
+                Column::Dimension(Dimension::Z),
+            ],
+            delimiter: None,
+            skip_lines: 0,
+            extra_bytes: Vec::new(),
+        }
+    }
+}
+
+fn resolve(columns: &[Column], descriptors: &[Descriptor]) -> Result<Vec<Resolved>> {
+    columns
+        .iter()
+        .map(|column| {
+            Ok(match column {
+                Column::Dimension(dimension) => Resolved::Dimension(*dimension),
+                Column::ExtraBytes(name) => {
+                    let (offset, descriptor) = extra_bytes::find(descriptors, name)
+                        .ok_or_else(|| Error::UnknownExtraBytes(name.clone()))?;
+                    Resolved::ExtraBytes(offset, descriptor.clone())
+                }
+                Column::Skip => Resolved::Skip,
+            })
+        })
+        .collect()
+}
+
+/// Returns the number of decimal places needed to show values at this scale.
+pub(crate) fn decimals(scale: f64) -> usize {
+    if scale > 0. && scale < 1. {
+        (-scale.log10() - 1e-9).ceil() as usize
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extra_bytes::DataType, Reader as LasReader, Writer as LasWriter};
+    use std::io::Cursor;
+
+    fn options(spec: &str) -> Options {
+        Options {
+            columns: columns(spec).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn roundtrip_autzen() {
+        let mut reader = LasReader::from_path("tests/data/autzen.las").unwrap();
+        let options = options("xyzitrncaup");
+        let mut text = Vec::new();
+        let _ = export(&mut reader, &mut text, &options).unwrap();
+
+        let precision = Vector {
+            x: 0.01,
+            y: 0.01,
+            z: 0.01,
+        };
+        let builder = builder(text.as_slice(), &options, &precision).unwrap();
+        assert_eq!(1, builder.point_format.to_u8().unwrap());
+        let mut writer =
+            LasWriter::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        assert_eq!(106, import(text.as_slice(), &options, &mut writer).unwrap());
+        let mut roundtrip = LasReader::new(writer.into_inner().unwrap()).unwrap();
+
+        let mut original = LasReader::from_path("tests/data/autzen.las").unwrap();
+        for (expected, actual) in original
+            .points()
+            .map(|p| p.unwrap())
+            .zip(roundtrip.points().map(|p| p.unwrap()))
+        {
+            assert!((expected.x - actual.x).abs() < 1e-6);
+            assert!((expected.y - actual.y).abs() < 1e-6);
+            assert!((expected.z - actual.z).abs() < 1e-6);
+            assert_eq!(expected.intensity, actual.intensity);
+            assert_eq!(expected.classification, actual.classification);
+            assert_eq!(expected.point_source_id, actual.point_source_id);
+            assert_eq!(expected.gps_time, actual.gps_time);
+        }
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut descriptor = Descriptor::new("height", DataType::I16);
+        descriptor.scale = Some(0.01);
+        let options = Options {
+            extra_bytes: vec![descriptor],
+            ..options("x,y,z,skip,height")
+        };
+        let text = "# comment\n1 2 3 foo 4.25\n\n5 6 7 bar -1.5\n";
+        let points: Vec<Point> = Reader::new(text.as_bytes(), &options)
+            .unwrap()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(2, points.len());
+        assert_eq!(
+            Some(4.25),
+            extra_bytes::value(&options.extra_bytes, "height", &points[0])
+        );
+
+        let precision = Vector {
+            x: 0.1,
+            y: 0.1,
+            z: 0.1,
+        };
+        let builder = builder(text.as_bytes(), &options, &precision).unwrap();
+        assert_eq!(2, builder.point_format.extra_bytes);
+        let header = builder.into_header().unwrap();
+        let mut writer = Writer::new(Vec::new(), &options, &header).unwrap();
+        for point in &points {
+            writer.write_point(point).unwrap();
+        }
+        assert_eq!(
+            "1.0 2.0 3.0 0 4.25\n5.0 6.0 7.0 0 -1.5\n",
+            String::from_utf8(writer.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn roundtrip_placeholders() {
+        let options = options("xyzsti");
+        let mut writer = Writer::new(Vec::new(), &options, &Header::default()).unwrap();
+        writer
+            .write_point(&Point {
+                x: 1.,
+                intensity: 7,
+                ..Default::default()
+            })
+            .unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!("1.000 0.000 0.000 0 nan 7\n", text);
+        let point = Reader::new(text.as_bytes(), &options)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(1., point.x);
+        assert_eq!(None, point.gps_time);
+        assert_eq!(7, point.intensity);
+    }
+
+    #[test]
+    fn skip_lines_and_delimiter() {
+        let options = Options {
+            delimiter: Some(';'),
+            skip_lines: 1,
+            ..options("xyzc")
+        };
+        let text = "X;Y;Z;Classification\n1;2;3;2\n";
+        let points: Vec<Point> = Reader::new(text.as_bytes(), &options)
+            .unwrap()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(1, points.len());
+        assert_eq!(
+            crate::point::Classification::Ground,
+            points[0].classification
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let options = options("xyz");
+        let mut reader = Reader::new("1 2\n".as_bytes(), &options).unwrap();
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(Error::InvalidText { line: 1, .. })
+        ));
+        let mut reader = Reader::new("\n1 2 a\n".as_bytes(), &options).unwrap();
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(Error::InvalidText { line: 2, .. })
+        ));
+        assert!(columns("").is_err());
+        assert!(columns("x,,z").is_err());
+    }
+
+    #[test]
+    fn decimal_places() {
+        assert_eq!(2, decimals(0.01));
+        assert_eq!(3, decimals(0.001));
+        assert_eq!(1, decimals(0.5));
+        assert_eq!(0, decimals(1.));
+        assert_eq!(0, decimals(10.));
+    }
+}
@@ -0,0 +1,270 @@
+//! Split las data into square tiles on a regular grid.
+//!
+//! [tile] reads a [Reader] once and writes each point to the [Writer] for its grid cell. Only a
+//! limited number of writers are kept open at a time; points for any other tiles are spilled to
+//! temporary files and written after the read is done.
+//!
+//! ```
+//! use las::{tile, Reader};
+//! use std::io::Cursor;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let options = tile::Options {
+//!     size: 1000.,
+//!     ..Default::default()
+//! };
+//! let tiles = tile::tile(&mut reader, &options, |_| Ok(Cursor::new(Vec::new()))).unwrap();
+//! assert!(tiles.contains(&tile::Tile { column: 636, row: 850 }));
+//! ```
+
+use crate::{spill::Spill, Error, Point, Reader, Result, Writer};
+use std::{
+    collections::BTreeMap,
+    io::{ErrorKind, Read, Seek, Write},
+};
+
+const SPILL_LIMIT: usize = 1 << 24;
+
+/// Tiling options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    /// The width and height of each tile.
+    pub size: f64,
+
+    /// The x and y coordinates of the lower left corner of tile (0, 0).
+    pub origin: (f64, f64),
+
+    /// Points within this distance of a tile are also written to that tile.
+    pub buffer: f64,
+
+    /// The maximum number of writers that are open at the same time.
+    pub max_open_writers: usize,
+}
+
+/// A cell in the tiling grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tile {
+    /// The tile's index along the x axis.
+    pub column: i64,
+
+    /// The tile's index along the y axis.
+    pub row: i64,
+}
+
+impl Options {
+    /// Returns the tiles that should contain a point at these coordinates, including buffers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::tile::{Options, Tile};
+    ///
+    /// let options = Options { size: 10., ..Default::default() };
+    /// assert_eq!(vec![Tile { column: 0, row: -1 }], options.tiles(5., -5.));
+    /// let options = Options { size: 10., buffer: 1., ..Default::default() };
+    /// assert_eq!(2, options.tiles(9.5, 5.).len());
+    /// ```
+    pub fn tiles(&self, x: f64, y: f64) -> Vec<Tile> {
+        let index = |n: f64, origin: f64| ((n - origin) / self.size).floor() as i64;
+        let (x0, x1) = (
+            index(x - self.buffer, self.origin.0),
+            index(x + self.buffer, self.origin.0),
+        );
+        let (y0, y1) = (
+            index(y - self.buffer, self.origin.1),
+            index(y + self.buffer, self.origin.1),
+        );
+        let mut tiles = Vec::new();
+        for column in x0..=x1 {
+            for row in y0..=y1 {
+                tiles.push(Tile { column, row });
+            }
+        }
+        tiles
+    }
+
+    fn verify(&self) -> Result<()> {
+        if !self.size.is_finite() || self.size <= 0. {
+            Err(Error::InvalidTileSize(self.size))
+        } else if !self.buffer.is_finite() || self.buffer < 0. {
+            Err(Error::InvalidTileBuffer(self.buffer))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            size: 1000.,
+            origin: (0., 0.),
+            buffer: 0.,
+            max_open_writers: 64,
+        }
+    }
+}
+
+/// Writes the reader's remaining points into tiles.
+///
+/// `create` is called once for each tile that has points. Every tile's header is a copy of the
+/// reader's header, with its own bounds and point counts. Returns the tiles that were written, in
+/// order.
+///
+/// # Examples
+///
+/// ```
+/// use las::{tile, Reader};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let options = tile::Options { size: 100_000., ..Default::default() };
+/// let tiles = tile::tile(&mut reader, &options, |_| Ok(Cursor::new(Vec::new()))).unwrap();
+/// assert_eq!(1, tiles.len());
+/// ```
+pub fn tile<W, F>(reader: &mut Reader, options: &Options, mut create: F) -> Result<Vec<Tile>>
+where
+    W: 'static + Write + Seek + Send,
+    F: FnMut(Tile) -> Result<W>,
+{
+    options.verify()?;
+    let header = reader.header().clone();
+    let transforms = *header.transforms();
+    let format = *header.point_format();
+    let mut writers = BTreeMap::new();
+    let mut spill: Option<Spill<Tile>> = None;
+    let mut bytes = Vec::with_capacity(usize::from(format.len()));
+    for point in reader.points() {
+        let point = point?;
+        for tile in options.tiles(point.x, point.y) {
+            if !writers.contains_key(&tile) && writers.len() < options.max_open_writers {
+                let writer = Writer::new(create(tile)?, header.clone())?;
+                let _ = writers.insert(tile, writer);
+            }
+            if let Some(writer) = writers.get_mut(&tile) {
+                writer.write_point(point.clone())?;
+            } else {
+                let spill = match spill.as_mut() {
+                    Some(spill) => spill,
+                    None => spill.insert(Spill::new(SPILL_LIMIT)?),
+                };
+                bytes.clear();
+                point
+                    .clone()
+                    .into_raw(&transforms)?
+                    .write_to(&mut bytes, &format)?;
+                spill.write(tile, &bytes)?;
+            }
+        }
+    }
+    let mut tiles = Vec::new();
+    for (tile, mut writer) in writers {
+        writer.close()?;
+        tiles.push(tile);
+    }
+    if let Some(mut spill) = spill {
+        for tile in spill.keys() {
+            let mut read = spill.take(tile)?;
+            let mut writer = Writer::new(create(tile)?, header.clone())?;
+            bytes.resize(usize::from(format.len()), 0);
+            loop {
+                match read.read_exact(&mut bytes) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                    Err(err) => return Err(err.into()),
+                }
+                let raw_point = crate::raw::Point::read_from(bytes.as_slice(), &format)?;
+                writer.write_point(Point::new(raw_point, &transforms))?;
+            }
+            writer.close()?;
+            tiles.push(tile);
+        }
+    }
+    tiles.sort();
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spill::TempDir;
+    use std::fs::File;
+
+    fn tile_autzen(options: &Options) -> (TempDir, Vec<(Tile, Reader)>) {
+        let dir = TempDir::new().unwrap();
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let tiles = tile(&mut reader, options, |tile| {
+            File::create(dir.path().join(format!("{}_{}.las", tile.column, tile.row)))
+                .map_err(Error::from)
+        })
+        .unwrap();
+        let readers = tiles
+            .into_iter()
+            .map(|tile| {
+                let path = dir.path().join(format!("{}_{}.las", tile.column, tile.row));
+                (tile, Reader::from_path(path).unwrap())
+            })
+            .collect();
+        (dir, readers)
+    }
+
+    #[test]
+    fn invalid_options() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let create = |_| Ok(std::io::Cursor::new(Vec::new()));
+        let options = Options {
+            size: 0.,
+            ..Default::default()
+        };
+        assert!(tile(&mut reader, &options, create).is_err());
+        let options = Options {
+            buffer: -1.,
+            ..Default::default()
+        };
+        assert!(tile(&mut reader, &options, create).is_err());
+    }
+
+    #[test]
+    fn spills() {
+        let options = Options {
+            size: 1000.,
+            max_open_writers: 2,
+            ..Default::default()
+        };
+        let (_dir, tiles) = tile_autzen(&options);
+        assert!(tiles.len() > 2);
+        let mut count = 0;
+        for (tile, mut reader) in tiles {
+            let header = reader.header().clone();
+            count += header.number_of_points();
+            let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(header.number_of_points(), points.len() as u64);
+            for point in points {
+                assert_eq!(vec![tile], options.tiles(point.x, point.y));
+                assert!(point.x >= header.bounds().min.x && point.x <= header.bounds().max.x);
+            }
+        }
+        assert_eq!(106, count);
+    }
+
+    #[test]
+    fn buffer() {
+        let options = Options {
+            size: 1000.,
+            buffer: 100.,
+            max_open_writers: 1,
+            ..Default::default()
+        };
+        let (_dir, tiles) = tile_autzen(&options);
+        let mut count = 0;
+        for (tile, reader) in tiles {
+            count += reader.header().number_of_points();
+            let bounds = reader.header().bounds();
+            assert!(bounds.min.x >= tile.column as f64 * 1000. - 100.);
+            assert!(bounds.max.x < (tile.column + 1) as f64 * 1000. + 100.);
+            assert!(bounds.min.y >= tile.row as f64 * 1000. - 100.);
+            assert!(bounds.max.y < (tile.row + 1) as f64 * 1000. + 100.);
+        }
+        assert!(count > 106);
+    }
+}
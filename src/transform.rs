@@ -1,8 +1,9 @@
-use crate::Result;
+use crate::{Error, Result, Vector};
 use std::fmt;
 
 /// A scale and an offset that transforms xyz coordinates.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     /// The scale.
     pub scale: f64,
@@ -11,6 +12,70 @@ pub struct Transform {
     pub offset: f64,
 }
 
+/// How a [Writer](crate::Writer) chooses the transforms used to store point coordinates.
+///
+/// The default transforms (a scale of 0.001 and an offset of zero) can't store large coordinates,
+/// e.g. UTM northings. If you don't know the extent of your data before you start writing it, use
+/// [TransformPolicy::Auto] to have the writer choose transforms centered on the data:
+///
+/// ```
+/// use las::{Point, TransformPolicy, Vector, Writer};
+/// use std::io::Cursor;
+///
+/// let policy = TransformPolicy::Auto {
+///     precision: Vector { x: 0.001, y: 0.001, z: 0.001 },
+/// };
+/// let mut writer =
+///     Writer::with_transform_policy(Cursor::new(Vec::new()), Default::default(), policy).unwrap();
+/// writer.write_point(Point { x: 500_000., y: 5_000_000., ..Default::default() }).unwrap();
+/// writer.close().unwrap();
+/// assert_eq!(5_000_000., writer.header().transforms().y.offset);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransformPolicy {
+    /// Use the header's transforms as they are.
+    #[default]
+    Fixed,
+
+    /// Choose transforms that fit the bounds of the written points.
+    ///
+    /// The writer buffers all points in memory and picks the transforms when it is closed, using
+    /// [Bounds::fit_transforms](crate::Bounds::fit_transforms). If you'd rather not buffer, scan
+    /// your points first and use [rescale::header](crate::rescale::header) instead.
+    Auto {
+        /// The desired precision in each dimension.
+        precision: Vector<f64>,
+    },
+}
+
+impl TransformPolicy {
+    /// Checks that this policy's precision, if any, is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{TransformPolicy, Vector};
+    ///
+    /// assert!(TransformPolicy::Fixed.verify().is_ok());
+    /// let policy = TransformPolicy::Auto { precision: Vector { x: 0.01, y: 0.01, z: 0. } };
+    /// assert!(policy.verify().is_err());
+    /// ```
+    pub fn verify(&self) -> Result<()> {
+        match *self {
+            TransformPolicy::Fixed => Ok(()),
+            TransformPolicy::Auto { precision } => {
+                for n in [precision.x, precision.y, precision.z] {
+                    if !n.is_finite() || n <= 0. {
+                        return Err(Error::InvalidPrecision(n));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 impl Transform {
     /// Applies this transform to an i32, returning a float.
     ///
@@ -40,9 +105,43 @@ impl Transform {
         self.inverse_with_rounding_mode(n, RoundingMode::Round)
     }
 
-    pub(crate) fn inverse_with_rounding_mode(&self, n: f64, r: RoundingMode) -> Result<i32> {
-        use crate::Error;
+    /// Creates a transform that can represent every value between `min` and `max`.
+    ///
+    /// The offset is the midpoint of the range, rounded to a whole number. The scale is
+    /// `precision`, unless the range is too large to be stored as `i32`s at that precision, in
+    /// which case the scale is coarsened by factors of ten until the range fits. If `min` or `max`
+    /// aren't finite (e.g. empty [Bounds](crate::Bounds)), the offset is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Transform;
+    ///
+    /// let transform = Transform::fit(500_000., 5_000_000., 0.001).unwrap();
+    /// assert_eq!(0.01, transform.scale);
+    /// assert_eq!(2_750_000., transform.offset);
+    /// assert!(transform.inverse(5_000_000.).is_ok());
+    /// ```
+    pub fn fit(min: f64, max: f64, precision: f64) -> Result<Transform> {
+        if !precision.is_finite() || precision <= 0. {
+            return Err(Error::InvalidPrecision(precision));
+        }
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return Ok(Transform {
+                scale: precision,
+                offset: 0.,
+            });
+        }
+        let offset = (min / 2. + max / 2.).round();
+        let half_range = (max - offset).abs().max((min - offset).abs());
+        let mut scale = precision;
+        while (half_range / scale).ceil() > f64::from(i32::MAX) {
+            scale *= 10.;
+        }
+        Ok(Transform { scale, offset })
+    }
 
+    pub(crate) fn inverse_with_rounding_mode(&self, n: f64, r: RoundingMode) -> Result<i32> {
         fn round(n: f64, r: RoundingMode) -> f64 {
             match r {
                 RoundingMode::Round => n.round(),
@@ -88,7 +187,6 @@ pub(crate) enum RoundingMode {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::i32;
 
     #[test]
     fn too_large() {
@@ -103,4 +201,32 @@ mod tests {
         let n = i32::MIN as f64 * transform.scale - 1.;
         assert!(transform.inverse(n).is_err());
     }
+
+    #[test]
+    fn fit_keeps_precision() {
+        let transform = Transform::fit(-1., 1., 0.001).unwrap();
+        assert_eq!(0.001, transform.scale);
+        assert_eq!(0., transform.offset);
+    }
+
+    #[test]
+    fn fit_large_range() {
+        let transform = Transform::fit(f64::from(i32::MIN), f64::from(i32::MAX), 0.001).unwrap();
+        assert!(transform.inverse(f64::from(i32::MIN)).is_ok());
+        assert!(transform.inverse(f64::from(i32::MAX)).is_ok());
+    }
+
+    #[test]
+    fn fit_empty() {
+        let transform = Transform::fit(f64::INFINITY, f64::NEG_INFINITY, 0.01).unwrap();
+        assert_eq!(0.01, transform.scale);
+        assert_eq!(0., transform.offset);
+    }
+
+    #[test]
+    fn fit_invalid_precision() {
+        assert!(Transform::fit(0., 1., 0.).is_err());
+        assert!(Transform::fit(0., 1., -1.).is_err());
+        assert!(Transform::fit(0., 1., f64::NAN).is_err());
+    }
 }
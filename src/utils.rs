@@ -12,6 +12,24 @@ pub(crate) trait FromLasStr {
     fn from_las_str(&mut self, s: &str) -> Result<()>;
 }
 
+/// Serializes bytes as a base64 string.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_base64 {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        STANDARD.decode(s).map_err(D::Error::custom)
+    }
+}
+
 pub(crate) fn some_or_none_if_zero<T: Zero>(n: T) -> Option<T> {
     if n.is_zero() {
         None
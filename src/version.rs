@@ -5,6 +5,7 @@ use std::fmt;
 ///
 /// Defaults to 1.2.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version {
     /// The major version.
     ///
@@ -28,14 +28,50 @@
 //! assert_eq!(0, header.evlrs().len());
 //! assert_eq!(1, header.vlrs().len());
 //! ```
+//!
+//! The records defined by the las specification, the GeoTIFF records, and the laszip record can
+//! be parsed into a [KnownVlr]:
+//!
+//! ```
+//! use las::{vlr::KnownVlr, Reader};
+//!
+//! let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let vlr = reader.header().find_vlr("LASF_Projection", 34737).unwrap();
+//! if let Some(KnownVlr::GeoAsciiParams(ascii)) = vlr.parse().unwrap() {
+//!     assert!(ascii.starts_with("NAD83(HARN) / Oregon Lambert (ft)"));
+//! } else {
+//!     panic!("expected GeoTIFF ascii params");
+//! }
+//! ```
 
-use crate::{raw, Error, Result};
+use crate::{
+    classification::{self, ClassificationScheme},
+    crs::{self, GeoKeyDirectory},
+    extra_bytes::{self, Descriptor},
+    raw,
+    waveform::{self, WaveformPacketDescriptor},
+    Error, Result,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
 
 const REGULAR_HEADER_SIZE: usize = 54;
 const EXTENDED_HEADER_SIZE: usize = 60;
 
+/// The record id of the Text Area Description vlr.
+pub const TEXT_AREA_DESCRIPTION_RECORD_ID: u16 = 3;
+
+/// The record id of a Superseded vlr.
+pub const SUPERSEDED_RECORD_ID: u16 = 7;
+
+/// The user id of the laszip vlr.
+pub const LASZIP_USER_ID: &str = "laszip encoded";
+
+/// The record id of the laszip vlr.
+pub const LASZIP_RECORD_ID: u16 = 22204;
+
 /// A variable length record.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vlr {
     /// The user that created this record.
     ///
@@ -50,6 +86,9 @@ pub struct Vlr {
     pub description: String,
 
     /// The data themselves.
+    ///
+    /// With the `serde` feature, the data are serialized as a base64 string.
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_base64"))]
     pub data: Vec<u8>,
 }
 
@@ -151,6 +190,57 @@ impl Vlr {
         self.data.len() > u16::MAX as usize
     }
 
+    /// Parses this vlr's data if it is a record that this crate knows about.
+    ///
+    /// Returns `None` for other records, and an error if a known record's data are malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{vlr::KnownVlr, Vlr};
+    ///
+    /// let vlr = KnownVlr::TextAreaDescription("Collected in 2024".to_string()).to_vlr().unwrap();
+    /// assert_eq!(
+    ///     Some(KnownVlr::TextAreaDescription("Collected in 2024".to_string())),
+    ///     vlr.parse().unwrap()
+    /// );
+    /// assert_eq!(None, Vlr::default().parse().unwrap());
+    /// ```
+    pub fn parse(&self) -> Result<Option<KnownVlr>> {
+        let known = match (self.user_id.as_str(), self.record_id) {
+            (classification::USER_ID, classification::RECORD_ID) => {
+                KnownVlr::ClassificationLookup(ClassificationScheme::from_vlr(self)?)
+            }
+            (extra_bytes::USER_ID, TEXT_AREA_DESCRIPTION_RECORD_ID) => {
+                KnownVlr::TextAreaDescription(text_from_data(&self.data)?)
+            }
+            (extra_bytes::USER_ID, extra_bytes::RECORD_ID) => {
+                KnownVlr::ExtraBytes(extra_bytes::descriptors(self)?)
+            }
+            (extra_bytes::USER_ID, SUPERSEDED_RECORD_ID) => KnownVlr::Superseded(self.data.clone()),
+            (waveform::USER_ID, _) => match WaveformPacketDescriptor::from_vlr(self)? {
+                Some((index, descriptor)) => KnownVlr::WaveformPacketDescriptor(index, descriptor),
+                None => return Ok(None),
+            },
+            (crs::USER_ID, crs::GEO_KEY_DIRECTORY_RECORD_ID) => {
+                KnownVlr::GeoKeyDirectory(GeoKeyDirectory::from_vlr(self)?)
+            }
+            (crs::USER_ID, crs::GEO_DOUBLE_PARAMS_RECORD_ID) => {
+                KnownVlr::GeoDoubleParams(crs::double_params_from_data(&self.data)?)
+            }
+            (crs::USER_ID, crs::GEO_ASCII_PARAMS_RECORD_ID) => {
+                KnownVlr::GeoAsciiParams(text_from_data(&self.data)?)
+            }
+            (crs::USER_ID, crs::WKT_RECORD_ID) => KnownVlr::Wkt(text_from_data(&self.data)?),
+            #[cfg(feature = "laz")]
+            (LASZIP_USER_ID, LASZIP_RECORD_ID) => {
+                KnownVlr::Laszip(laz::LazVlr::from_buffer(&self.data)?)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(known))
+    }
+
     fn record_length_after_header(&self, is_extended: bool) -> Result<raw::vlr::RecordLength> {
         if is_extended {
             Ok(raw::vlr::RecordLength::Evlr(self.data.len() as u64))
@@ -162,6 +252,134 @@ impl Vlr {
     }
 }
 
+/// A vlr whose data have a layout defined by the las specification, GeoTIFF, or laszip.
+///
+/// The laszip variant only exists with the `laz` feature, and more record types may be added,
+/// so matches need a wildcard arm.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum KnownVlr {
+    /// The Classification Lookup vlr (`LASF_Spec`, record id 0).
+    ClassificationLookup(ClassificationScheme),
+
+    /// The Text Area Description vlr (`LASF_Spec`, record id 3).
+    TextAreaDescription(String),
+
+    /// The Extra Bytes vlr (`LASF_Spec`, record id 4).
+    ExtraBytes(Vec<Descriptor>),
+
+    /// A Superseded vlr (`LASF_Spec`, record id 7), which keeps the data of a record that is no
+    /// longer valid.
+    Superseded(Vec<u8>),
+
+    /// A Waveform Packet Descriptor vlr (`LASF_Spec`, record ids 100 through 354) and its index.
+    WaveformPacketDescriptor(u8, WaveformPacketDescriptor),
+
+    /// The GeoKeyDirectoryTag vlr (`LASF_Projection`, record id 34735).
+    GeoKeyDirectory(GeoKeyDirectory),
+
+    /// The GeoDoubleParamsTag vlr (`LASF_Projection`, record id 34736).
+    GeoDoubleParams(Vec<f64>),
+
+    /// The GeoAsciiParamsTag vlr (`LASF_Projection`, record id 34737).
+    GeoAsciiParams(String),
+
+    /// The OGC coordinate system WKT vlr (`LASF_Projection`, record id 2112).
+    Wkt(String),
+
+    /// The laszip vlr (`laszip encoded`, record id 22204).
+    #[cfg(feature = "laz")]
+    Laszip(laz::LazVlr),
+}
+
+impl KnownVlr {
+    /// Creates a vlr with this record's user id, record id, and data.
+    ///
+    /// Strings are written with a terminating NUL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::vlr::KnownVlr;
+    ///
+    /// let vlr = KnownVlr::Wkt("GEOGCS[\"WGS 84\"]".to_string()).to_vlr().unwrap();
+    /// assert_eq!("LASF_Projection", vlr.user_id);
+    /// assert_eq!(2112, vlr.record_id);
+    /// ```
+    pub fn to_vlr(&self) -> Result<Vlr> {
+        let vlr = |user_id: &str, record_id, description: &str, data| Vlr {
+            user_id: user_id.to_string(),
+            record_id,
+            description: description.to_string(),
+            data,
+        };
+        match self {
+            KnownVlr::ClassificationLookup(scheme) => scheme.to_vlr(),
+            KnownVlr::TextAreaDescription(text) => Ok(vlr(
+                extra_bytes::USER_ID,
+                TEXT_AREA_DESCRIPTION_RECORD_ID,
+                "Text Area Description",
+                data_from_text(text),
+            )),
+            KnownVlr::ExtraBytes(descriptors) => extra_bytes::vlr(descriptors),
+            KnownVlr::Superseded(data) => Ok(vlr(
+                extra_bytes::USER_ID,
+                SUPERSEDED_RECORD_ID,
+                "Superseded",
+                data.clone(),
+            )),
+            KnownVlr::WaveformPacketDescriptor(index, descriptor) => descriptor.to_vlr(*index),
+            KnownVlr::GeoKeyDirectory(directory) => directory.to_vlr(),
+            KnownVlr::GeoDoubleParams(doubles) => {
+                let mut data = Vec::with_capacity(doubles.len() * 8);
+                for &n in doubles {
+                    data.write_f64::<LittleEndian>(n)?;
+                }
+                Ok(vlr(
+                    crs::USER_ID,
+                    crs::GEO_DOUBLE_PARAMS_RECORD_ID,
+                    "GeoTiff GeoDoubleParamsTag",
+                    data,
+                ))
+            }
+            KnownVlr::GeoAsciiParams(ascii) => Ok(vlr(
+                crs::USER_ID,
+                crs::GEO_ASCII_PARAMS_RECORD_ID,
+                "GeoTiff GeoAsciiParamsTag",
+                data_from_text(ascii),
+            )),
+            KnownVlr::Wkt(wkt) => Ok(vlr(
+                crs::USER_ID,
+                crs::WKT_RECORD_ID,
+                "OGC Coordinate System WKT",
+                data_from_text(wkt),
+            )),
+            #[cfg(feature = "laz")]
+            KnownVlr::Laszip(laz_vlr) => {
+                let mut data = Vec::new();
+                laz_vlr.write_to(&mut data)?;
+                Ok(vlr(
+                    LASZIP_USER_ID,
+                    LASZIP_RECORD_ID,
+                    laz::LazVlr::DESCRIPTION,
+                    data,
+                ))
+            }
+        }
+    }
+}
+
+fn text_from_data(data: &[u8]) -> Result<String> {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    Ok(std::str::from_utf8(&data[..end])?.to_string())
+}
+
+fn data_from_text(text: &str) -> Vec<u8> {
+    let mut data = text.as_bytes().to_vec();
+    data.push(0);
+    data
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,7 +397,6 @@ mod tests {
 
     #[test]
     fn too_long() {
-        use std::u16;
         let data = vec![0; u16::MAX as usize + 1];
         let vlr = Vlr {
             data,
@@ -216,4 +433,76 @@ mod tests {
             vlr.description
         );
     }
+
+    #[test]
+    fn known_roundtrip() {
+        let mut scheme = ClassificationScheme::default();
+        scheme.set_name(64, "Power Line").unwrap();
+        let known_vlrs = [
+            KnownVlr::ClassificationLookup(scheme),
+            KnownVlr::TextAreaDescription("a description".to_string()),
+            KnownVlr::ExtraBytes(vec![Descriptor::new("height", extra_bytes::DataType::F32)]),
+            KnownVlr::Superseded(vec![1, 2, 3]),
+            KnownVlr::WaveformPacketDescriptor(2, WaveformPacketDescriptor::default()),
+            KnownVlr::GeoKeyDirectory(
+                GeoKeyDirectory::from_vlr(
+                    &crs::GeoKeys::from_epsg(32610).unwrap().to_vlrs().unwrap()[0],
+                )
+                .unwrap(),
+            ),
+            KnownVlr::GeoDoubleParams(vec![1., 2.5]),
+            KnownVlr::GeoAsciiParams("NAD83|".to_string()),
+            KnownVlr::Wkt("GEOGCS[\"WGS 84\"]".to_string()),
+        ];
+        for known in known_vlrs {
+            let vlr = known.to_vlr().unwrap();
+            assert_eq!(Some(known), vlr.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_autzen() {
+        let reader = crate::Reader::from_path("tests/data/autzen.las").unwrap();
+        let header = reader.header();
+        for vlr in header.all_vlrs().filter(|vlr| vlr.user_id == crs::USER_ID) {
+            let known = vlr.parse().unwrap().unwrap();
+            assert_eq!(vlr.data, known.to_vlr().unwrap().data);
+        }
+    }
+
+    #[test]
+    fn unknown() {
+        let vlr = Vlr {
+            user_id: "LASF_Spec".to_string(),
+            record_id: 42,
+            ..Default::default()
+        };
+        assert_eq!(None, vlr.parse().unwrap());
+    }
+
+    #[test]
+    fn malformed() {
+        let vlr = Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 34735,
+            data: vec![1, 0],
+            ..Default::default()
+        };
+        assert!(vlr.parse().is_err());
+        let vlr = Vlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 34736,
+            data: vec![0; 12],
+            ..Default::default()
+        };
+        assert!(matches!(vlr.parse(), Err(Error::InvalidGeoKeys(_))));
+        let vlr = Vlr {
+            data: vec![0; 16],
+            ..vlr
+        };
+        assert_eq!(
+            Some(KnownVlr::GeoDoubleParams(vec![0., 0.])),
+            vlr.parse().unwrap()
+        );
+    }
 }
@@ -0,0 +1,686 @@
+//! Read and write full waveform data.
+//!
+//! Point formats 4, 5, 9, and 10 can point into a waveform data packet, which holds the digitized
+//! samples of the laser pulse that produced the point. Each point's
+//! [Waveform](crate::raw::point::Waveform) names a [WaveformPacketDescriptor], stored as a
+//! `LASF_Spec` vlr with a record id between 100 and 354, that describes how the samples are
+//! encoded. The packets themselves live either inside the las file, in a single evlr with record
+//! id 65535, or in an external `.wdp` file next to it, as recorded by
+//! [Header::waveform_storage].
+//!
+//! Write packets with [Writer::write_point_with_waveform](crate::Writer::write_point_with_waveform),
+//! which stores them inside the las file, and read them back with a [WaveformReader]:
+//!
+//! ```
+//! use las::{
+//!     point::Format,
+//!     raw::point::Waveform,
+//!     waveform::{WaveformPacketDescriptor, WaveformReader},
+//!     Builder, Point, Reader, Writer,
+//! };
+//! use std::io::Cursor;
+//!
+//! let mut builder = Builder::from((1, 4));
+//! builder.point_format = Format::new(4).unwrap();
+//! let descriptor = WaveformPacketDescriptor {
+//!     bits_per_sample: 8,
+//!     number_of_samples: 3,
+//!     ..Default::default()
+//! };
+//! builder.vlrs.push(descriptor.to_vlr(1).unwrap());
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+//! let mut point = Point::default();
+//! point.gps_time = Some(0.);
+//! point.waveform = Some(Waveform { wave_packet_descriptor_index: 1, ..Default::default() });
+//! writer.write_point_with_waveform(point, &[1, 2, 3]).unwrap();
+//!
+//! let mut cursor = writer.into_inner().unwrap();
+//! let mut reader = Reader::new(cursor.clone()).unwrap();
+//! let header = reader.header().clone();
+//! let point = reader.read_point().unwrap().unwrap();
+//! let mut waveform_reader = WaveformReader::new(&mut cursor, &header).unwrap();
+//! assert_eq!(vec![1, 2, 3], waveform_reader.read_samples(&point).unwrap().unwrap());
+//! ```
+
+use crate::{raw::vlr::RecordLength, Error, Header, Point, Result, Vlr};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The user id of waveform packet descriptor and waveform data packet vlrs.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of the evlr that holds waveform data packets inside a las file.
+pub const DATA_PACKETS_RECORD_ID: u16 = 65535;
+
+/// The record id of the first waveform packet descriptor, for index 1.
+///
+/// Descriptor index `i` is stored with record id `i + 99`.
+pub const FIRST_DESCRIPTOR_RECORD_ID: u16 = 100;
+
+/// The length of an evlr header, which comes before the first waveform data packet.
+///
+/// Waveform byte offsets are relative to the start of this header, so the first packet is at
+/// offset 60.
+pub const DATA_PACKETS_HEADER_LEN: u64 = 60;
+
+const DESCRIPTOR_LEN: usize = 26;
+
+/// Where a file's waveform data packets are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WaveformStorage {
+    /// The packets are in the las file, starting at
+    /// [Header::start_of_waveform_data_packet_record].
+    Internal,
+
+    /// The packets are in an auxiliary file with the same name as the las file and a `.wdp`
+    /// extension.
+    External,
+}
+
+/// Describes how the samples of a waveform data packet are encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaveformPacketDescriptor {
+    /// The number of bits per sample, from 2 to 32.
+    ///
+    /// Only whole bytes, i.e. 8, 16, 24, or 32 bits, can be read and written.
+    pub bits_per_sample: u8,
+
+    /// The compression type, where zero means uncompressed.
+    ///
+    /// Compressed packets can't be read or written.
+    pub compression_type: u8,
+
+    /// The number of samples in each packet.
+    pub number_of_samples: u32,
+
+    /// The time between samples, in picoseconds.
+    pub temporal_sample_spacing: u32,
+
+    /// The gain used to convert a sample to volts.
+    pub digitizer_gain: f64,
+
+    /// The offset used to convert a sample to volts.
+    pub digitizer_offset: f64,
+}
+
+/// Reads the waveform samples of points.
+#[derive(Debug)]
+pub struct WaveformReader<R: Read + Seek> {
+    read: R,
+    start: u64,
+    descriptors: HashMap<u8, WaveformPacketDescriptor>,
+}
+
+impl WaveformPacketDescriptor {
+    /// Returns the descriptor index and descriptor stored in a vlr, or `None` if the vlr isn't a
+    /// waveform packet descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    ///
+    /// let descriptor = WaveformPacketDescriptor { bits_per_sample: 8, ..Default::default() };
+    /// let vlr = descriptor.to_vlr(3).unwrap();
+    /// assert_eq!(102, vlr.record_id);
+    /// assert_eq!(
+    ///     Some((3, descriptor)),
+    ///     WaveformPacketDescriptor::from_vlr(&vlr).unwrap()
+    /// );
+    /// ```
+    pub fn from_vlr(vlr: &Vlr) -> Result<Option<(u8, WaveformPacketDescriptor)>> {
+        let index = match descriptor_index(vlr) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        if vlr.data.len() != DESCRIPTOR_LEN {
+            return Err(Error::InvalidWaveform(format!(
+                "waveform packet descriptor {} has {} bytes, expected {}",
+                index,
+                vlr.data.len(),
+                DESCRIPTOR_LEN
+            )));
+        }
+        let mut read = vlr.data.as_slice();
+        let descriptor = WaveformPacketDescriptor {
+            bits_per_sample: read.read_u8()?,
+            compression_type: read.read_u8()?,
+            number_of_samples: read.read_u32::<LittleEndian>()?,
+            temporal_sample_spacing: read.read_u32::<LittleEndian>()?,
+            digitizer_gain: read.read_f64::<LittleEndian>()?,
+            digitizer_offset: read.read_f64::<LittleEndian>()?,
+        };
+        Ok(Some((index, descriptor)))
+    }
+
+    /// Creates a vlr for this descriptor at the given index.
+    ///
+    /// Returns an error if the index is zero, which means "no waveform".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    ///
+    /// let descriptor = WaveformPacketDescriptor::default();
+    /// assert_eq!(26, descriptor.to_vlr(1).unwrap().data.len());
+    /// assert!(descriptor.to_vlr(0).is_err());
+    /// ```
+    pub fn to_vlr(&self, index: u8) -> Result<Vlr> {
+        if index == 0 {
+            return Err(Error::InvalidWaveform(
+                "waveform packet descriptor index can't be zero".to_string(),
+            ));
+        }
+        let mut data = Vec::with_capacity(DESCRIPTOR_LEN);
+        data.write_u8(self.bits_per_sample)?;
+        data.write_u8(self.compression_type)?;
+        data.write_u32::<LittleEndian>(self.number_of_samples)?;
+        data.write_u32::<LittleEndian>(self.temporal_sample_spacing)?;
+        data.write_f64::<LittleEndian>(self.digitizer_gain)?;
+        data.write_f64::<LittleEndian>(self.digitizer_offset)?;
+        Ok(Vlr {
+            user_id: USER_ID.to_string(),
+            record_id: FIRST_DESCRIPTOR_RECORD_ID + u16::from(index) - 1,
+            description: "Waveform Packet Descriptor".to_string(),
+            data,
+        })
+    }
+
+    /// Converts a sample to volts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    ///
+    /// let descriptor = WaveformPacketDescriptor {
+    ///     digitizer_gain: 0.5,
+    ///     digitizer_offset: 1.,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(6., descriptor.volts(10));
+    /// ```
+    pub fn volts(&self, sample: u32) -> f64 {
+        self.digitizer_gain * f64::from(sample) + self.digitizer_offset
+    }
+
+    /// Encodes samples into a waveform data packet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    ///
+    /// let descriptor = WaveformPacketDescriptor { bits_per_sample: 16, ..Default::default() };
+    /// assert_eq!(vec![1, 0, 2, 1], descriptor.encode(&[1, 258]).unwrap());
+    /// ```
+    pub fn encode(&self, samples: &[u32]) -> Result<Vec<u8>> {
+        let bytes = self.bytes_per_sample()?;
+        let max = u32::MAX >> (32 - 8 * bytes);
+        let mut data = Vec::with_capacity(samples.len() * bytes);
+        for &sample in samples {
+            if sample > max {
+                return Err(Error::InvalidWaveform(format!(
+                    "sample {} does not fit in {} bits",
+                    sample, self.bits_per_sample
+                )));
+            }
+            data.extend_from_slice(&sample.to_le_bytes()[..bytes]);
+        }
+        Ok(data)
+    }
+
+    /// Decodes the samples of a waveform data packet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::waveform::WaveformPacketDescriptor;
+    ///
+    /// let descriptor = WaveformPacketDescriptor { bits_per_sample: 16, ..Default::default() };
+    /// assert_eq!(vec![1, 258], descriptor.decode(&[1, 0, 2, 1]).unwrap());
+    /// ```
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<u32>> {
+        let bytes = self.bytes_per_sample()?;
+        if !data.len().is_multiple_of(bytes) {
+            return Err(Error::InvalidWaveform(format!(
+                "packet of {} bytes is not a whole number of {} bit samples",
+                data.len(),
+                self.bits_per_sample
+            )));
+        }
+        Ok(data
+            .chunks_exact(bytes)
+            .map(|chunk| {
+                let mut buf = [0; 4];
+                buf[..bytes].copy_from_slice(chunk);
+                u32::from_le_bytes(buf)
+            })
+            .collect())
+    }
+
+    fn bytes_per_sample(&self) -> Result<usize> {
+        if self.compression_type != 0 {
+            return Err(Error::InvalidWaveform(format!(
+                "compression type {} is not supported",
+                self.compression_type
+            )));
+        }
+        match self.bits_per_sample {
+            8 | 16 | 24 | 32 => Ok(usize::from(self.bits_per_sample / 8)),
+            n => Err(Error::InvalidWaveform(format!(
+                "{} bits per sample is not supported",
+                n
+            ))),
+        }
+    }
+}
+
+impl<R: Read + Seek> WaveformReader<R> {
+    /// Creates a new waveform reader.
+    ///
+    /// If the header's waveform data packets are [internal](WaveformStorage::Internal), `read`
+    /// should be the las file itself. If they're [external](WaveformStorage::External), `read`
+    /// should be the `.wdp` file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::WaveformReader, Header};
+    /// use std::io::Cursor;
+    ///
+    /// // This header doesn't have any waveforms.
+    /// assert!(WaveformReader::new(Cursor::new(Vec::new()), &Header::default()).is_err());
+    /// ```
+    pub fn new(read: R, header: &Header) -> Result<WaveformReader<R>> {
+        let start = match header.waveform_storage() {
+            Some(WaveformStorage::Internal) => header
+                .start_of_waveform_data_packet_record()
+                .ok_or_else(|| {
+                    Error::InvalidWaveform(
+                        "the start of the waveform data packet record is not set".to_string(),
+                    )
+                })?,
+            Some(WaveformStorage::External) => 0,
+            None => {
+                return Err(Error::InvalidWaveform(
+                    "the header does not have waveform data packets".to_string(),
+                ))
+            }
+        };
+        Ok(WaveformReader {
+            read,
+            start,
+            descriptors: header.waveform_packet_descriptors()?,
+        })
+    }
+
+    /// Reads the samples of a point's waveform data packet.
+    ///
+    /// Returns `None` if the point doesn't have a waveform, or its descriptor index is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::{WaveformReader, WaveformStorage}, Builder, Point};
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 3));
+    /// builder.waveform_storage = Some(WaveformStorage::External);
+    /// let header = builder.into_header().unwrap();
+    /// let mut reader = WaveformReader::new(Cursor::new(Vec::new()), &header).unwrap();
+    /// assert!(reader.read_samples(&Point::default()).unwrap().is_none());
+    /// ```
+    pub fn read_samples(&mut self, point: &Point) -> Result<Option<Vec<u32>>> {
+        let waveform = match point.waveform {
+            Some(waveform) if waveform.wave_packet_descriptor_index != 0 => waveform,
+            _ => return Ok(None),
+        };
+        let descriptor = self
+            .descriptors
+            .get(&waveform.wave_packet_descriptor_index)
+            .ok_or_else(|| {
+                Error::InvalidWaveform(format!(
+                    "no waveform packet descriptor with index {}",
+                    waveform.wave_packet_descriptor_index
+                ))
+            })?;
+        let _ = self.read.seek(SeekFrom::Start(
+            self.start + waveform.byte_offset_to_waveform_data,
+        ))?;
+        let mut data = vec![0; usize::try_from(waveform.waveform_packet_size_in_bytes)?];
+        self.read.read_exact(&mut data)?;
+        descriptor.decode(&data).map(Some)
+    }
+}
+
+impl WaveformReader<BufReader<File>> {
+    /// Opens the waveform data packets for a las file.
+    ///
+    /// Depending on the header's [WaveformStorage], this opens either the las file itself or the
+    /// `.wdp` file next to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{waveform::WaveformReader, Reader};
+    ///
+    /// // autzen.las doesn't have any waveforms.
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// assert!(WaveformReader::from_path("tests/data/autzen.las", reader.header()).is_err());
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        header: &Header,
+    ) -> Result<WaveformReader<BufReader<File>>> {
+        let path = path.as_ref();
+        let file = if header.waveform_storage() == Some(WaveformStorage::External) {
+            File::open(path.with_extension("wdp"))?
+        } else {
+            File::open(path)?
+        };
+        WaveformReader::new(BufReader::new(file), header)
+    }
+}
+
+impl Header {
+    /// Returns where this header's waveform data packets are stored, if it has any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().waveform_storage().is_none());
+    /// ```
+    pub fn waveform_storage(&self) -> Option<WaveformStorage> {
+        self.waveform_storage
+    }
+
+    /// Returns the offset from the start of the file to the internal waveform data packet
+    /// record, if it has been read or written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().start_of_waveform_data_packet_record().is_none());
+    /// ```
+    pub fn start_of_waveform_data_packet_record(&self) -> Option<u64> {
+        self.start_of_waveform_data_packet_record
+    }
+
+    /// Returns this header's waveform packet descriptors, keyed by their index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Header;
+    /// assert!(Header::default().waveform_packet_descriptors().unwrap().is_empty());
+    /// ```
+    pub fn waveform_packet_descriptors(&self) -> Result<HashMap<u8, WaveformPacketDescriptor>> {
+        let mut descriptors = HashMap::new();
+        for vlr in self.vlrs() {
+            if let Some((index, descriptor)) = WaveformPacketDescriptor::from_vlr(vlr)? {
+                let _ = descriptors.insert(index, descriptor);
+            }
+        }
+        Ok(descriptors)
+    }
+}
+
+/// Returns true if this is the evlr that holds waveform data packets.
+pub(crate) fn is_data_packets_vlr(vlr: &Vlr) -> bool {
+    vlr.user_id == USER_ID && vlr.record_id == DATA_PACKETS_RECORD_ID
+}
+
+/// Creates the evlr that holds waveform data packets.
+pub(crate) fn data_packets_vlr(data: Vec<u8>) -> Vlr {
+    Vlr {
+        user_id: USER_ID.to_string(),
+        record_id: DATA_PACKETS_RECORD_ID,
+        description: "Waveform Data Packets".to_string(),
+        data,
+    }
+}
+
+/// Writes the header of the evlr that holds `len` bytes of waveform data packets.
+pub(crate) fn write_data_packets_header<W: Write>(write: W, len: u64) -> Result<()> {
+    let mut raw_vlr = data_packets_vlr(Vec::new()).into_raw(true)?;
+    raw_vlr.record_length_after_header = RecordLength::Evlr(len);
+    raw_vlr.write_to(write)
+}
+
+fn descriptor_index(vlr: &Vlr) -> Option<u8> {
+    if vlr.user_id == USER_ID && vlr.record_id >= FIRST_DESCRIPTOR_RECORD_ID {
+        u8::try_from(vlr.record_id - FIRST_DESCRIPTOR_RECORD_ID + 1).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Format, Builder, Reader, Writer};
+    use std::io::Cursor;
+
+    fn builder(version: (u8, u8), format: u8) -> Builder {
+        let mut builder = Builder::from(version);
+        builder.point_format = Format::new(format).unwrap();
+        for (index, bits_per_sample) in [(1, 8), (2, 16)] {
+            let descriptor = WaveformPacketDescriptor {
+                bits_per_sample,
+                number_of_samples: 4,
+                temporal_sample_spacing: 1000,
+                digitizer_gain: 0.1,
+                digitizer_offset: -1.,
+                ..Default::default()
+            };
+            builder.vlrs.push(descriptor.to_vlr(index).unwrap());
+        }
+        builder
+    }
+
+    fn point(format: &Format, index: u8) -> Point {
+        let mut point = Point {
+            waveform: Some(crate::raw::point::Waveform {
+                wave_packet_descriptor_index: index,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        point.conform_to(format);
+        point
+    }
+
+    fn roundtrip(version: (u8, u8), format: u8) {
+        let builder = builder(version, format);
+        let format = builder.point_format;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 1), &[1, 2, 3, 255])
+            .unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 2), &[1000, 2000, 3000, 65535])
+            .unwrap();
+        writer.write_point(point(&format, 0)).unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+
+        let mut reader = Reader::new(cursor.clone()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(Some(WaveformStorage::Internal), header.waveform_storage());
+        assert_eq!(2, header.waveform_packet_descriptors().unwrap().len());
+        let points = reader.read_points(3).unwrap();
+        assert_eq!(
+            DATA_PACKETS_HEADER_LEN,
+            points[0].waveform.unwrap().byte_offset_to_waveform_data
+        );
+        let mut waveform_reader = WaveformReader::new(&mut cursor, &header).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 255],
+            waveform_reader.read_samples(&points[0]).unwrap().unwrap()
+        );
+        assert_eq!(
+            vec![1000, 2000, 3000, 65535],
+            waveform_reader.read_samples(&points[1]).unwrap().unwrap()
+        );
+        assert!(waveform_reader.read_samples(&points[2]).unwrap().is_none());
+    }
+
+    #[test]
+    fn roundtrip_las_1_3() {
+        roundtrip((1, 3), 4);
+    }
+
+    #[test]
+    fn roundtrip_las_1_4() {
+        roundtrip((1, 4), 9);
+    }
+
+    #[test]
+    fn packets_and_other_evlrs() {
+        let mut builder = builder((1, 4), 4);
+        let format = builder.point_format;
+        builder.evlrs.push(Vlr {
+            user_id: "test".to_string(),
+            record_id: 42,
+            description: String::new(),
+            data: vec![1, 2, 3],
+        });
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 1), &[4, 5, 6, 7])
+            .unwrap();
+        let mut cursor = writer.into_inner().unwrap();
+        let mut reader = Reader::new(cursor.clone()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(2, header.evlrs().len());
+        assert_eq!(vec![1, 2, 3], header.evlrs()[1].data);
+        let point = reader.read_point().unwrap().unwrap();
+        let mut waveform_reader = WaveformReader::new(&mut cursor, &header).unwrap();
+        assert_eq!(
+            vec![4, 5, 6, 7],
+            waveform_reader.read_samples(&point).unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn external() {
+        let dir = crate::spill::TempDir::new().unwrap();
+        let path = dir.path().join("waveforms.las");
+        let mut builder = builder((1, 3), 4);
+        builder.waveform_storage = Some(WaveformStorage::External);
+        let format = builder.point_format;
+        let mut writer = Writer::from_path(&path, builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 1), &[1, 2, 3, 255])
+            .unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 2), &[1000, 2000, 3000, 65535])
+            .unwrap();
+        writer.close().unwrap();
+
+        let mut reader = Reader::from_path(&path).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(Some(WaveformStorage::External), header.waveform_storage());
+        assert!(header.evlrs().is_empty());
+        let points = reader.read_points(2).unwrap();
+        let mut waveform_reader = WaveformReader::from_path(&path, &header).unwrap();
+        assert_eq!(
+            vec![1, 2, 3, 255],
+            waveform_reader.read_samples(&points[0]).unwrap().unwrap()
+        );
+        assert_eq!(
+            vec![1000, 2000, 3000, 65535],
+            waveform_reader.read_samples(&points[1]).unwrap().unwrap()
+        );
+        let wdp = std::fs::read(path.with_extension("wdp")).unwrap();
+        assert_eq!(DATA_PACKETS_HEADER_LEN + 4 + 8, wdp.len() as u64);
+        assert_eq!(12, u64::from_le_bytes(wdp[20..28].try_into().unwrap()));
+    }
+
+    fn copy(version: (u8, u8), format: u8) {
+        let builder = builder(version, format);
+        let format = builder.point_format;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 1), &[4, 5, 6, 7])
+            .unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let rebuilt = Builder::from(reader.header().clone())
+            .into_header()
+            .unwrap();
+        assert_eq!(1, rebuilt.evlrs().len());
+        let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        for point in reader.points() {
+            writer.write_point(point.unwrap()).unwrap();
+        }
+        let mut cursor = writer.into_inner().unwrap();
+        let mut reader = Reader::new(cursor.clone()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(1, header.evlrs().len());
+        let point = reader.read_point().unwrap().unwrap();
+        let mut waveform_reader = WaveformReader::new(&mut cursor, &header).unwrap();
+        assert_eq!(
+            vec![4, 5, 6, 7],
+            waveform_reader.read_samples(&point).unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn copy_keeps_packets_las_1_3() {
+        copy((1, 3), 4);
+    }
+
+    #[test]
+    fn copy_keeps_packets_las_1_4() {
+        copy((1, 4), 4);
+    }
+
+    #[test]
+    fn copy_without_packets() {
+        let builder = builder((1, 3), 4);
+        let format = builder.point_format;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        writer
+            .write_point_with_waveform(point(&format, 1), &[4, 5, 6, 7])
+            .unwrap();
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let mut header = reader.header().clone();
+        let _ = header.take_waveform_data_packets();
+        assert!(Writer::new(Cursor::new(Vec::new()), header).is_err());
+    }
+
+    #[test]
+    fn sample_too_large() {
+        let builder = builder((1, 4), 4);
+        let format = builder.point_format;
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        assert!(writer
+            .write_point_with_waveform(point(&format, 1), &[256])
+            .is_err());
+        assert!(writer
+            .write_point_with_waveform(point(&format, 3), &[1])
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_descriptor() {
+        let mut vlr = WaveformPacketDescriptor::default().to_vlr(1).unwrap();
+        let _ = vlr.data.pop();
+        assert!(WaveformPacketDescriptor::from_vlr(&vlr).is_err());
+    }
+}
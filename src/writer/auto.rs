@@ -0,0 +1,155 @@
+use super::WritePoint;
+use crate::{Header, Point, Result, Vector};
+use std::io::Write;
+
+/// Buffers points until the writer is done, then fits the transforms to their bounds.
+pub(crate) struct PointWriter<W: Write> {
+    inner: Box<dyn WritePoint<W> + Send>,
+    precision: Vector<f64>,
+    points: Vec<Point>,
+}
+
+impl<W: Write> PointWriter<W> {
+    pub(crate) fn new(inner: Box<dyn WritePoint<W> + Send>, precision: Vector<f64>) -> Self {
+        PointWriter {
+            inner,
+            precision,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> WritePoint<W> for PointWriter<W> {
+    fn write_point(&mut self, point: Point) -> Result<()> {
+        self.inner.header_mut().add_point(&point);
+        self.points.push(point);
+        Ok(())
+    }
+
+    fn into_inner(self: Box<Self>) -> W {
+        self.inner.into_inner()
+    }
+
+    fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    fn header(&self) -> &Header {
+        self.inner.header()
+    }
+
+    fn header_mut(&mut self) -> &mut Header {
+        self.inner.header_mut()
+    }
+
+    fn done(&mut self) -> Result<()> {
+        let header = self.inner.header_mut();
+        *header.transforms_mut() = header.bounds().fit_transforms(&self.precision)?;
+        // The inner writer counts the points again as they're written.
+        header.clear();
+        for point in self.points.drain(..) {
+            self.inner.write_point(point)?;
+        }
+        self.inner.done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builder, Point, Reader, TransformPolicy, Vector, Writer};
+    use std::io::Cursor;
+
+    fn builder() -> Builder {
+        Builder::from((1, 4))
+    }
+
+    fn writer(builder: Builder) -> Writer<Cursor<Vec<u8>>> {
+        let policy = TransformPolicy::Auto {
+            precision: Vector {
+                x: 0.001,
+                y: 0.001,
+                z: 0.001,
+            },
+        };
+        Writer::with_transform_policy(
+            Cursor::new(Vec::new()),
+            builder.into_header().unwrap(),
+            policy,
+        )
+        .unwrap()
+    }
+
+    fn utm_points() -> Vec<Point> {
+        (0..10)
+            .map(|i| Point {
+                x: 500_000. + f64::from(i),
+                y: 5_000_000. + f64::from(i),
+                z: 100.123,
+                return_number: 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn roundtrip(builder: Builder) {
+        let mut writer = writer(builder);
+        for point in utm_points() {
+            writer.write_point(point).unwrap();
+        }
+        assert_eq!(10, writer.header().number_of_points());
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let transforms = *reader.header().transforms();
+        assert_eq!(500_004.5_f64.round(), transforms.x.offset);
+        assert_eq!(5_000_004.5_f64.round(), transforms.y.offset);
+        assert_eq!(0.001, transforms.y.scale);
+        assert_eq!(10, reader.header().number_of_points());
+        assert_eq!(Some(10), reader.header().number_of_points_by_return(1));
+        for (expected, actual) in utm_points().into_iter().zip(reader.points()) {
+            let actual = actual.unwrap();
+            assert_eq!(expected.x, actual.x);
+            assert_eq!(expected.y, actual.y);
+            assert!((expected.z - actual.z).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fixed_overflows() {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+        assert!(writer.write_point(utm_points().remove(0)).is_err());
+    }
+
+    #[test]
+    fn auto() {
+        roundtrip(builder());
+    }
+
+    #[cfg(feature = "laz")]
+    #[test]
+    fn auto_laz() {
+        let mut builder = builder();
+        builder.point_format.is_compressed = true;
+        roundtrip(builder);
+    }
+
+    #[test]
+    fn auto_no_points() {
+        let writer = writer(builder());
+        let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(0, reader.header().number_of_points());
+    }
+
+    #[test]
+    fn invalid_precision() {
+        let policy = TransformPolicy::Auto {
+            precision: Vector {
+                x: 0.01,
+                y: -0.01,
+                z: 0.01,
+            },
+        };
+        assert!(matches!(
+            Writer::with_transform_policy(Cursor::new(Vec::new()), Default::default(), policy),
+            Err(crate::Error::InvalidPrecision(_))
+        ));
+    }
+}
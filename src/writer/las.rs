@@ -1,4 +1,4 @@
-use super::WritePoint;
+use super::{verify_coordinates, WritePoint};
 use crate::{Header, Point, Result};
 use std::io::{Seek, Write};
 
@@ -15,6 +15,8 @@ impl<W: Write + Seek> PointWriter<W> {
 
 impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<W> {
     fn write_point(&mut self, point: Point) -> Result<()> {
+        // Check the coordinates first so a point that can't be stored isn't counted.
+        verify_coordinates(&point, self.header.transforms())?;
         self.header.add_point(&point);
         point
             .into_raw(self.header.transforms())
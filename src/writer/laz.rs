@@ -1,4 +1,4 @@
-use super::WritePoint;
+use super::{verify_coordinates, WritePoint};
 use crate::{Error, Header, Point, Result};
 use ::laz::LasZipCompressor;
 use std::io::{Cursor, Seek, Write};
@@ -25,6 +25,8 @@ impl<'a, W: Write + Seek + Send> PointWriter<'a, W> {
 
 impl<W: Write + Seek + Send> WritePoint<W> for PointWriter<'_, W> {
     fn write_point(&mut self, point: Point) -> Result<()> {
+        // Check the coordinates first so a point that can't be stored isn't counted.
+        verify_coordinates(&point, self.header.transforms())?;
         self.header.add_point(&point);
         self.buffer.set_position(0);
         point
@@ -66,11 +68,12 @@ mod tests {
 
     #[test]
     fn evlr() {
-        let mut vlr = Vlr::default();
-        vlr.user_id = "@gadomski".to_string();
-        vlr.record_id = 42;
-        vlr.description = "A great vlr".to_string();
-        vlr.data = b"some data".to_vec();
+        let vlr = Vlr {
+            user_id: "@gadomski".to_string(),
+            record_id: 42,
+            description: "A great vlr".to_string(),
+            data: b"some data".to_vec(),
+        };
         let mut builder = Builder::default();
         builder.version.minor = 4;
         builder.point_format.is_compressed = true;
@@ -79,8 +82,10 @@ mod tests {
         let cursor = Cursor::new(Vec::new());
         let mut writer = Writer::new(cursor, header).unwrap();
         for i in 0..5 {
-            let mut point = Point::default();
-            point.return_number = i;
+            let point = Point {
+                return_number: i,
+                ..Default::default()
+            };
             writer.write_point(point).unwrap();
         }
         let cursor = writer.into_inner().unwrap();
@@ -31,18 +31,30 @@
 //! assert!(writer.write(point).is_err()); // the point's color would be lost
 //! ```
 
+mod auto;
 mod las;
 #[cfg(feature = "laz")]
 mod laz;
-
-use crate::{Error, Header, Point, Result};
+mod reproject;
+
+use crate::{
+    feature::Evlrs,
+    reproject::CoordinateTransform,
+    spill::Spill,
+    waveform::{self, WaveformPacketDescriptor, WaveformStorage},
+    Error, Header, Point, Result, Transform, TransformPolicy, Vector,
+};
 use std::{
+    collections::HashMap,
     fmt::Debug,
     fs::File,
-    io::{BufWriter, Cursor, Seek, SeekFrom},
-    path::Path,
+    io::{BufWriter, Cursor, Seek, SeekFrom, Write as _},
+    path::{Path, PathBuf},
 };
 
+/// The number of bytes of waveform data packets held in memory before they're spilled to disk.
+const WAVEFORM_SPILL_LIMIT: usize = 16 * 1024 * 1024;
+
 trait WritePoint<W: std::io::Write>: Send {
     fn write_point(&mut self, point: Point) -> Result<()>;
     //https://users.rust-lang.org/t/is-there-a-way-to-move-a-trait-object/707
@@ -53,6 +65,23 @@ trait WritePoint<W: std::io::Write>: Send {
     fn done(&mut self) -> Result<()>;
 }
 
+/// Checks that the point's coordinates can be stored with these transforms.
+fn verify_coordinates(point: &Point, transforms: &Vector<Transform>) -> Result<()> {
+    let _ = transforms.x.inverse(point.x)?;
+    let _ = transforms.y.inverse(point.y)?;
+    let _ = transforms.z.inverse(point.z)?;
+    Ok(())
+}
+
+/// Where waveform data packets are written until the writer is closed.
+enum WaveformData {
+    /// Spilled to temporary files, then copied into the las file after the points.
+    Internal(Spill<()>),
+
+    /// Written to a `.wdp` file, after a placeholder evlr header.
+    External(BufWriter<File>),
+}
+
 struct ClosedPointWriter;
 
 impl<W: std::io::Write> WritePoint<W> for ClosedPointWriter {
@@ -129,6 +158,10 @@ pub struct Writer<W: 'static + std::io::Write + Seek + Send> {
     closed: bool,
     start: u64,
     point_writer: Box<dyn WritePoint<W> + Send>,
+    waveform_data: Option<WaveformData>,
+    waveform_data_len: u64,
+    waveform_data_path: Option<PathBuf>,
+    waveform_packet_descriptors: Option<HashMap<u8, WaveformPacketDescriptor>>,
 }
 
 impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
@@ -137,6 +170,9 @@ impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
     /// The header that is passed in will have various fields zero'd, e.g. bounds, number of
     /// points, etc.
     ///
+    /// Returns [Error::WktRequired] if the header's point format is 6 through 10 and it has
+    /// GeoTIFF keys but no WKT. Headers without a coordinate reference system are allowed.
+    ///
     /// # Examples
     ///
     /// ```
@@ -145,31 +181,130 @@ impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
     /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default());
     /// ```
     pub fn new(mut write: W, mut header: Header) -> Result<Writer<W>> {
+        header.verify_wkt()?;
         let start = write.stream_position()?;
+        let packets = header.take_waveform_data_packets();
+        if packets.is_empty()
+            && header.waveform_storage == Some(WaveformStorage::Internal)
+            && header.start_of_waveform_data_packet_record.is_some()
+        {
+            // The points would point to waveforms that aren't written.
+            return Err(Error::InvalidWaveform(
+                "the header has internal waveforms but no waveform data packets".to_string(),
+            ));
+        }
+        header.start_of_waveform_data_packet_record = None;
+        if header.waveform_storage == Some(WaveformStorage::Internal) {
+            header.waveform_storage = None;
+        }
+        let waveform_data = if packets.is_empty() {
+            None
+        } else {
+            let mut spill = Spill::new(WAVEFORM_SPILL_LIMIT)?;
+            spill.write((), &packets)?;
+            Some(WaveformData::Internal(spill))
+        };
         header.clear();
-        if header.point_format().is_compressed {
+        let point_writer: Box<dyn WritePoint<W> + Send> = if header.point_format().is_compressed {
             #[cfg(feature = "laz")]
             {
                 header.add_laz_vlr()?;
                 header.write_to(&mut write)?;
-                Ok(Writer {
-                    closed: false,
-                    start,
-                    point_writer: Box::new(laz::PointWriter::new(write, header)?),
-                })
+                Box::new(laz::PointWriter::new(write, header)?)
             }
             #[cfg(not(feature = "laz"))]
             {
-                Err(Error::LaszipNotEnabled)
+                return Err(Error::LaszipNotEnabled);
             }
         } else {
             header.write_to(&mut write)?;
-            Ok(Writer {
-                closed: false,
-                start,
-                point_writer: Box::new(las::PointWriter::new(write, header)),
-            })
+            Box::new(las::PointWriter::new(write, header))
+        };
+        Ok(Writer {
+            closed: false,
+            start,
+            point_writer,
+            waveform_data,
+            waveform_data_len: packets.len() as u64,
+            waveform_data_path: None,
+            waveform_packet_descriptors: None,
+        })
+    }
+
+    /// Creates a new writer that chooses its transforms with the given policy.
+    ///
+    /// With [TransformPolicy::Fixed] this is the same as [Writer::new]. With
+    /// [TransformPolicy::Auto], the header's transforms are replaced by ones fit to the written
+    /// points when the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Point, TransformPolicy, Vector, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let policy = TransformPolicy::Auto { precision: Vector { x: 0.01, y: 0.01, z: 0.01 } };
+    /// let mut writer =
+    ///     Writer::with_transform_policy(Cursor::new(Vec::new()), Default::default(), policy)
+    ///         .unwrap();
+    /// writer.write_point(Point { y: 5_000_000., ..Default::default() }).unwrap();
+    /// writer.close().unwrap();
+    /// assert_eq!(0.01, writer.header().transforms().y.scale);
+    /// ```
+    pub fn with_transform_policy(
+        write: W,
+        header: Header,
+        transform_policy: TransformPolicy,
+    ) -> Result<Writer<W>> {
+        transform_policy.verify()?;
+        let mut writer = Writer::new(write, header)?;
+        if let TransformPolicy::Auto { precision } = transform_policy {
+            let point_writer =
+                std::mem::replace(&mut writer.point_writer, Box::new(ClosedPointWriter));
+            writer.point_writer = Box::new(auto::PointWriter::new(point_writer, precision));
         }
+        Ok(writer)
+    }
+
+    /// Creates a new writer that transforms points into another coordinate reference system
+    /// before writing them.
+    ///
+    /// The header should describe the points as they're passed to
+    /// [write_point](Writer::write_point). The written header's coordinate reference system vlrs
+    /// and transforms are replaced to match the target coordinate reference system, as described
+    /// in [reproject::header](crate::reproject::header).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{reproject::{Hemisphere, Utm, UtmToGeographic}, Point, Writer};
+    /// use std::io::Cursor;
+    ///
+    /// let utm = Utm::new(10, Hemisphere::North).unwrap();
+    /// let mut writer = Writer::with_coordinate_transform(
+    ///     Cursor::new(Vec::new()),
+    ///     Default::default(),
+    ///     UtmToGeographic(utm),
+    /// )
+    /// .unwrap();
+    /// writer.write_point(Point { x: 500_000., ..Default::default() }).unwrap();
+    /// writer.close().unwrap();
+    /// assert!((writer.header().bounds().min.x + 123.).abs() < 1e-6);
+    /// ```
+    pub fn with_coordinate_transform<T: CoordinateTransform + 'static>(
+        write: W,
+        header: Header,
+        transform: T,
+    ) -> Result<Writer<W>> {
+        let transform_policy = crate::reproject::transform_policy(&header, &transform);
+        let header = crate::reproject::header(&header, &transform)?;
+        let mut writer = Writer::with_transform_policy(write, header, transform_policy)?;
+        let point_writer = std::mem::replace(&mut writer.point_writer, Box::new(ClosedPointWriter));
+        writer.point_writer = Box::new(reproject::PointWriter::new(
+            point_writer,
+            Box::new(transform),
+        ));
+        Ok(writer)
     }
 
     /// Close this writer.
@@ -197,6 +332,25 @@ impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
         self.point_writer
             .header_mut()
             .set_start_of_first_evlr(start_of_first_evlr);
+        let mut data_packets_evlr = false;
+        match self.waveform_data.take() {
+            Some(WaveformData::Internal(mut spill)) => {
+                // The packets are the first evlr, and before las 1.4 they're the only one.
+                let write = self.point_writer.get_mut();
+                waveform::write_data_packets_header(&mut *write, self.waveform_data_len)?;
+                let _ = std::io::copy(&mut spill.take(())?, write)?;
+                let header = self.point_writer.header_mut();
+                header.start_of_waveform_data_packet_record = Some(start_of_first_evlr);
+                header.waveform_storage = Some(WaveformStorage::Internal);
+                data_packets_evlr = header.version().supports::<Evlrs>();
+            }
+            Some(WaveformData::External(mut write)) => {
+                let _ = write.seek(SeekFrom::Start(0))?;
+                waveform::write_data_packets_header(&mut write, self.waveform_data_len)?;
+                write.flush()?;
+            }
+            None => {}
+        }
         let raw_evlrs: Vec<Result<crate::raw::Vlr>> = {
             self.point_writer
                 .header()
@@ -214,10 +368,20 @@ impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
             .point_writer
             .get_mut()
             .seek(SeekFrom::Start(self.start))?;
-        self.header()
-            .clone()
-            .into_raw()
-            .and_then(|raw_header| raw_header.write_to(self.point_writer.get_mut()))?;
+        let mut raw_header = self.header().clone().into_raw()?;
+        if data_packets_evlr {
+            // The data packets evlr was written directly, so the header didn't count it.
+            match raw_header.evlr.as_mut() {
+                Some(evlr) => evlr.number_of_evlrs += 1,
+                None => {
+                    raw_header.evlr = Some(crate::raw::header::Evlr {
+                        start_of_first_evlr,
+                        number_of_evlrs: 1,
+                    })
+                }
+            }
+        }
+        raw_header.write_to(self.point_writer.get_mut())?;
         let _ = self
             .point_writer
             .get_mut()
@@ -262,6 +426,92 @@ impl<W: 'static + std::io::Write + Seek + Send> Writer<W> {
         self.point_writer.write_point(point)
     }
 
+    /// Writes a point and its waveform samples.
+    ///
+    /// The samples are encoded with the waveform packet descriptor named by the point's
+    /// [Waveform](crate::raw::point::Waveform), and the point's byte offset and packet size are
+    /// filled in.
+    ///
+    /// If the header's waveform storage is [external](WaveformStorage::External) and the writer
+    /// was created with [Writer::from_path], packets are written to a `.wdp` file next to the las
+    /// file. Otherwise, they're spilled to temporary files as they're written and copied into the
+    /// las file, after the points, when the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{
+    ///     point::Format,
+    ///     raw::point::Waveform,
+    ///     waveform::WaveformPacketDescriptor,
+    ///     Builder, Point, Writer,
+    /// };
+    /// use std::io::Cursor;
+    ///
+    /// let mut builder = Builder::from((1, 3));
+    /// builder.point_format = Format::new(4).unwrap();
+    /// let descriptor = WaveformPacketDescriptor { bits_per_sample: 8, ..Default::default() };
+    /// builder.vlrs.push(descriptor.to_vlr(1).unwrap());
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// let point = Point {
+    ///     gps_time: Some(0.),
+    ///     waveform: Some(Waveform { wave_packet_descriptor_index: 1, ..Default::default() }),
+    ///     ..Default::default()
+    /// };
+    /// writer.write_point_with_waveform(point, &[1, 2, 3]).unwrap();
+    /// ```
+    pub fn write_point_with_waveform(&mut self, mut point: Point, samples: &[u32]) -> Result<()> {
+        if self.closed {
+            return Err(Error::ClosedWriter);
+        }
+        let descriptors = match self.waveform_packet_descriptors.take() {
+            Some(descriptors) => descriptors,
+            None => self.header().waveform_packet_descriptors()?,
+        };
+        let descriptors = self.waveform_packet_descriptors.insert(descriptors);
+        let waveform = point.waveform.as_mut().ok_or_else(|| {
+            Error::InvalidWaveform("the point does not have waveform attributes".to_string())
+        })?;
+        let data = descriptors
+            .get(&waveform.wave_packet_descriptor_index)
+            .ok_or_else(|| {
+                Error::InvalidWaveform(format!(
+                    "no waveform packet descriptor with index {}",
+                    waveform.wave_packet_descriptor_index
+                ))
+            })?
+            .encode(samples)?;
+        waveform.byte_offset_to_waveform_data =
+            waveform::DATA_PACKETS_HEADER_LEN + self.waveform_data_len;
+        waveform.waveform_packet_size_in_bytes = u32::try_from(data.len())?;
+        self.write_point(point)?;
+        let waveform_data = match self.waveform_data.as_mut() {
+            Some(waveform_data) => waveform_data,
+            None => {
+                let waveform_data = self.new_waveform_data()?;
+                self.waveform_data.insert(waveform_data)
+            }
+        };
+        match waveform_data {
+            WaveformData::Internal(spill) => spill.write((), &data)?,
+            WaveformData::External(write) => write.write_all(&data)?,
+        }
+        self.waveform_data_len += data.len() as u64;
+        Ok(())
+    }
+
+    fn new_waveform_data(&self) -> Result<WaveformData> {
+        match &self.waveform_data_path {
+            Some(path) => {
+                let mut write = BufWriter::new(File::create(path)?);
+                // Rewritten with the packets' length when the writer is closed.
+                waveform::write_data_packets_header(&mut write, 0)?;
+                Ok(WaveformData::External(write))
+            }
+            None => Ok(WaveformData::Internal(Spill::new(WAVEFORM_SPILL_LIMIT)?)),
+        }
+    }
+
     /// Writes a point.
     #[deprecated(since = "0.9.0", note = "Use write_point() instead")]
     pub fn write(&mut self, point: Point) -> Result<()> {
@@ -313,6 +563,9 @@ impl Writer<BufWriter<File>> {
     /// If the "laz" feature is enabled, guesses from the extension if the
     /// data will be written compressed
     ///
+    /// If the header's waveform storage is [external](WaveformStorage::External), waveform data
+    /// packets are written to a file with the same name and a `.wdp` extension.
+    ///
     /// # Examples
     ///
     /// ```
@@ -336,9 +589,14 @@ impl Writer<BufWriter<File>> {
         };
 
         header.point_format_mut().is_compressed = compress;
-        File::create(path)
+        let external = header.waveform_storage() == Some(WaveformStorage::External);
+        let mut writer = File::create(&path)
             .map_err(Error::from)
-            .and_then(|file| Writer::new(BufWriter::new(file), header))
+            .and_then(|file| Writer::new(BufWriter::new(file), header))?;
+        if external {
+            writer.waveform_data_path = Some(path.as_ref().with_extension("wdp"));
+        }
+        Ok(writer)
     }
 }
 
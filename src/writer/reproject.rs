@@ -0,0 +1,48 @@
+use super::WritePoint;
+use crate::{
+    reproject::{self, CoordinateTransform},
+    Header, Point, Result,
+};
+use std::io::Write;
+
+/// Transforms points into another coordinate reference system before they're written.
+pub(crate) struct PointWriter<W: Write> {
+    inner: Box<dyn WritePoint<W> + Send>,
+    transform: Box<dyn CoordinateTransform>,
+}
+
+impl<W: Write> PointWriter<W> {
+    pub(crate) fn new(
+        inner: Box<dyn WritePoint<W> + Send>,
+        transform: Box<dyn CoordinateTransform>,
+    ) -> Self {
+        PointWriter { inner, transform }
+    }
+}
+
+impl<W: Write> WritePoint<W> for PointWriter<W> {
+    fn write_point(&mut self, mut point: Point) -> Result<()> {
+        reproject::transform_point(&mut point, self.transform.as_ref())?;
+        self.inner.write_point(point)
+    }
+
+    fn into_inner(self: Box<Self>) -> W {
+        self.inner.into_inner()
+    }
+
+    fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    fn header(&self) -> &Header {
+        self.inner.header()
+    }
+
+    fn header_mut(&mut self) -> &mut Header {
+        self.inner.header_mut()
+    }
+
+    fn done(&mut self) -> Result<()> {
+        self.inner.done()
+    }
+}
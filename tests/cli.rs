@@ -0,0 +1,158 @@
+//! Run the `las` binary.
+
+#![cfg(feature = "cli")]
+
+use las::{Reader, Version};
+use std::{path::PathBuf, process::Command};
+
+fn las(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_las"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("las-rs-cli-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn info_text() {
+    let text = las(&["info", "tests/data/autzen.las", "--stats"]);
+    assert!(text.contains("number of points:       106"));
+    assert!(text.contains("LASF_Projection 34735"));
+    assert!(text.contains("Ground"));
+}
+
+#[test]
+fn info_json() {
+    let json = las(&[
+        "info",
+        "tests/data/autzen.las",
+        "--format",
+        "json",
+        "--stats",
+    ]);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(106, value["header"]["number_of_points"]);
+    assert_eq!("1.2", value["header"]["version"]);
+    assert_eq!(4, value["header"]["vlrs"].as_array().unwrap().len());
+    assert_eq!(106, value["stats"]["number_of_points"]);
+    assert!(value["classifications_by_name"]["Ground"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn info_missing_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_las"))
+        .args(["info", "tests/data/does-not-exist.las"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn translate_version_and_format() {
+    let path = temp_path("translate.laz");
+    let _ = las(&[
+        "translate",
+        "tests/data/autzen.las",
+        path.to_str().unwrap(),
+        "--version",
+        "1.4",
+        "--point-format",
+        "6",
+        "--wkt",
+        "PROJCS[\"NAD83(HARN) / Oregon Lambert (ft)\"]",
+        "--keep-classes",
+        "2",
+    ]);
+    let mut reader = Reader::from_path(&path).unwrap();
+    assert_eq!(Version::new(1, 4), reader.header().version());
+    assert_eq!(6, reader.header().point_format().to_u8().unwrap());
+    assert!(reader.header().point_format().is_compressed);
+    assert_eq!(24, reader.header().number_of_points());
+    assert!(reader.header().geo_keys().unwrap().is_none());
+    assert!(reader
+        .header()
+        .wkt()
+        .unwrap()
+        .unwrap()
+        .starts_with("PROJCS"));
+    for point in reader.points() {
+        assert_eq!(2, u8::from(point.unwrap().classification));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn translate_crop_and_scale() {
+    let path = temp_path("crop.las");
+    let _ = las(&[
+        "translate",
+        "tests/data/autzen.las",
+        path.to_str().unwrap(),
+        "--crop",
+        "636000,849000,638000,853000",
+        "--drop-classes",
+        "2",
+        "--scale",
+        "0.001",
+    ]);
+    let mut reader = Reader::from_path(&path).unwrap();
+    assert!(!reader.header().point_format().is_compressed);
+    assert_eq!(0.001, reader.header().transforms().x.scale);
+    for point in reader.points() {
+        let point = point.unwrap();
+        assert!(point.x >= 636000. && point.x <= 638000.);
+        assert!(point.y >= 849000. && point.y <= 853000.);
+        assert_ne!(2, u8::from(point.classification));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn translate_set() {
+    let path = temp_path("set.las");
+    let _ = las(&[
+        "translate",
+        "tests/data/autzen.las",
+        path.to_str().unwrap(),
+        "--set",
+        "Classification = LowPoint where Classification == Ground && Z < 420",
+        "--set",
+        "Withheld = 1 where Classification == LowPoint",
+    ]);
+    let mut reader = Reader::from_path(&path).unwrap();
+    let mut n = 0;
+    for point in reader.points() {
+        let point = point.unwrap();
+        assert_eq!(point.is_withheld, u8::from(point.classification) == 7);
+        if point.is_withheld {
+            assert!(point.z < 420.);
+            n += 1;
+        }
+    }
+    assert!(n > 0);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn translate_invalid_crop() {
+    let output = Command::new(env!("CARGO_BIN_EXE_las"))
+        .args([
+            "translate",
+            "tests/data/autzen.las",
+            temp_path("invalid.las").to_str().unwrap(),
+            "--crop",
+            "1,2,3",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}